@@ -0,0 +1,138 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro_crate::{crate_name, FoundCrate};
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, Ident, LitStr, Path, Type};
+
+/// The `task_type` of a task, either a built-in `TaskType` or a custom one.
+enum TaskTypeArg {
+    Builtin(Ident),
+    Custom(LitStr),
+}
+
+/// Implements `TaskDefinition` for a task payload, which gives it
+/// `register()` for the `TaskRegistry` and `into_request()` for managers.
+///
+/// # Attributes
+///
+/// * `task_type` - A `TaskType` variant ex: `Role`, or a string for `TaskType::Custom`.
+/// * `action` - The `task_action` the task answers to.
+/// * `db` - The database the task runs against, `PostgresDatabase` when missing.
+/// * `idempotent` - Marks the task as safe to retry after a transient failure.
+/// * `read_only` - Marks the task as leaving the database untouched, it keeps running while in maintenance.
+/// * `crate` - The path of the `ark` crate ex: `crate = "my_ark"`, found from `Cargo.toml` when missing.
+///
+/// # Examples
+/// ```ignore
+/// #[derive(Serialize, Deserialize, Task)]
+/// #[task(task_type = Role, action = "role_read", idempotent, read_only)]
+/// pub struct RoleReadTask {
+///     pub identifier: String,
+/// }
+///
+/// RoleReadTask::register();
+/// let request = RoleReadTask { identifier }.into_request();
+/// ```
+#[proc_macro_derive(Task, attributes(task))]
+pub fn derive_task(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_task(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_task(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut task_type: Option<TaskTypeArg> = None;
+    let mut action: Option<LitStr> = None;
+    let mut db: Option<Type> = None;
+    let mut idempotent = false;
+    let mut read_only = false;
+    let mut krate: Option<Path> = None;
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("task")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("task_type") {
+                let value = meta.value()?;
+                task_type = Some(if value.peek(LitStr) {
+                    TaskTypeArg::Custom(value.parse()?)
+                } else {
+                    TaskTypeArg::Builtin(value.parse()?)
+                });
+                Ok(())
+            } else if meta.path.is_ident("action") {
+                action = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("db") {
+                db = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("idempotent") {
+                idempotent = true;
+                Ok(())
+            } else if meta.path.is_ident("read_only") {
+                read_only = true;
+                Ok(())
+            } else if meta.path.is_ident("crate") {
+                krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected `task_type`, `action`, `db`, `idempotent`, `read_only` or `crate`",
+                ))
+            }
+        })?;
+    }
+
+    let ark = match krate {
+        Some(krate) => quote!(#krate),
+        None => ark_path(),
+    };
+    let task_type = match task_type {
+        Some(TaskTypeArg::Builtin(variant)) => {
+            quote!(#ark::app::service::task::message::TaskType::#variant)
+        }
+        Some(TaskTypeArg::Custom(name)) => {
+            quote!(#ark::app::service::task::message::TaskType::Custom(String::from(#name)))
+        }
+        None => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "missing `#[task(task_type = ...)]`",
+            ))
+        }
+    };
+    let action = action.ok_or_else(|| {
+        syn::Error::new_spanned(&input.ident, "missing `#[task(action = \"...\")]`")
+    })?;
+    let db = match db {
+        Some(db) => quote!(#db),
+        None => quote!(#ark::app::database::postgres::PostgresDatabase),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ark::app::service::task::registry::TaskDefinition for #name #ty_generics #where_clause {
+            type Database = #db;
+            const ACTION: &'static str = #action;
+            const IDEMPOTENT: bool = #idempotent;
+            const READ_ONLY: bool = #read_only;
+
+            fn task_type() -> #ark::app::service::task::message::TaskType {
+                #task_type
+            }
+        }
+    })
+}
+
+/// The path of the `ark` crate as the crate deriving `Task` depends on it,
+/// ark itself included through its `extern crate self as ark`.
+fn ark_path() -> TokenStream2 {
+    match crate_name("ark") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote!(::#name)
+        }
+        Ok(FoundCrate::Itself) | Err(_) => quote!(::ark),
+    }
+}
@@ -1,9 +1,12 @@
 use std::env;
 
 use oauth2::{
-    basic::BasicClient, AuthUrl, ClientId, ClientSecret, RedirectUrl, RevocationUrl, Scope,
-    TokenUrl,
+    basic::{BasicClient, BasicTokenResponse},
+    reqwest::async_http_client,
+    AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl,
+    RevocationUrl, Scope, StandardRevocableToken, TokenUrl,
 };
+use serde::Deserialize;
 
 struct OAuthConfig {
     client_id: ClientId,
@@ -12,6 +15,7 @@ struct OAuthConfig {
     token_url: Option<TokenUrl>,
     redirect_url: RedirectUrl,
     revocation_url: RevocationUrl,
+    user_info_url: String,
 }
 
 #[derive(Clone)]
@@ -23,12 +27,25 @@ pub struct OAuthCollectionAdapter {
 pub struct OAuthProvider {
     pub name: String,
     pub client: BasicClient,
-    pub scopes: Vec<Scope>
+    pub scopes: Vec<Scope>,
+    pub user_info_url: String,
+}
+
+/// The identity returned by a provider's user info endpoint.
+#[derive(Debug, Deserialize)]
+pub struct OAuthUserInfo {
+    #[serde(alias = "sub")]
+    pub id: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
 }
 
 #[derive(Debug)]
 pub enum OAuthAdapterError {
     UnsupportedProvider,
+    TokenExchangeFailed,
+    UserInfoRequestFailed,
+    TokenRevocationFailed,
 }
 
 impl OAuthConfig {
@@ -58,12 +75,13 @@ impl OAuthConfig {
     /// ```
     fn new(provider: &str) -> Self {
         // Dynamic generation of environment variable keys based on the provider
-        let id = format!("{0}_{1}", provider, "CLIENT_ID".to_string());
-        let secret = format!("{0}_{1}", provider, "CLIENT_SECRET".to_string());
-        let auth_url = format!("{0}_{1}", provider, "AUTH_URL".to_string());
-        let token_url = format!("{0}_{1}", provider, "TOKEN_URL".to_string());
+        let id = format!("{0}_{1}", provider, "CLIENT_ID");
+        let secret = format!("{0}_{1}", provider, "CLIENT_SECRET");
+        let auth_url = format!("{0}_{1}", provider, "AUTH_URL");
+        let token_url = format!("{0}_{1}", provider, "TOKEN_URL");
         let redirect_uri = "OAUTH2_REDIRECT_URL".to_string();
-        let revocation_uri = format!("{0}_{1}", provider, "REVOCATION_URL".to_string());
+        let revocation_uri = format!("{0}_{1}", provider, "REVOCATION_URL");
+        let user_info_uri = format!("{0}_{1}", provider, "USER_INFO_URL");
 
         // Constructing the configuration structure with environment variables
         Self {
@@ -74,6 +92,7 @@ impl OAuthConfig {
             redirect_url: RedirectUrl::new(env::var(&redirect_uri).expect(&redirect_uri)).unwrap(),
             revocation_url: RevocationUrl::new(env::var(&revocation_uri).expect(&revocation_uri))
                 .unwrap(),
+            user_info_url: env::var(&user_info_uri).expect(&user_info_uri),
         }
     }
 }
@@ -97,11 +116,7 @@ impl OAuthCollectionAdapter {
     /// ```
     pub fn new() -> Self {
         Self {
-            discord: OAuthProvider {
-                name: "discord".to_string(),
-                client: OAuthCollectionAdapter::create_auth_client("discord"),
-                scopes: OAuthCollectionAdapter::create_scopes(&["identify", "email"])
-            },
+            discord: OAuthCollectionAdapter::create_provider("discord", &["identify", "email"]),
         }
     }
 
@@ -118,7 +133,7 @@ impl OAuthCollectionAdapter {
         Err(OAuthAdapterError::UnsupportedProvider)
     }
 
-    /// Creates a simplified version of `BasicClient` for a specified provider.
+    /// Creates an `OAuthProvider` for a specified provider.
     ///
     /// This function acts as a helper to construct `BasicClient` instances with
     /// the necessary configuration for different OAuth providers. It leverages
@@ -127,17 +142,24 @@ impl OAuthCollectionAdapter {
     /// # Arguments
     ///
     /// * `provider` - The provider you would like to use ex: discord, google etc;
-    fn create_auth_client(provider: &str) -> BasicClient {
-        let config = OAuthConfig::new(provider);
-
-        BasicClient::new(
+    /// * `scopes` - The scopes requested from the provider.
+    fn create_provider(provider: &str, scopes: &[&str]) -> OAuthProvider {
+        let config = OAuthConfig::new(&provider.to_uppercase());
+        let client = BasicClient::new(
             config.client_id,
             config.client_secret,
             config.auth_url,
             config.token_url,
         )
         .set_redirect_uri(config.redirect_url)
-        .set_revocation_uri(config.revocation_url)
+        .set_revocation_uri(config.revocation_url);
+
+        OAuthProvider {
+            name: provider.to_string(),
+            client,
+            scopes: OAuthCollectionAdapter::create_scopes(scopes),
+            user_info_url: config.user_info_url,
+        }
     }
 
     /// A better way of adding scopes. More clean.
@@ -155,12 +177,99 @@ impl OAuthCollectionAdapter {
     }
 }
 
+impl OAuthProvider {
+    /// Builds the provider's authorize url along with the csrf token that
+    /// must be checked once the provider redirects back to us.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (url, csrf_token) = auth.discord.authorize_url();
+    /// ```
+    pub fn authorize_url(&self) -> (String, CsrfToken) {
+        let (url, csrf_token) = self
+            .client
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(self.scopes.clone())
+            .url();
+        (url.to_string(), csrf_token)
+    }
+
+    /// Exchanges the authorization code the provider handed back for a token.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The `code` query parameter received on the callback.
+    pub async fn exchange_code(&self, code: &str) -> Result<BasicTokenResponse, OAuthAdapterError> {
+        self.client
+            .exchange_code(AuthorizationCode::new(code.to_string()))
+            .request_async(async_http_client)
+            .await
+            .map_err(|_| OAuthAdapterError::TokenExchangeFailed)
+    }
+
+    /// Retrieves the identity of the owner of the access token.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_token` - The access token returned by `exchange_code`.
+    pub async fn fetch_user_info(
+        &self,
+        access_token: &str,
+    ) -> Result<OAuthUserInfo, OAuthAdapterError> {
+        let response = reqwest::Client::new()
+            .get(&self.user_info_url)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(|_| OAuthAdapterError::UserInfoRequestFailed)?;
+        response
+            .error_for_status()
+            .map_err(|_| OAuthAdapterError::UserInfoRequestFailed)?
+            .json::<OAuthUserInfo>()
+            .await
+            .map_err(|_| OAuthAdapterError::UserInfoRequestFailed)
+    }
+
+    /// Revokes an access token at the provider through its revocation url,
+    /// so the user is no longer authorized there once signed out.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_token` - The access token returned by `exchange_code`.
+    pub async fn revoke_token(&self, access_token: &str) -> Result<(), OAuthAdapterError> {
+        self.client
+            .revoke_token(StandardRevocableToken::AccessToken(AccessToken::new(
+                access_token.to_string(),
+            )))
+            .map_err(|_| OAuthAdapterError::TokenRevocationFailed)?
+            .request_async(async_http_client)
+            .await
+            .map_err(|_| OAuthAdapterError::TokenRevocationFailed)
+    }
+}
+
+impl Default for OAuthCollectionAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl std::fmt::Display for OAuthAdapterError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
             OAuthAdapterError::UnsupportedProvider => {
                 write!(f, "OAuthAdapterError: Unsupported OAuth Provider")
             }
+            OAuthAdapterError::TokenExchangeFailed => {
+                write!(f, "OAuthAdapterError: Failed to exchange the authorization code")
+            }
+            OAuthAdapterError::UserInfoRequestFailed => {
+                write!(f, "OAuthAdapterError: Failed to retrieve the user from the provider")
+            }
+            OAuthAdapterError::TokenRevocationFailed => {
+                write!(f, "OAuthAdapterError: Failed to revoke the token at the provider")
+            }
         }
     }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 use core::fmt;
 use std::{env, sync::Arc};
 
-use axum::{extract::FromRef, Extension, Router};
+use axum::{extract::FromRef, middleware, Extension, Router};
 use tokio::net::TcpListener;
 use tower_cookies::{CookieManagerLayer, Key};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -12,12 +12,30 @@ use super::{
         postgres::{PostgresConfig, PostgresDatabase},
         redis::{RedisConfig, RedisDatabase},
     },
-    platform::iam::{
-        permission::manager::PermissionManager,
-        role::manager::RoleManager,
-        user::manager::UserManager,
+    platform::{
+        dead_letter, health, idempotency,
+        iam::{
+            batch,
+            permission::{self, manager::PermissionManager},
+            role::{self, manager::RoleManager},
+            session,
+            sync::IamCacheSync,
+            user::{self, manager::UserManager},
+        },
+        maintenance::{self, Maintenance},
+        metrics,
+    },
+    service::{
+        cache::manager::CacheManager,
+        task::{
+            dead_letter::{DeadLetterConfig, DeadLetters},
+            idempotency::{Idempotency, IdempotencyConfig},
+            manager::{TaskManager, TaskManagerConfig},
+            recorder::{RecorderConfig, TaskRecorder},
+            schedule::{Scheduler, SchedulerConfig},
+            stream::{RedisStreamConfig, RedisStreamTransport},
+        },
     },
-    service::{cache::manager::CacheManager, task::manager::TaskManager},
 };
 
 static ADDRESS: &str = "0.0.0.0";
@@ -62,6 +80,17 @@ impl ArkServer {
             port: PORT,
             mode: MODE,
             router: Router::new()
+                .merge(health::router())
+                .merge(metrics::router())
+                .merge(session::route::router())
+                .merge(user::route::router())
+                .merge(role::route::router())
+                .merge(permission::route::router())
+                .merge(batch::route::router())
+                .merge(dead_letter::router())
+                .merge(maintenance::router())
+                .layer(middleware::from_fn(idempotency::guard))
+                .layer(middleware::from_fn(maintenance::guard))
                 .layer(Extension(Arc::new(ArkState::default().await)))
                 .layer(CookieManagerLayer::new()),
         }
@@ -82,7 +111,8 @@ impl ArkServer {
     /// ```
     pub async fn run(self, pg: PostgresDatabase, redis: RedisDatabase) {
         let tcp = TcpListener::bind(&self.get_addr()).await.unwrap();
-        println!("[ARK] mode: {}", self.mode.to_string());
+        println!("[ARK] mode: {}", self.mode);
+        Maintenance::share(redis.clone()).await;
         match self.mode {
             ServerMode::Production => {}
             ServerMode::Development => {
@@ -90,10 +120,16 @@ impl ArkServer {
             }
             ServerMode::Maintenance => {
                 Self::enable_tracing();
+                Maintenance::set_enabled(true)
+                    .await
+                    .expect("failed to share the maintenance state");
             }
         }
-        Self::register_listeners(pg, redis).await;
+        IamCacheSync::share(redis.clone()).await;
+        Self::register_listeners(pg, redis.clone()).await;
+        Self::register_transport(redis.clone());
         Self::preload_necessities().await;
+        Self::start_scheduler(redis);
         println!(
             "[ARK] router initialized, now listening on port {}.",
             &self.port
@@ -166,15 +202,75 @@ impl ArkServer {
     ///     register_tasks(pg_database, redis_database).await;
     /// }
     /// ```
-    async fn register_listeners(pg: PostgresDatabase, redis: RedisDatabase) {
-        TaskManager::new(pg, redis.clone()).listen();
+    pub(crate) async fn register_listeners(pg: PostgresDatabase, redis: RedisDatabase) {
+        Self::register_tasks();
+        DeadLetters::enable(redis.clone(), DeadLetterConfig::default());
+        Idempotency::enable(redis.clone(), IdempotencyConfig::default());
+        TaskRecorder::enable(RecorderConfig::default()).expect("TASK_RECORD");
+        TaskManager::new(pg, redis.clone(), TaskManagerConfig::default()).listen();
         CacheManager::new(redis).listen();
     }
 
-    async fn preload_necessities() {
-        PermissionManager::preload_permission_cache().unwrap();
-        RoleManager::preload_role_cache().unwrap();
-        UserManager::preload_user_cache().unwrap();
+    /// Registers the tasks of the iam platform, without the interceptors
+    /// `register_listeners` turns on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ArkServer::register_tasks();
+    /// ```
+    pub(crate) fn register_tasks() {
+        permission::task::register_tasks();
+        role::task::register_tasks();
+        user::task::register_tasks();
+        batch::task::register_tasks();
+        session::task::register_tasks();
+    }
+
+    /// Sends the task types listed in `TASK_REMOTE_TYPES` to `ark-worker`
+    /// processes through Redis Streams when `TASK_TRANSPORT` is `redis`.
+    ///
+    /// # Arguments
+    ///
+    /// * `redis` - An instance of `RedisDatabase` the streams live in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// Self::register_transport(redis_database);
+    /// ```
+    fn register_transport(redis: RedisDatabase) {
+        if !env::var("TASK_TRANSPORT").is_ok_and(|transport| transport.eq("redis")) {
+            return;
+        }
+        let transport = RedisStreamTransport::new(redis, RedisStreamConfig::default());
+        transport.listen();
+        TaskManager::use_transport(transport);
+    }
+
+    /// Defines the scheduled jobs then starts submitting them, a job defined to
+    /// run once per cluster runs on whichever instance wins its fire.
+    ///
+    /// # Arguments
+    ///
+    /// * `redis` - An instance of `RedisDatabase` the job locks live in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// Self::start_scheduler(redis_database);
+    /// ```
+    pub(crate) fn start_scheduler(redis: RedisDatabase) {
+        role::task::register_jobs();
+        session::task::register_jobs();
+        user::task::register_jobs();
+        Scheduler::new(redis, SchedulerConfig::default()).start();
+    }
+
+    pub(crate) async fn preload_necessities() {
+        PermissionManager::preload_permission_cache().await.unwrap();
+        RoleManager::preload_role_cache().await.unwrap();
+        UserManager::preload_user_cache().await.unwrap();
     }
     
 }
@@ -185,6 +281,7 @@ impl ArkServer {
 /// - `Production`: Mode indicating the server is in a live, production environment (value 0).
 /// - `Development`: Mode for development and testing purposes (value 1).
 /// - `Maintenance`: Indicates the server is in maintenance mode, possibly for updates or repairs (value 2).
+#[allow(dead_code)]
 #[derive(Clone, Copy, PartialEq)]
 enum ServerMode {
     Production,
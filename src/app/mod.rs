@@ -1,5 +1,7 @@
 pub mod adapter;
 pub mod database;
 pub mod platform;
+pub mod replay;
 pub mod service;
-pub mod ark;
\ No newline at end of file
+pub mod ark;
+pub mod worker;
\ No newline at end of file
@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    platform::{
+        iam::access::{layer::RequirePermission, ADMIN_PERMISSION},
+        response::{CustomJsonResponse, ErrorJsonResponse},
+    },
+    service::task::dead_letter::{DeadLetter, DeadLetters, ReplayOutcome},
+};
+
+/// How many dead letters are listed when `count` is left out, and at most.
+static DEFAULT_COUNT: usize = 50;
+static MAX_COUNT: usize = 500;
+
+#[derive(Deserialize)]
+pub struct DeadLetterQuery {
+    pub count: Option<usize>,
+    pub before: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplayQuery {
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeadLetterPage {
+    pub dead_letters: Vec<DeadLetter>,
+    /// The `before` of the next page, absent on the last one.
+    pub next: Option<String>,
+}
+
+/// Admin routes over `DeadLetters`, all guarded by `ADMIN_PERMISSION`.
+///
+/// # Routes
+///
+/// * `GET /tasks/dead-letters` - List dead letters newest first ex: `?count=50&before=1700000000000-0`.
+/// * `GET /tasks/dead-letters/:id` - Read a dead letter.
+/// * `DELETE /tasks/dead-letters/:id` - Discard a dead letter.
+/// * `POST /tasks/dead-letters/:id/replay` - Run a dead letter again, discarded once it completes ex: `?force=true`.
+pub fn router() -> Router {
+    Router::new()
+        .route("/tasks/dead-letters", get(list_dead_letters))
+        .route(
+            "/tasks/dead-letters/:id",
+            get(get_dead_letter).delete(discard_dead_letter),
+        )
+        .route("/tasks/dead-letters/:id/replay", post(replay_dead_letter))
+        .route_layer(RequirePermission(ADMIN_PERMISSION))
+}
+
+async fn list_dead_letters(
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<CustomJsonResponse<DeadLetterPage>, ErrorJsonResponse> {
+    let count = query.count.unwrap_or(DEFAULT_COUNT).clamp(1, MAX_COUNT);
+    let dead_letters = DeadLetters::list(count, query.before.as_deref()).await?;
+    let next = match dead_letters.len() {
+        len if len == count => dead_letters
+            .last()
+            .map(|dead_letter| dead_letter.id.clone()),
+        _ => None,
+    };
+    Ok(CustomJsonResponse::new(
+        StatusCode::OK,
+        DeadLetterPage { dead_letters, next },
+    ))
+}
+
+async fn get_dead_letter(
+    Path(id): Path<String>,
+) -> Result<CustomJsonResponse<DeadLetter>, ErrorJsonResponse> {
+    let dead_letter = DeadLetters::get(&id).await?;
+    Ok(CustomJsonResponse::new(StatusCode::OK, dead_letter))
+}
+
+async fn discard_dead_letter(Path(id): Path<String>) -> Result<StatusCode, ErrorJsonResponse> {
+    DeadLetters::discard(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Answers 200 with the outcome of the replay, whether the task completed
+/// or failed again, and 409 for a task that may have run already unless forced
+/// or whose secrets were redacted.
+async fn replay_dead_letter(
+    Path(id): Path<String>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<CustomJsonResponse<ReplayOutcome>, ErrorJsonResponse> {
+    let outcome = DeadLetters::replay(&id, query.force).await?;
+    Ok(CustomJsonResponse::new(StatusCode::OK, outcome))
+}
@@ -0,0 +1,83 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{http::StatusCode, routing::get, Extension, Router};
+use bb8_redis::redis;
+use serde::Serialize;
+use tokio::time::timeout;
+
+use crate::app::{
+    ark::ArkState,
+    database::{postgres::PostgresDatabase, redis::RedisDatabase},
+    platform::response::CustomJsonResponse,
+    service::{cache::manager::CacheManager, task::manager::TaskManager},
+};
+
+/// How long a single readiness check may take before it counts as failed.
+static CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The outcome of every readiness check.
+#[derive(Serialize)]
+pub struct Readiness {
+    pub postgres: bool,
+    pub redis: bool,
+    pub task_listener: bool,
+    pub cache_listener: bool,
+}
+
+impl Readiness {
+    fn is_ready(&self) -> bool {
+        self.postgres && self.redis && self.task_listener && self.cache_listener
+    }
+}
+
+/// Routes used by orchestrators to probe the server, neither requires a session.
+///
+/// # Routes
+///
+/// * `GET /healthz` - Answers as long as the process can serve http.
+/// * `GET /readyz` - Answers 200 when Postgres, Redis and the listeners are usable, 503 otherwise.
+pub fn router() -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+}
+
+async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+async fn readyz(Extension(state): Extension<Arc<ArkState>>) -> CustomJsonResponse<Readiness> {
+    let readiness = Readiness {
+        postgres: check_postgres(&state.postgres).await,
+        redis: check_redis(&state.redis).await,
+        task_listener: TaskManager::is_listening(),
+        cache_listener: CacheManager::is_listening(),
+    };
+    let status_code = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    CustomJsonResponse::new(status_code, readiness)
+}
+
+/// Takes a connection out of the pool and runs `SELECT 1` on it.
+async fn check_postgres(pg: &PostgresDatabase) -> bool {
+    let check = async {
+        let connection = pg.pool.get().await.ok()?;
+        connection.simple_query("SELECT 1").await.ok()
+    };
+    matches!(timeout(CHECK_TIMEOUT, check).await, Ok(Some(_)))
+}
+
+/// Takes a connection out of the pool and sends a `PING` on it.
+async fn check_redis(redis: &RedisDatabase) -> bool {
+    let check = async {
+        let mut connection = redis.pool.get().await.ok()?;
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut *connection)
+            .await
+            .ok()
+    };
+    matches!(timeout(CHECK_TIMEOUT, check).await, Ok(Some(_)))
+}
@@ -0,0 +1,123 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tower::{Layer, Service};
+
+use crate::app::platform::{iam::session::extractor::CurrentUser, response::ErrorJsonResponse};
+
+use super::EffectiveAccess;
+
+/// Only lets callers that hold the permission (id or key) through.
+///
+/// # Examples
+/// ```
+/// Router::new().route(
+///     "/content",
+///     post(publish).route_layer(RequirePermission("content.publish")),
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct RequirePermission(pub &'static str);
+
+/// Only lets callers that hold the role (id or name) through.
+///
+/// # Examples
+/// ```
+/// Router::new().route("/admin", get(dashboard).route_layer(RequireRole("Admin")));
+/// ```
+#[derive(Clone, Copy)]
+pub struct RequireRole(pub &'static str);
+
+#[derive(Clone, Copy)]
+enum AccessRequirement {
+    Permission(&'static str),
+    Role(&'static str),
+}
+
+impl AccessRequirement {
+    fn is_satisfied_by(&self, access: &EffectiveAccess) -> bool {
+        match self {
+            AccessRequirement::Permission(permission) => access.has_permission(permission),
+            AccessRequirement::Role(role) => access.has_role(role),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = AccessGuard<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessGuard {
+            inner,
+            requirement: AccessRequirement::Permission(self.0),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequireRole {
+    type Service = AccessGuard<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessGuard {
+            inner,
+            requirement: AccessRequirement::Role(self.0),
+        }
+    }
+}
+
+/// The service produced by `RequirePermission` and `RequireRole`.
+///
+/// Resolves the `CurrentUser` (401 when there is none), computes the
+/// caller's `EffectiveAccess` and answers with a 403 when the requirement
+/// is not met.
+#[derive(Clone)]
+pub struct AccessGuard<S> {
+    inner: S,
+    requirement: AccessRequirement,
+}
+
+impl<S> Service<Request<Body>> for AccessGuard<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // the clone is not guaranteed to be ready, keep the one that is.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let requirement = self.requirement;
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let current_user = match CurrentUser::from_request_parts(&mut parts, &()).await {
+                Ok(current_user) => current_user,
+                Err(rejection) => return Ok(rejection.into_response()),
+            };
+            let access = EffectiveAccess::resolve(&current_user.user.access);
+            if !requirement.is_satisfied_by(&access) {
+                return Ok(ErrorJsonResponse::new(
+                    StatusCode::FORBIDDEN,
+                    "You do not have access to this resource.",
+                )
+                .into_response());
+            }
+            inner.call(Request::from_parts(parts, body)).await
+        })
+    }
+}
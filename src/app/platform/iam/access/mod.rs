@@ -0,0 +1,223 @@
+use crate::app::service::cache::LocalizedCache;
+
+use super::{
+    permission::{cache::PermissionCache, model::Permission},
+    role::{cache::RoleCache, model::Role},
+    user::model::UserAccessInfo,
+};
+
+pub mod layer;
+
+/// The permission required to use the IAM admin routes.
+pub static ADMIN_PERMISSION: &str = "iam.admin";
+
+/// The roles and permissions a user actually holds.
+///
+/// Permissions are the user's direct permissions plus the permissions of
+/// every role the user has. Identifiers that are no longer inside of the
+/// `RoleCache`/`PermissionCache` are skipped.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct EffectiveAccess {
+    pub roles: Vec<Role>,
+    pub permissions: Vec<Permission>,
+}
+
+impl EffectiveAccess {
+    /// Resolves the ids stored inside of `UserAccessInfo` against the caches.
+    ///
+    /// # Arguments
+    /// - `access`: the user's access information.
+    ///
+    /// # Examples
+    /// ```
+    /// let access = EffectiveAccess::resolve(&user.access);
+    /// ```
+    pub fn resolve(access: &UserAccessInfo) -> Self {
+        let mut effective = EffectiveAccess::default();
+        for role_identifier in &access.role {
+            if let Ok(role) = RoleCache::get(role_identifier) {
+                for permission_identifier in &role.role_permissions {
+                    effective.push_permission(permission_identifier);
+                }
+                if !effective.roles.iter().any(|r| r.role_id == role.role_id) {
+                    effective.roles.push(role);
+                }
+            }
+        }
+        for permission_identifier in &access.permission {
+            effective.push_permission(permission_identifier);
+        }
+        effective
+    }
+
+    /// Checks whether a permission is held, by id or key. Names are only
+    /// meant for display, so they never grant a permission.
+    pub fn has_permission(&self, identifier: &str) -> bool {
+        self.permissions.iter().any(|permission| {
+            permission.permission_id == identifier || permission.permission_key == identifier
+        })
+    }
+
+    /// Checks whether a role is held, by id or name.
+    pub fn has_role(&self, identifier: &str) -> bool {
+        self.roles
+            .iter()
+            .any(|role| role.role_id == identifier || role.role_name == identifier)
+    }
+
+    fn push_permission(&mut self, identifier: &str) {
+        if let Ok(permission) = PermissionCache::get(identifier) {
+            if !self
+                .permissions
+                .iter()
+                .any(|p| p.permission_id == permission.permission_id)
+            {
+                self.permissions.push(permission);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    // the caches are shared by every test, so each test uses ids of its own.
+    fn permission(name: &str) -> Permission {
+        let permission = Permission::new(
+            &Uuid::new_v4().to_string(),
+            &format!("{}-{}", name, Uuid::new_v4()),
+            &format!("{}.{}", name, Uuid::new_v4()),
+        );
+        PermissionCache::add(permission.clone());
+        permission
+    }
+
+    fn role(name: &str, permissions: &[&Permission]) -> Role {
+        let role = Role::new(
+            &Uuid::new_v4().to_string(),
+            &format!("{}-{}", name, Uuid::new_v4()),
+            permissions
+                .iter()
+                .map(|permission| permission.permission_id.clone())
+                .collect(),
+        );
+        RoleCache::add(role.clone());
+        role
+    }
+
+    #[test]
+    fn resolves_the_permissions_of_roles_and_direct_permissions() {
+        let read = permission("read");
+        let write = permission("write");
+        let direct = permission("direct");
+        let editor = role("editor", &[&read, &write]);
+        let access = UserAccessInfo {
+            role: vec![editor.role_id.clone()],
+            permission: vec![direct.permission_id.clone()],
+        };
+
+        let effective = EffectiveAccess::resolve(&access);
+
+        assert_eq!(effective.roles, vec![editor]);
+        assert_eq!(effective.permissions, vec![read, write, direct]);
+    }
+
+    #[test]
+    fn resolves_roles_and_permissions_by_name() {
+        let read = permission("read");
+        let viewer = role("viewer", &[&read]);
+        let direct = permission("direct");
+        let access = UserAccessInfo {
+            role: vec![viewer.role_name.clone()],
+            permission: vec![direct.permission_name.clone()],
+        };
+
+        let effective = EffectiveAccess::resolve(&access);
+
+        assert_eq!(effective.roles, vec![viewer]);
+        assert_eq!(effective.permissions, vec![read, direct]);
+    }
+
+    #[test]
+    fn keeps_a_single_copy_of_what_is_held_twice() {
+        let shared = permission("shared");
+        let first = role("first", &[&shared]);
+        let second = role("second", &[&shared]);
+        let access = UserAccessInfo {
+            role: vec![
+                first.role_id.clone(),
+                first.role_name.clone(),
+                second.role_id.clone(),
+            ],
+            permission: vec![shared.permission_id.clone(), shared.permission_key.clone()],
+        };
+
+        let effective = EffectiveAccess::resolve(&access);
+
+        assert_eq!(effective.roles, vec![first, second]);
+        assert_eq!(effective.permissions, vec![shared]);
+    }
+
+    #[test]
+    fn skips_what_is_no_longer_cached() {
+        let read = permission("read");
+        let removed = permission("removed");
+        let reader = role("reader", &[&read, &removed]);
+        PermissionCache::remove(&removed.permission_id).unwrap();
+        let access = UserAccessInfo {
+            role: vec![reader.role_id.clone(), Uuid::new_v4().to_string()],
+            permission: vec![Uuid::new_v4().to_string()],
+        };
+
+        let effective = EffectiveAccess::resolve(&access);
+
+        assert_eq!(effective.roles, vec![reader]);
+        assert_eq!(effective.permissions, vec![read]);
+    }
+
+    #[test]
+    fn resolves_nothing_from_no_access() {
+        let effective = EffectiveAccess::resolve(&UserAccessInfo::default());
+
+        assert_eq!(effective, EffectiveAccess::default());
+    }
+
+    #[test]
+    fn checks_what_is_held_by_any_identifier() {
+        let read = permission("read");
+        let reader = role("reader", &[&read]);
+        let effective = EffectiveAccess::resolve(&UserAccessInfo {
+            role: vec![reader.role_id.clone()],
+            permission: Vec::new(),
+        });
+
+        assert!(effective.has_role(&reader.role_id));
+        assert!(effective.has_role(&reader.role_name));
+        assert!(effective.has_permission(&read.permission_id));
+        assert!(effective.has_permission(&read.permission_key));
+        assert!(!effective.has_permission(&read.permission_name));
+        assert!(!effective.has_permission("unknown"));
+        assert!(!effective.has_role("unknown"));
+    }
+
+    #[test]
+    fn never_grants_a_permission_through_its_name() {
+        let admin = permission("admin");
+        let impostor = Permission::new(
+            &Uuid::new_v4().to_string(),
+            &admin.permission_key,
+            &format!("impostor.{}", Uuid::new_v4()),
+        );
+        PermissionCache::add(impostor.clone());
+        let effective = EffectiveAccess::resolve(&UserAccessInfo {
+            role: Vec::new(),
+            permission: vec![impostor.permission_id.clone()],
+        });
+
+        assert_eq!(effective.permissions, vec![impostor]);
+        assert!(!effective.has_permission(&admin.permission_key));
+    }
+}
@@ -0,0 +1,44 @@
+use crate::app::service::task::{
+    error::TaskResult, manager::TaskManager, message::TaskRequest, registry::TaskDefinition,
+};
+
+use super::{
+    model::{BatchReport, BatchStep},
+    task::IamBatchTask,
+};
+
+pub struct BatchManager;
+
+impl BatchManager {
+    /// Runs the steps in order inside of one transaction, they succeed or
+    /// fail as one unit. The report is returned whether the batch committed
+    /// or not, see `BatchReport::outcome`.
+    ///
+    /// # Arguments
+    /// - `steps`: the operations of the batch.
+    ///
+    /// # Examples
+    /// ```
+    /// let report = BatchManager::run_batch(vec![
+    ///     BatchStep::RoleCreate { role_name: String::from("Moderator") },
+    ///     BatchStep::UserAddRole { user: user_id, role: String::from("Moderator") },
+    /// ]).await?;
+    /// ```
+    pub async fn run_batch(steps: Vec<BatchStep>) -> TaskResult<BatchReport> {
+        let task_request = Self::run_batch_request(steps);
+        TaskManager::process_task_with_report::<BatchReport>(task_request).await
+    }
+
+    /// Composes a batch request.
+    ///
+    /// # Arguments
+    /// - `steps`: the operations of the batch.
+    ///
+    /// # Examples
+    /// ```
+    /// Self::run_batch_request(steps)
+    /// ```
+    fn run_batch_request(steps: Vec<BatchStep>) -> TaskRequest {
+        IamBatchTask { steps }.into_request()
+    }
+}
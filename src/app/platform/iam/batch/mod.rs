@@ -0,0 +1,4 @@
+pub mod manager;
+pub mod model;
+pub mod route;
+pub mod task;
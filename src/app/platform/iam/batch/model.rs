@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app::service::task::error::TaskError;
+
+/// One operation of a batch. Roles, permissions and users are looked up by
+/// id or name, including the ones created by an earlier step of the batch.
+///
+/// # Examples
+/// ```
+/// {"op": "role_create", "role_name": "Moderator"}
+/// {"op": "role_add_permission", "role": "Moderator", "permission": "ban.user"}
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchStep {
+    RoleCreate {
+        role_name: String,
+    },
+    PermissionCreate {
+        permission_name: String,
+        permission_key: String,
+    },
+    RoleAddPermission {
+        role: String,
+        permission: String,
+    },
+    RoleDeletePermission {
+        role: String,
+        permission: String,
+    },
+    UserAddRole {
+        user: String,
+        role: String,
+    },
+    UserDeleteRole {
+        user: String,
+        role: String,
+    },
+    UserAddPermission {
+        user: String,
+        permission: String,
+    },
+    UserDeletePermission {
+        user: String,
+        permission: String,
+    },
+}
+
+/// What became of a step once the batch finished.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StepStatus {
+    /// The step ran and the batch committed.
+    Completed,
+    /// The step ran but a later step failed, so it was rolled back.
+    RolledBack,
+    /// The step failed, which rolled back the whole batch.
+    Failed,
+    /// The step never ran because an earlier step failed.
+    Skipped,
+    /// The step ran but the commit failed without telling whether it went
+    /// through, ex: the connection dropped while committing.
+    Indeterminate,
+}
+
+/// What became of the batch as a whole.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BatchOutcome {
+    /// Every step ran and the batch committed.
+    Committed,
+    /// A step, or the commit, failed and nothing was applied.
+    RolledBack,
+    /// The commit failed without telling whether it went through, the
+    /// changes of the batch have to be checked before running it again.
+    Indeterminate,
+}
+
+/// The outcome of a single step, `result` holds the role or permission a
+/// create step made.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StepOutcome {
+    pub step: BatchStep,
+    pub status: StepStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<TaskError>,
+}
+
+/// The per step outcome of a batch, in the order of its steps.
+/// `commit_error` is set when every step ran but the commit itself failed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub outcome: BatchOutcome,
+    pub steps: Vec<StepOutcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_error: Option<TaskError>,
+}
+
+impl BatchReport {
+    /// Whether the batch committed.
+    pub fn committed(&self) -> bool {
+        self.outcome == BatchOutcome::Committed
+    }
+
+    /// The error of the step, or of the commit, that kept the batch from
+    /// committing.
+    pub fn error(&self) -> Option<&TaskError> {
+        self.steps
+            .iter()
+            .find_map(|outcome| outcome.error.as_ref())
+            .or(self.commit_error.as_ref())
+    }
+}
@@ -0,0 +1,41 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde::Deserialize;
+
+use crate::app::platform::{
+    iam::access::{layer::RequirePermission, ADMIN_PERMISSION},
+    response::{CustomJsonResponse, ErrorJsonResponse},
+};
+
+use super::{
+    manager::BatchManager,
+    model::{BatchReport, BatchStep},
+};
+
+#[derive(Deserialize)]
+pub struct BatchBody {
+    pub steps: Vec<BatchStep>,
+}
+
+/// Admin routes over `BatchManager`, all guarded by `ADMIN_PERMISSION`.
+///
+/// # Routes
+///
+/// * `POST /iam/batch` - Run steps as one unit ex: `{"steps": [{"op": "role_create", "role_name": "Moderator"}]}`.
+pub fn router() -> Router {
+    Router::new()
+        .route("/iam/batch", post(run_batch))
+        .route_layer(RequirePermission(ADMIN_PERMISSION))
+}
+
+/// Answers 200 once the batch commits, otherwise with the status of the
+/// step, or commit, that failed. The report is sent back either way.
+async fn run_batch(
+    Json(body): Json<BatchBody>,
+) -> Result<CustomJsonResponse<BatchReport>, ErrorJsonResponse> {
+    let report = BatchManager::run_batch(body.steps).await?;
+    let status_code = match report.error() {
+        Some(error) if !report.committed() => error.status_code(),
+        _ => StatusCode::OK,
+    };
+    Ok(CustomJsonResponse::new(status_code, report))
+}
@@ -0,0 +1,474 @@
+use ark_macros::Task;
+use axum::async_trait;
+use bb8_postgres::tokio_postgres::{error::SqlState, Error, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app::{
+    database::postgres::PostgresDatabase,
+    platform::iam::{
+        permission::model::Permission,
+        role::{cache::RoleCache, model::Role},
+        sync::{IamCacheChange, IamCacheSync},
+        user::manager::UserCacheManager,
+    },
+    service::{
+        cache::LocalizedCache,
+        task::{
+            error::TaskError,
+            message::{TaskRequest, TaskResponse, TaskStatus},
+            registry::TaskDefinition,
+            retry::Transient,
+            Task,
+        },
+    },
+};
+
+use super::model::{BatchOutcome, BatchReport, BatchStep, StepOutcome, StepStatus};
+
+/// The most steps a single batch may hold.
+pub static MAX_BATCH_STEPS: usize = 100;
+
+/// Registers every batch task with the `TaskRegistry`.
+///
+/// # Examples
+/// ```
+/// register_tasks();
+/// ```
+pub fn register_tasks() {
+    IamBatchTask::register();
+}
+
+/// What a step changed, applied to the caches only once the batch commits.
+enum CacheEffect {
+    RoleCreated(Role),
+    PermissionCreated(Permission),
+    RolePermissionAdded { role_id: String, permission_id: String },
+    RolePermissionDeleted { role_id: String, permission_id: String },
+    UserRoleAdded { user_id: String, role_id: String },
+    UserRoleDeleted { user_id: String, role_id: String },
+    UserPermissionAdded { user_id: String, permission_id: String },
+    UserPermissionDeleted { user_id: String, permission_id: String },
+}
+
+/// Runs the steps in order inside of one transaction. The first step that
+/// fails rolls back every step, otherwise the batch commits and the caches
+/// are updated. A batch that did not commit fails the task with the error
+/// that stopped it, its `BatchReport` is the result either way.
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Batch, action = "iam_batch")]
+pub(super) struct IamBatchTask {
+    pub steps: Vec<BatchStep>,
+}
+
+#[async_trait]
+impl Task<PostgresDatabase, TaskRequest, IamBatchTask> for IamBatchTask {
+    async fn run(db: &PostgresDatabase, request: TaskRequest, param: IamBatchTask) -> TaskResponse {
+        if param.steps.is_empty() {
+            return TaskResponse::throw_failed_response(
+                request,
+                vec![TaskError::BatchEmpty.to_string()],
+            );
+        }
+        if param.steps.len() > MAX_BATCH_STEPS {
+            return TaskResponse::throw_failed_response(
+                request,
+                vec![TaskError::BatchTooLarge.to_string()],
+            );
+        }
+        let mut pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let transaction = match pool.transaction().await {
+            Ok(transaction) => transaction,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+
+        let mut outcomes: Vec<StepOutcome> = Vec::new();
+        let mut effects: Vec<CacheEffect> = Vec::new();
+        let mut failed = false;
+        for step in param.steps {
+            if failed {
+                outcomes.push(StepOutcome {
+                    step,
+                    status: StepStatus::Skipped,
+                    result: None,
+                    error: None,
+                });
+                continue;
+            }
+            match run_step(&transaction, &step).await {
+                Ok((result, effect)) => {
+                    outcomes.push(StepOutcome {
+                        step,
+                        status: StepStatus::Completed,
+                        result,
+                        error: None,
+                    });
+                    effects.push(effect);
+                }
+                Err(error) => {
+                    outcomes.push(StepOutcome {
+                        step,
+                        status: StepStatus::Failed,
+                        result: None,
+                        error: Some(error),
+                    });
+                    failed = true;
+                }
+            }
+        }
+
+        if failed {
+            // dropping the transaction rolls it back as well, this only frees the connection sooner.
+            let _ = transaction.rollback().await;
+            let report = BatchReport {
+                outcome: BatchOutcome::RolledBack,
+                steps: mark_ran(outcomes, StepStatus::RolledBack),
+                commit_error: None,
+            };
+            return fail_batch(request, report);
+        }
+        if let Err(error) = transaction.commit().await {
+            println!(
+                "[TASK] Task: {} failed to commit its batch. Error: {}",
+                request.task_id, error
+            );
+            let report = match was_rejected(&error) {
+                true => BatchReport {
+                    outcome: BatchOutcome::RolledBack,
+                    steps: mark_ran(outcomes, StepStatus::RolledBack),
+                    commit_error: Some(match error.is_transient() {
+                        true => TaskError::TransientFailure,
+                        false => TaskError::TaskInternalError,
+                    }),
+                },
+                false => BatchReport {
+                    outcome: BatchOutcome::Indeterminate,
+                    steps: mark_ran(outcomes, StepStatus::Indeterminate),
+                    commit_error: Some(TaskError::BatchOutcomeUnknown),
+                },
+            };
+            return fail_batch(request, report);
+        }
+        for effect in effects {
+            apply_effect(effect).await;
+        }
+        return TaskResponse::compose_response(
+            request,
+            TaskStatus::Completed,
+            BatchReport {
+                outcome: BatchOutcome::Committed,
+                steps: outcomes,
+                commit_error: None,
+            },
+            Vec::default(),
+        );
+    }
+}
+
+/// Fails the task with the error that kept the batch from committing, the
+/// report is kept as its result.
+fn fail_batch(request: TaskRequest, report: BatchReport) -> TaskResponse {
+    let error = report
+        .error()
+        .cloned()
+        .unwrap_or(TaskError::TaskInternalError);
+    TaskResponse::compose_response(request, TaskStatus::Failed, report, vec![error.to_string()])
+}
+
+/// Whether the server answered the commit by rolling the transaction back,
+/// ex: a deferred constraint or a serialization failure. Any other error,
+/// a dropped connection included, leaves the outcome of the commit unknown.
+fn was_rejected(error: &Error) -> bool {
+    error.code().is_some_and(|code| {
+        // integrity constraint violations and transaction rollbacks.
+        code.code().starts_with("23") || code.code().starts_with("40")
+    })
+}
+
+/// Gives the steps that ran the status the batch ended with.
+fn mark_ran(mut outcomes: Vec<StepOutcome>, status: StepStatus) -> Vec<StepOutcome> {
+    for outcome in outcomes.iter_mut() {
+        if outcome.status == StepStatus::Completed {
+            outcome.status = status.clone();
+        }
+    }
+    outcomes
+}
+
+/// Runs a single step, returning what it made along with its cache effect.
+async fn run_step(
+    transaction: &Transaction<'_>,
+    step: &BatchStep,
+) -> Result<(Option<Value>, CacheEffect), TaskError> {
+    match step {
+        BatchStep::RoleCreate { role_name } => {
+            let role = Role::builder().role_name(role_name).build();
+            transaction
+                .execute(
+                    "INSERT INTO iam_roles (id, role_name) VALUES ($1, $2)",
+                    &[&role.role_id, &role.role_name],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::RoleDuplication))?;
+            let result = serde_json::to_value(&role).ok();
+            Ok((result, CacheEffect::RoleCreated(role)))
+        }
+        BatchStep::PermissionCreate {
+            permission_name,
+            permission_key,
+        } => {
+            let permission = Permission::builder()
+                .permission_name(permission_name)
+                .permission_key(permission_key)
+                .build();
+            transaction
+                .execute(
+                    "INSERT INTO iam_permissions (id, permission_name, permission_key) VALUES ($1, $2, $3)",
+                    &[
+                        &permission.permission_id,
+                        &permission.permission_name,
+                        &permission.permission_key,
+                    ],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::PermissionDuplication))?;
+            let result = serde_json::to_value(&permission).ok();
+            Ok((result, CacheEffect::PermissionCreated(permission)))
+        }
+        BatchStep::RoleAddPermission { role, permission } => {
+            let role_id = resolve_role(transaction, role).await?;
+            let permission_id = resolve_permission(transaction, permission).await?;
+            transaction
+                .execute(
+                    "INSERT INTO iam_role_permission (role_id, permission_id) VALUES ($1, $2)",
+                    &[&role_id, &permission_id],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::PermissionLinkAlreadyExist))?;
+            Ok((
+                None,
+                CacheEffect::RolePermissionAdded {
+                    role_id,
+                    permission_id,
+                },
+            ))
+        }
+        BatchStep::RoleDeletePermission { role, permission } => {
+            let role_id = resolve_role(transaction, role).await?;
+            let permission_id = resolve_permission(transaction, permission).await?;
+            let deleted = transaction
+                .execute(
+                    "DELETE FROM iam_role_permission WHERE role_id = $1 AND permission_id = $2",
+                    &[&role_id, &permission_id],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::FailedToCompleteTask))?;
+            if deleted == 0 {
+                return Err(TaskError::PermissionNotFound);
+            }
+            Ok((
+                None,
+                CacheEffect::RolePermissionDeleted {
+                    role_id,
+                    permission_id,
+                },
+            ))
+        }
+        BatchStep::UserAddRole { user, role } => {
+            let user_id = resolve_user(transaction, user).await?;
+            let role_id = resolve_role(transaction, role).await?;
+            transaction
+                .execute(
+                    "INSERT INTO iam_user_role (user_id, role_id) VALUES ($1, $2)",
+                    &[&user_id, &role_id],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::UserRoleAlreadyExists))?;
+            Ok((None, CacheEffect::UserRoleAdded { user_id, role_id }))
+        }
+        BatchStep::UserDeleteRole { user, role } => {
+            let user_id = resolve_user(transaction, user).await?;
+            let role_id = resolve_role(transaction, role).await?;
+            let deleted = transaction
+                .execute(
+                    "DELETE FROM iam_user_role WHERE user_id = $1 AND role_id = $2",
+                    &[&user_id, &role_id],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::FailedToCompleteTask))?;
+            if deleted == 0 {
+                return Err(TaskError::RoleNotFound);
+            }
+            Ok((None, CacheEffect::UserRoleDeleted { user_id, role_id }))
+        }
+        BatchStep::UserAddPermission { user, permission } => {
+            let user_id = resolve_user(transaction, user).await?;
+            let permission_id = resolve_permission(transaction, permission).await?;
+            transaction
+                .execute(
+                    "INSERT INTO iam_user_permission (user_id, permission_id) VALUES ($1, $2)",
+                    &[&user_id, &permission_id],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::UserPermissionAlreadyExists))?;
+            Ok((
+                None,
+                CacheEffect::UserPermissionAdded {
+                    user_id,
+                    permission_id,
+                },
+            ))
+        }
+        BatchStep::UserDeletePermission { user, permission } => {
+            let user_id = resolve_user(transaction, user).await?;
+            let permission_id = resolve_permission(transaction, permission).await?;
+            let deleted = transaction
+                .execute(
+                    "DELETE FROM iam_user_permission WHERE user_id = $1 AND permission_id = $2",
+                    &[&user_id, &permission_id],
+                )
+                .await
+                .map_err(|error| step_error(&error, TaskError::FailedToCompleteTask))?;
+            if deleted == 0 {
+                return Err(TaskError::PermissionNotFound);
+            }
+            Ok((
+                None,
+                CacheEffect::UserPermissionDeleted {
+                    user_id,
+                    permission_id,
+                },
+            ))
+        }
+    }
+}
+
+/// The error a step fails with, a unique violation is the logical error of
+/// the step while a transient failure is reported as such.
+fn step_error(error: &Error, logical: TaskError) -> TaskError {
+    if error.is_transient() {
+        return TaskError::TransientFailure;
+    }
+    match error.code() {
+        Some(code) if *code == SqlState::UNIQUE_VIOLATION => logical,
+        _ => TaskError::FailedToCompleteTask,
+    }
+}
+
+/// Finds the id of a role by id or name, through the transaction so roles
+/// created by an earlier step are found.
+async fn resolve_role(transaction: &Transaction<'_>, identifier: &str) -> Result<String, TaskError> {
+    resolve(
+        transaction,
+        "SELECT id FROM iam_roles WHERE id = $1 OR role_name = $1",
+        identifier,
+        TaskError::RoleNotFound,
+    )
+    .await
+}
+
+/// Finds the id of a permission by id, name or key.
+async fn resolve_permission(
+    transaction: &Transaction<'_>,
+    identifier: &str,
+) -> Result<String, TaskError> {
+    resolve(
+        transaction,
+        "SELECT id FROM iam_permissions WHERE id = $1 OR permission_name = $1 OR permission_key = $1",
+        identifier,
+        TaskError::PermissionNotFound,
+    )
+    .await
+}
+
+/// Finds the id of a user by id, username or email.
+async fn resolve_user(transaction: &Transaction<'_>, identifier: &str) -> Result<String, TaskError> {
+    resolve(
+        transaction,
+        "SELECT id FROM iam_users WHERE id = $1 OR username = $1 OR email = $1",
+        identifier,
+        TaskError::UserNotFound,
+    )
+    .await
+}
+
+async fn resolve(
+    transaction: &Transaction<'_>,
+    query: &str,
+    identifier: &str,
+    not_found: TaskError,
+) -> Result<String, TaskError> {
+    match transaction.query_opt(query, &[&identifier]).await {
+        Ok(Some(row)) => Ok(row.get(0)),
+        Ok(None) => Err(not_found),
+        Err(error) if error.is_transient() => Err(TaskError::TransientFailure),
+        Err(_) => Err(TaskError::FailedToCompleteTask),
+    }
+}
+
+/// Applies the effect of a committed step to the caches, the same way the
+/// task of the single operation does, shared with every other process
+/// through `IamCacheSync`.
+async fn apply_effect(effect: CacheEffect) {
+    match effect {
+        CacheEffect::RoleCreated(role) => {
+            IamCacheSync::apply(IamCacheChange::RoleSaved(role)).await
+        }
+        CacheEffect::PermissionCreated(permission) => {
+            IamCacheSync::apply(IamCacheChange::PermissionSaved(permission)).await
+        }
+        CacheEffect::RolePermissionAdded {
+            role_id,
+            permission_id,
+        } => {
+            if let Ok(mut role) = RoleCache::get(&role_id) {
+                role.role_permissions.push(permission_id);
+                IamCacheSync::apply(IamCacheChange::RoleSaved(role)).await;
+            }
+        }
+        CacheEffect::RolePermissionDeleted {
+            role_id,
+            permission_id,
+        } => {
+            if let Ok(mut role) = RoleCache::get(&role_id) {
+                role.role_permissions
+                    .retain(|permission| permission != &permission_id);
+                IamCacheSync::apply(IamCacheChange::RoleSaved(role)).await;
+            }
+        }
+        CacheEffect::UserRoleAdded { user_id, role_id } => {
+            // a user that is not cached is read from the database the next time.
+            if let Ok(mut user) = UserCacheManager::read_user_from_cache(&user_id).await {
+                user.access.role.push(role_id);
+                let _ = UserCacheManager::add_user_to_cache(user).await;
+            }
+        }
+        CacheEffect::UserRoleDeleted { user_id, role_id } => {
+            if let Ok(mut user) = UserCacheManager::read_user_from_cache(&user_id).await {
+                user.access.role.retain(|role| role != &role_id);
+                let _ = UserCacheManager::add_user_to_cache(user).await;
+            }
+        }
+        CacheEffect::UserPermissionAdded {
+            user_id,
+            permission_id,
+        } => {
+            if let Ok(mut user) = UserCacheManager::read_user_from_cache(&user_id).await {
+                user.access.permission.push(permission_id);
+                let _ = UserCacheManager::add_user_to_cache(user).await;
+            }
+        }
+        CacheEffect::UserPermissionDeleted {
+            user_id,
+            permission_id,
+        } => {
+            if let Ok(mut user) = UserCacheManager::read_user_from_cache(&user_id).await {
+                user.access.permission.retain(|permission| permission != &permission_id);
+                let _ = UserCacheManager::add_user_to_cache(user).await;
+            }
+        }
+    }
+}
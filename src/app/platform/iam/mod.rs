@@ -1,4 +1,7 @@
+pub mod access;
+pub mod batch;
 pub mod permission;
 pub mod role;
 pub mod session;
+pub mod sync;
 pub mod user;
\ No newline at end of file
@@ -39,7 +39,10 @@ impl LocalizedCache<Permission> for PermissionCache {
     fn remove(id: &str) -> CacheResult<bool> {
         let mut cache = PERMISSION_CACHE.write().unwrap();
         match cache.remove(id) {
-            Some(_) => Ok(true),
+            Some(item) => {
+                cache.retain(|_, v| v.permission_id != item.permission_id);
+                Ok(true)
+            }
             None => Err(CacheError::ItemNotFound),
         }
     }
@@ -51,4 +54,14 @@ impl LocalizedCache<Permission> for PermissionCache {
             None => Err(CacheError::ItemNotFound),
         }
     }
+
+    fn all() -> Vec<Permission> {
+        let cache = PERMISSION_CACHE.read().unwrap();
+        // every item is stored under its id, the other keys are aliases.
+        cache
+            .iter()
+            .filter(|(key, item)| **key == item.permission_id)
+            .map(|(_, item)| item.as_ref().clone())
+            .collect()
+    }
 }
@@ -1,7 +1,8 @@
 use crate::app::service::task::{
     error::TaskResult,
     manager::TaskManager,
-    message::{TaskRequest, TaskStatus, TaskType},
+    message::{TaskRequest, TaskStatus},
+    registry::TaskDefinition,
 };
 
 use super::{
@@ -27,11 +28,11 @@ impl PermissionManager {
     ///     .permission_name("admin ban user")
     ///     .permission_key("admin.ban")
     ///     .build();
-    /// let task_response = create_permission(permission);
+    /// let task_response = create_permission(permission).await;
     /// ```
-    pub fn create_permission(permission: Permission) -> TaskResult<TaskStatus> {
+    pub async fn create_permission(permission: Permission) -> TaskResult<TaskStatus> {
         let task_request = Self::create_permission_request(permission);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
 
     /// Composes a permission create request.
@@ -45,11 +46,7 @@ impl PermissionManager {
     /// Self::process_permission_task(permission)
     /// ```
     fn create_permission_request(permission: Permission) -> TaskRequest {
-        TaskRequest::compose_request(
-            PermissionCreateTask::from(permission),
-            TaskType::Permission,
-            "permission_create",
-        )
+        PermissionCreateTask::from(permission).into_request()
     }
 
     /// Delete a permission.
@@ -64,13 +61,13 @@ impl PermissionManager {
     ///     .permission_name("admin ban user")
     ///     .permission_key("admin.ban")
     ///     .build();
-    /// delete_permission("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3"); // delete by id.
+    /// delete_permission("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3").await; // delete by id.
     /// delete_permission("Testing name") // delete by name
     /// delete_permission("Testing.key") // delete by key
     /// ```
-    pub fn delete_permission(permission_identifer: &str) -> TaskResult<TaskStatus> {
+    pub async fn delete_permission(permission_identifer: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::delete_permission_request(permission_identifer);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
 
     /// Composes a permission delete request.
@@ -84,13 +81,10 @@ impl PermissionManager {
     /// delete_permission_request("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3");
     /// ```
     fn delete_permission_request(identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request::<PermissionDeleteTask>(
-            PermissionDeleteTask {
-                identifier: identifier.to_string(),
-            },
-            TaskType::Permission,
-            "permission_delete",
-        )
+        PermissionDeleteTask {
+            identifier: identifier.to_string(),
+        }
+        .into_request()
     }
 
     /// Updates specific field within a permission.
@@ -103,16 +97,16 @@ impl PermissionManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// update_permission("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3", "permission_name", "admin ban user.");
-    /// update_permission("admin ban user.", "permission_key", "admin.ban.key");
+    /// update_permission("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3", "permission_name", "admin ban user.").await;
+    /// update_permission("admin ban user.", "permission_key", "admin.ban.key").await;
     /// ```
-    pub fn update_permission(
+    pub async fn update_permission(
         search_by: &str,
         update_for: &str,
         value: &str,
     ) -> TaskResult<TaskStatus> {
         let request = Self::update_permission_request(search_by, update_for, value);
-        TaskManager::process_task(request)
+        TaskManager::process_task(request).await
     }
 
     /// Composes a permission update request.
@@ -129,18 +123,15 @@ impl PermissionManager {
     ///     .permission_name("admin ban user")
     ///     .permission_key("admin.ban")
     ///     .build();
-    /// let task_response = create_permission(permission);
+    /// let task_response = create_permission(permission).await;
     /// ```
     fn update_permission_request(search_by: &str, update_for: &str, value: &str) -> TaskRequest {
-        TaskRequest::compose_request::<PermissionUpdateTask>(
-            PermissionUpdateTask {
-                search_by: search_by.to_string(),
-                update_for: update_for.to_string(),
-                value: value.to_string(),
-            },
-            TaskType::Permission,
-            "permission_update",
-        )
+        PermissionUpdateTask {
+            search_by: search_by.to_string(),
+            update_for: update_for.to_string(),
+            value: value.to_string(),
+        }
+        .into_request()
     }
 
     /// Grab a specific permission based on it's id, name or key.
@@ -150,11 +141,11 @@ impl PermissionManager {
     ///
     /// # Examples
     /// ```
-    /// get_permission("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3");
+    /// get_permission("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3").await;
     /// ```
-    pub fn get_permission(identifier: &str) -> TaskResult<Permission> {
+    pub async fn get_permission(identifier: &str) -> TaskResult<Permission> {
         let request = Self::read_permission_request(identifier);
-        TaskManager::process_task_with_result::<Permission>(request)
+        TaskManager::process_task_with_result::<Permission>(request).await
     }
 
     /// Composes a permission read request.
@@ -168,13 +159,10 @@ impl PermissionManager {
     /// read_permission_request("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3");
     /// ```
     fn read_permission_request(identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request::<PermissionReadTask>(
-            PermissionReadTask {
-                identifier: String::from(identifier),
-            },
-            TaskType::Permission,
-            "permission_read",
-        )
+        PermissionReadTask {
+            identifier: String::from(identifier),
+        }
+        .into_request()
     }
 
     /// Preload permission cache.
@@ -182,11 +170,11 @@ impl PermissionManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// preload_permission_cache();
+    /// preload_permission_cache().await;
     /// ```
-    pub fn preload_permission_cache() -> TaskResult<TaskStatus> {
+    pub async fn preload_permission_cache() -> TaskResult<TaskStatus> {
         let request = Self::preload_permission_request();
-        TaskManager::process_task(request)
+        TaskManager::process_task(request).await
     }
 
     /// Composes a permission preload cache request.
@@ -196,10 +184,6 @@ impl PermissionManager {
     /// let task_response = preload_permission_request();
     /// ```
     fn preload_permission_request() -> TaskRequest {
-        TaskRequest::compose_request(
-            PermissionPreloadCache {},
-            TaskType::Permission,
-            "permission_preload_cache",
-        )
+        PermissionPreloadCache {}.into_request()
     }
 }
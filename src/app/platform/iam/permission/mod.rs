@@ -1,6 +1,7 @@
 pub mod cache;
 pub mod manager;
 pub mod model;
+pub mod route;
 pub mod task;
 
 
@@ -0,0 +1,93 @@
+use axum::{extract::Path, http::StatusCode, routing::get, Json, Router};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::app::{
+    platform::{
+        iam::access::{layer::RequirePermission, ADMIN_PERMISSION},
+        response::{CustomJsonResponse, ErrorJsonResponse},
+    },
+    service::{cache::LocalizedCache, task::error::TaskError},
+};
+
+use super::{cache::PermissionCache, manager::PermissionManager, model::Permission};
+
+#[derive(Deserialize)]
+pub struct PermissionCreateBody {
+    pub permission_name: String,
+    pub permission_key: String,
+}
+
+/// Admin routes over `PermissionManager`, all guarded by `ADMIN_PERMISSION`.
+///
+/// # Routes
+///
+/// * `GET /iam/permissions` - List every permission inside of the `PermissionCache`.
+/// * `POST /iam/permissions` - Create a permission ex: `{"permission_name": "Ban User", "permission_key": "ban.user"}`.
+/// * `GET /iam/permissions/:permission` - Read a permission by id, name or key.
+/// * `PATCH /iam/permissions/:permission` - Update the fields inside of the body ex: `{"permission_key": "admin.ban"}`.
+/// * `DELETE /iam/permissions/:permission` - Delete a permission.
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/iam/permissions",
+            get(list_permissions).post(create_permission),
+        )
+        .route(
+            "/iam/permissions/:permission",
+            get(get_permission)
+                .patch(update_permission)
+                .delete(delete_permission),
+        )
+        .route_layer(RequirePermission(ADMIN_PERMISSION))
+}
+
+async fn list_permissions() -> CustomJsonResponse<Vec<Permission>> {
+    let mut permissions = PermissionCache::all();
+    permissions.sort_by(|a, b| a.permission_key.cmp(&b.permission_key));
+    CustomJsonResponse::new(StatusCode::OK, permissions)
+}
+
+async fn create_permission(
+    Json(body): Json<PermissionCreateBody>,
+) -> Result<CustomJsonResponse<Permission>, ErrorJsonResponse> {
+    let permission = Permission::builder()
+        .permission_name(&body.permission_name)
+        .permission_key(&body.permission_key)
+        .build();
+    let permission_id = permission.permission_id.clone();
+    PermissionManager::create_permission(permission).await?;
+    let permission = PermissionManager::get_permission(&permission_id).await?;
+    Ok(CustomJsonResponse::new(StatusCode::CREATED, permission))
+}
+
+async fn get_permission(
+    Path(permission): Path<String>,
+) -> Result<CustomJsonResponse<Permission>, ErrorJsonResponse> {
+    let permission = PermissionManager::get_permission(&permission).await?;
+    Ok(CustomJsonResponse::new(StatusCode::OK, permission))
+}
+
+/// Applies every field of the body, the permission is looked up by its id
+/// so renaming it does not affect the rest of the update.
+async fn update_permission(
+    Path(permission): Path<String>,
+    Json(fields): Json<Map<String, Value>>,
+) -> Result<CustomJsonResponse<Permission>, ErrorJsonResponse> {
+    let permission_id = PermissionManager::get_permission(&permission).await?.permission_id;
+    for (update_for, value) in &fields {
+        let value = match value {
+            Value::String(v) => v,
+            _ => return Err(TaskError::PermissionFieldNotFound.into()),
+        };
+        PermissionManager::update_permission(&permission_id, update_for, value).await?;
+    }
+    get_permission(Path(permission_id)).await
+}
+
+async fn delete_permission(
+    Path(permission): Path<String>,
+) -> Result<StatusCode, ErrorJsonResponse> {
+    PermissionManager::delete_permission(&permission).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,104 +1,36 @@
+use ark_macros::Task;
 use axum::async_trait;
 use serde::{Deserialize, Serialize};
 
 use crate::app::{
     database::postgres::PostgresDatabase,
+    platform::iam::sync::{IamCacheChange, IamCacheSync},
     service::{
         cache::{notify_cache_hit, notify_cache_miss, LocalizedCache},
         task::{
             error::TaskError,
             message::{TaskRequest, TaskResponse, TaskStatus},
-            Task, TaskHandler,
+            registry::TaskDefinition,
+            retry::Transient,
+            Task,
         },
     },
 };
 
 use super::{cache::PermissionCache, model::Permission};
 
-pub struct PermissionTaskHandler;
-
-#[async_trait]
-impl TaskHandler<PostgresDatabase> for PermissionTaskHandler {
-    async fn handle(pg: &PostgresDatabase, task_request: TaskRequest) -> TaskResponse {
-        if task_request.task_action.eq("permission_create") {
-            let payload = match TaskRequest::intepret_request_payload::<PermissionCreateTask>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return PermissionCreateTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("permission_delete") {
-            let payload = match TaskRequest::intepret_request_payload::<PermissionDeleteTask>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return PermissionDeleteTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("permission_update") {
-            let payload = match TaskRequest::intepret_request_payload::<PermissionUpdateTask>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return PermissionUpdateTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("permission_read") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<PermissionReadTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return PermissionReadTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("permission_preload_cache") {
-            let payload = match TaskRequest::intepret_request_payload::<PermissionPreloadCache>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return PermissionPreloadCache::run(pg, task_request, payload).await;
-        }
-        
-        return TaskResponse::throw_failed_response(
-            task_request,
-            vec![TaskError::FailedToFindAction.to_string()],
-        );
-    }
+/// Registers every permission task with the `TaskRegistry`.
+///
+/// # Examples
+/// ```
+/// register_tasks();
+/// ```
+pub fn register_tasks() {
+    PermissionCreateTask::register();
+    PermissionDeleteTask::register();
+    PermissionUpdateTask::register();
+    PermissionReadTask::register();
+    PermissionPreloadCache::register();
 }
 
 /// Represents a task for creating a permission.
@@ -117,7 +49,8 @@ impl TaskHandler<PostgresDatabase> for PermissionTaskHandler {
 ///     }
 /// }
 /// ```
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, Task)]
+#[task(task_type = Permission, action = "permission_create")]
 pub(super) struct PermissionCreateTask {
     pub permission_id: String,
     pub permission_name: String,
@@ -141,13 +74,19 @@ impl Task<PostgresDatabase, TaskRequest, PermissionCreateTask> for PermissionCre
         request: TaskRequest,
         param: PermissionCreateTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare(
                 "INSERT INTO iam_permissions (id, permission_name, permission_key) VALUES ($1, $2, $3)",
             )
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool
             .execute(
                 &stmt,
@@ -160,11 +99,12 @@ impl Task<PostgresDatabase, TaskRequest, PermissionCreateTask> for PermissionCre
             .await
         {
             Ok(_) => {
-                PermissionCache::add(Permission::new(
+                IamCacheSync::apply(IamCacheChange::PermissionSaved(Permission::new(
                     &param.permission_id,
                     &param.permission_name,
                     &param.permission_key,
-                ));
+                )))
+                .await;
                 return TaskResponse::compose_response(
                     request,
                     TaskStatus::Completed,
@@ -172,6 +112,9 @@ impl Task<PostgresDatabase, TaskRequest, PermissionCreateTask> for PermissionCre
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -198,7 +141,8 @@ impl Task<PostgresDatabase, TaskRequest, PermissionCreateTask> for PermissionCre
 ///     }
 /// }
 /// ```
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Permission, action = "permission_delete")]
 pub(super) struct PermissionDeleteTask {
     pub identifier: String,
 }
@@ -209,8 +153,11 @@ impl Task<PostgresDatabase, TaskRequest, PermissionDeleteTask> for PermissionDel
         request: TaskRequest,
         param: PermissionDeleteTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare(
                 "DELETE FROM iam_permissions
                 WHERE id = $1
@@ -218,7 +165,10 @@ impl Task<PostgresDatabase, TaskRequest, PermissionDeleteTask> for PermissionDel
                    OR permission_key = $1",
             )
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool.execute(&stmt, &[&param.identifier]).await {
             Ok(v) => {
                 if v != 0 {
@@ -226,7 +176,10 @@ impl Task<PostgresDatabase, TaskRequest, PermissionDeleteTask> for PermissionDel
                     // might need to convert this into a hashmap
                     // RoleCache::update(search_by, update_for, value);
                     // or re-preload the cache?
-                    PermissionCache::remove(&param.identifier).unwrap();
+                    IamCacheSync::apply(IamCacheChange::PermissionRemoved(
+                        param.identifier.clone(),
+                    ))
+                    .await;
                     return TaskResponse::compose_response(
                         request,
                         TaskStatus::Completed,
@@ -239,6 +192,9 @@ impl Task<PostgresDatabase, TaskRequest, PermissionDeleteTask> for PermissionDel
                     vec![TaskError::PermissionNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(er) => {
                 println!("{}", er);
                 return TaskResponse::throw_failed_response(
@@ -271,7 +227,8 @@ impl Task<PostgresDatabase, TaskRequest, PermissionDeleteTask> for PermissionDel
 ///     }
 /// }
 /// ```
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Permission, action = "permission_update")]
 pub(super) struct PermissionUpdateTask {
     pub search_by: String,
     pub update_for: String,
@@ -284,7 +241,10 @@ impl Task<PostgresDatabase, TaskRequest, PermissionUpdateTask> for PermissionUpd
         request: TaskRequest,
         param: PermissionUpdateTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         if param.update_for.eq_ignore_ascii_case("id") {
             return TaskResponse::throw_failed_response(
                 request,
@@ -307,6 +267,9 @@ impl Task<PostgresDatabase, TaskRequest, PermissionUpdateTask> for PermissionUpd
             .await
         {
             Ok(v) => v,
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -320,16 +283,14 @@ impl Task<PostgresDatabase, TaskRequest, PermissionUpdateTask> for PermissionUpd
             .await
         {
             Ok(v) => {
-                if v.len() != 0 {
-                    let old_perm = PermissionCache::get(v.get(0)).unwrap();
-                    // id should automatically get replaced...
-                    if param.update_for.eq("permission_name") {
-                        PermissionCache::remove(&old_perm.permission_name).unwrap();
-                    }
-                    if param.update_for.eq("permission_key") {
-                        PermissionCache::remove(&old_perm.permission_key).unwrap();
-                    }
-                    PermissionCache::add(Permission::new(v.get(0), v.get(1), v.get(2)));
+                if !v.is_empty() {
+                    // replaces the old name or key along with the permission.
+                    IamCacheSync::apply(IamCacheChange::PermissionSaved(Permission::new(
+                        v.get(0),
+                        v.get(1),
+                        v.get(2),
+                    )))
+                    .await;
                     return TaskResponse::compose_response(
                         request,
                         TaskStatus::Completed,
@@ -342,6 +303,9 @@ impl Task<PostgresDatabase, TaskRequest, PermissionUpdateTask> for PermissionUpd
                     vec![TaskError::PermissionNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -353,7 +317,8 @@ impl Task<PostgresDatabase, TaskRequest, PermissionUpdateTask> for PermissionUpd
 }
 
 /// Represents a task for reading a permission.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Permission, action = "permission_read", idempotent, read_only)]
 pub(super) struct PermissionReadTask {
     pub identifier: String,
 }
@@ -365,7 +330,10 @@ impl Task<PostgresDatabase, TaskRequest, PermissionReadTask> for PermissionReadT
         request: TaskRequest,
         param: PermissionReadTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match PermissionCache::get(&param.identifier) {
             Ok(permission) => {
                 notify_cache_hit("PermissionCache", "PermissionReadTask", &request.task_id);
@@ -377,14 +345,17 @@ impl Task<PostgresDatabase, TaskRequest, PermissionReadTask> for PermissionReadT
                 );
             }
             Err(_) => {
-                let stmt = pool
+                let stmt = match pool
                     .prepare(
                         "SELECT * FROM iam_permissions WHERE id = $1
         OR permission_name = $1
         OR permission_key = $1",
                     )
                     .await
-                    .unwrap();
+                {
+                    Ok(stmt) => stmt,
+                    Err(error) => return TaskResponse::throw_database_failure(request, &error),
+                };
                 match pool.query_one(&stmt, &[&param.identifier]).await {
                     Ok(row) => {
                         notify_cache_miss(
@@ -401,6 +372,9 @@ impl Task<PostgresDatabase, TaskRequest, PermissionReadTask> for PermissionReadT
                             Vec::default(),
                         );
                     }
+                    Err(error) if error.is_transient() => {
+                        return TaskResponse::throw_database_failure(request, &error)
+                    }
                     Err(_) => {
                         return TaskResponse::throw_failed_response(
                             request,
@@ -413,7 +387,8 @@ impl Task<PostgresDatabase, TaskRequest, PermissionReadTask> for PermissionReadT
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Permission, action = "permission_preload_cache", idempotent, read_only)]
 pub(super) struct PermissionPreloadCache;
 #[async_trait]
 impl Task<PostgresDatabase, TaskRequest, PermissionPreloadCache> for PermissionPreloadCache {
@@ -422,8 +397,14 @@ impl Task<PostgresDatabase, TaskRequest, PermissionPreloadCache> for PermissionP
         request: TaskRequest,
         _: PermissionPreloadCache,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool.prepare("SELECT * FROM iam_permissions").await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool.prepare("SELECT * FROM iam_permissions").await {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         match pool.query(&stmt, &[]).await {
             Ok(rows) => {
@@ -440,6 +421,9 @@ impl Task<PostgresDatabase, TaskRequest, PermissionPreloadCache> for PermissionP
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -35,7 +35,10 @@ impl LocalizedCache<Role> for RoleCache {
     fn remove(id: &str) -> CacheResult<bool> {
         let mut cache = ROLE_CACHE.write().unwrap();
         match cache.remove(id) {
-            Some(_) => Ok(true),
+            Some(item) => {
+                cache.retain(|_, v| v.role_id != item.role_id);
+                Ok(true)
+            }
             None => Err(CacheError::ItemNotFound),
         }
     }
@@ -47,4 +50,14 @@ impl LocalizedCache<Role> for RoleCache {
             None => Err(CacheError::ItemNotFound),
         }
     }
+
+    fn all() -> Vec<Role> {
+        let cache = ROLE_CACHE.read().unwrap();
+        // every item is stored under its id, the other keys are aliases.
+        cache
+            .iter()
+            .filter(|(key, item)| **key == item.role_id)
+            .map(|(_, item)| item.as_ref().clone())
+            .collect()
+    }
 }
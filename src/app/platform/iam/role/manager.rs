@@ -1,7 +1,8 @@
 use crate::app::service::task::{
     error::TaskResult,
     manager::TaskManager,
-    message::{TaskRequest, TaskStatus, TaskType},
+    message::{TaskRequest, TaskStatus},
+    registry::TaskDefinition,
 };
 
 use super::{
@@ -22,11 +23,11 @@ impl RoleManager {
     /// let role = PermissionBuilder::builder()
     ///     .role_name("Member")
     ///     .build();
-    /// create_role(role);
+    /// create_role(role).await;
     /// ```
-    pub fn create_role(role: Role) -> TaskResult<TaskStatus> {
+    pub async fn create_role(role: Role) -> TaskResult<TaskStatus> {
         let task_request = Self::create_role_request(role);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
 
     /// Composes a role create request.
@@ -40,7 +41,7 @@ impl RoleManager {
     /// Self::create_role_request(role)
     /// ```
     fn create_role_request(role: Role) -> TaskRequest {
-        TaskRequest::compose_request(RoleCreateTask::from(role), TaskType::Role, "role_create")
+        RoleCreateTask::from(role).into_request()
     }
 
     /// Updates specific field within a role.
@@ -50,12 +51,12 @@ impl RoleManager {
     ///
     /// # Examples
     /// ```
-    /// delete_role("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3");
-    /// delete_role("Admin");
+    /// delete_role("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3").await;
+    /// delete_role("Admin").await;
     /// ```
-    pub fn delete_role(identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn delete_role(identifier: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::delete_role_request(identifier);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
 
     /// Composes a role create request.
@@ -72,13 +73,10 @@ impl RoleManager {
     /// Self::delete_role_request("Administrator")
     /// ```
     fn delete_role_request(identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            RoleDeleteTask {
-                identifier: String::from(identifier),
-            },
-            TaskType::Role,
-            "role_delete",
-        )
+        RoleDeleteTask {
+            identifier: String::from(identifier),
+        }
+        .into_request()
     }
 
     /// Updates specific field within a role.
@@ -90,12 +88,12 @@ impl RoleManager {
     ///
     /// # Examples
     /// ```
-    /// update_role("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3", "role_name", "Admin");
-    /// update_role("Admin", "role_name", "Administrator");
+    /// update_role("dd2546c3-e34a-4fcb-9b12-1a96eb6873e3", "role_name", "Admin").await;
+    /// update_role("Admin", "role_name", "Administrator").await;
     /// ```
-    pub fn update_role(search_by: &str, update_for: &str, value: &str) -> TaskResult<TaskStatus> {
+    pub async fn update_role(search_by: &str, update_for: &str, value: &str) -> TaskResult<TaskStatus> {
         let request = Self::update_role_request(search_by, update_for, value);
-        TaskManager::process_task(request)
+        TaskManager::process_task(request).await
     }
 
     /// Composes a role update request.
@@ -110,15 +108,12 @@ impl RoleManager {
     /// let task_response = update_role_request("Administrator", "role_name", "Admin");
     /// ```
     fn update_role_request(search_by: &str, update_for: &str, value: &str) -> TaskRequest {
-        TaskRequest::compose_request::<RoleUpdateTask>(
-            RoleUpdateTask {
-                search_by: search_by.to_string(),
-                update_for: update_for.to_string(),
-                value: value.to_string(),
-            },
-            TaskType::Role,
-            "role_update",
-        )
+        RoleUpdateTask {
+            search_by: search_by.to_string(),
+            update_for: update_for.to_string(),
+            value: value.to_string(),
+        }
+        .into_request()
     }
 
     /// Read a specific role
@@ -128,11 +123,11 @@ impl RoleManager {
     ///
     /// # Examples
     /// ```
-    /// get_role("Administrator");
+    /// get_role("Administrator").await;
     /// ```
-    pub fn get_role(identifier: &str) -> TaskResult<Role> {
+    pub async fn get_role(identifier: &str) -> TaskResult<Role> {
         let request = Self::read_role_request(identifier);
-        TaskManager::process_task_with_result::<Role>(request)
+        TaskManager::process_task_with_result::<Role>(request).await
     }
 
     /// Add a permission to a role
@@ -143,11 +138,11 @@ impl RoleManager {
     ///
     /// # Examples
     /// ```
-    /// link_permission_to_role("Administrator", "ban.user");
+    /// link_permission_to_role("Administrator", "ban.user").await;
     /// ```
-    pub fn link_permission_to_role(role_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn link_permission_to_role(role_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
         let request = Self::add_role_add_permission_request(role_identifier, permission_identifier);
-        TaskManager::process_task(request)
+        TaskManager::process_task(request).await
     }
 
     /// Composes an add role permission request.
@@ -161,14 +156,11 @@ impl RoleManager {
     /// let task_response = add_role_add_permission_request("55d9b8a5-167c-4ca3-9387-a62d8fad0394", "e028e747-0ef6-49b9-8ae8-00212c455d16");
     /// ```
     fn add_role_add_permission_request(role_identifier: &str, permission_identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request::<RolePermissionLinkToRole>(
-            RolePermissionLinkToRole {
-                role_id: String::from(role_identifier),
-                permission_id: String::from(permission_identifier),
-            },
-            TaskType::Role,
-            "role_add_permission",
-        )
+        RolePermissionLinkToRole {
+            role_id: String::from(role_identifier),
+            permission_id: String::from(permission_identifier),
+        }
+        .into_request()
     }
 
     /// Deletes a permission to a role
@@ -179,11 +171,11 @@ impl RoleManager {
     ///
     /// # Examples
     /// ```
-    /// delete_permission_from_role("Administrator", "Admin Ban");
+    /// delete_permission_from_role("Administrator", "Admin Ban").await;
     /// ```
-    pub fn delete_permission_from_role(role_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn delete_permission_from_role(role_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
         let request = Self::remove_role_add_permission_request(role_identifier, permission_identifier);
-        TaskManager::process_task(request)
+        TaskManager::process_task(request).await
     }
 
     /// Composes an remove role permission request.
@@ -197,14 +189,11 @@ impl RoleManager {
     /// let task_response = remove_role_add_permission_request("55d9b8a5-167c-4ca3-9387-a62d8fad0394", "e028e747-0ef6-49b9-8ae8-00212c455d16");
     /// ```
     fn remove_role_add_permission_request(role_identifier: &str, permission_identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request::<RolePermissionDeleteLinkToRole>(
-            RolePermissionDeleteLinkToRole {
-                role_id: String::from(role_identifier),
-                permission_id: String::from(permission_identifier),
-            },
-            TaskType::Role,
-            "role_delete_permission",
-        )
+        RolePermissionDeleteLinkToRole {
+            role_id: String::from(role_identifier),
+            permission_id: String::from(permission_identifier),
+        }
+        .into_request()
     }
 
     /// Composes a role update request.
@@ -217,13 +206,10 @@ impl RoleManager {
     /// let task_response = read_role_request("Administrator");
     /// ```
     fn read_role_request(identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request::<RoleReadTask>(
-            RoleReadTask {
-                identifier: String::from(identifier),
-            },
-            TaskType::Role,
-            "role_read",
-        )
+        RoleReadTask {
+            identifier: String::from(identifier),
+        }
+        .into_request()
     }
 
     /// Preload role cache.
@@ -231,11 +217,11 @@ impl RoleManager {
     /// # Examples
     /// ```
     /// // Assuming `role` is a reference to a valid Permission
-    /// preload_role_cache();
+    /// preload_role_cache().await;
     /// ```
-    pub fn preload_role_cache() -> TaskResult<TaskStatus> {
+    pub async fn preload_role_cache() -> TaskResult<TaskStatus> {
         let request = Self::preload_role_request();
-        TaskManager::process_task(request)
+        TaskManager::process_task(request).await
     }
 
     /// Composes a permission preload cache request.
@@ -245,6 +231,6 @@ impl RoleManager {
     /// let task_response = preload_permission_request();
     /// ```
     fn preload_role_request() -> TaskRequest {
-        TaskRequest::compose_request(RolePreloadCache {}, TaskType::Role, "role_preload_cache")
+        RolePreloadCache {}.into_request()
     }
 }
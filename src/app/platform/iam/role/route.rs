@@ -0,0 +1,112 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::app::{
+    platform::{
+        iam::access::{layer::RequirePermission, ADMIN_PERMISSION},
+        response::{CustomJsonResponse, ErrorJsonResponse},
+    },
+    service::{cache::LocalizedCache, task::error::TaskError},
+};
+
+use super::{cache::RoleCache, manager::RoleManager, model::Role};
+
+#[derive(Deserialize)]
+pub struct RoleCreateBody {
+    pub role_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct RolePermissionBody {
+    pub permission: String,
+}
+
+/// Admin routes over `RoleManager`, all guarded by `ADMIN_PERMISSION`.
+///
+/// # Routes
+///
+/// * `GET /iam/roles` - List every role inside of the `RoleCache`.
+/// * `POST /iam/roles` - Create a role ex: `{"role_name": "Admin"}`.
+/// * `GET /iam/roles/:role` - Read a role by id or name.
+/// * `PATCH /iam/roles/:role` - Update the fields inside of the body ex: `{"role_name": "Administrator"}`.
+/// * `DELETE /iam/roles/:role` - Delete a role.
+/// * `POST /iam/roles/:role/permissions` - Link a permission ex: `{"permission": "ban.user"}`.
+/// * `DELETE /iam/roles/:role/permissions/:permission` - Unlink a permission.
+pub fn router() -> Router {
+    Router::new()
+        .route("/iam/roles", get(list_roles).post(create_role))
+        .route(
+            "/iam/roles/:role",
+            get(get_role).patch(update_role).delete(delete_role),
+        )
+        .route("/iam/roles/:role/permissions", post(link_permission))
+        .route(
+            "/iam/roles/:role/permissions/:permission",
+            delete(unlink_permission),
+        )
+        .route_layer(RequirePermission(ADMIN_PERMISSION))
+}
+
+async fn list_roles() -> CustomJsonResponse<Vec<Role>> {
+    let mut roles = RoleCache::all();
+    roles.sort_by(|a, b| a.role_name.cmp(&b.role_name));
+    CustomJsonResponse::new(StatusCode::OK, roles)
+}
+
+async fn create_role(
+    Json(body): Json<RoleCreateBody>,
+) -> Result<CustomJsonResponse<Role>, ErrorJsonResponse> {
+    let role = Role::builder().role_name(&body.role_name).build();
+    let role_id = role.role_id.clone();
+    RoleManager::create_role(role).await?;
+    let role = RoleManager::get_role(&role_id).await?;
+    Ok(CustomJsonResponse::new(StatusCode::CREATED, role))
+}
+
+async fn get_role(Path(role): Path<String>) -> Result<CustomJsonResponse<Role>, ErrorJsonResponse> {
+    let role = RoleManager::get_role(&role).await?;
+    Ok(CustomJsonResponse::new(StatusCode::OK, role))
+}
+
+/// Applies every field of the body, the role is looked up by its id so
+/// renaming it does not affect the rest of the update.
+async fn update_role(
+    Path(role): Path<String>,
+    Json(fields): Json<Map<String, Value>>,
+) -> Result<CustomJsonResponse<Role>, ErrorJsonResponse> {
+    let role_id = RoleManager::get_role(&role).await?.role_id;
+    for (update_for, value) in &fields {
+        let value = match value {
+            Value::String(v) => v,
+            _ => return Err(TaskError::RoleFieldNotFound.into()),
+        };
+        RoleManager::update_role(&role_id, update_for, value).await?;
+    }
+    get_role(Path(role_id)).await
+}
+
+async fn delete_role(Path(role): Path<String>) -> Result<StatusCode, ErrorJsonResponse> {
+    RoleManager::delete_role(&role).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn link_permission(
+    Path(role): Path<String>,
+    Json(body): Json<RolePermissionBody>,
+) -> Result<CustomJsonResponse<Role>, ErrorJsonResponse> {
+    RoleManager::link_permission_to_role(&role, &body.permission).await?;
+    get_role(Path(role)).await
+}
+
+async fn unlink_permission(
+    Path((role, permission)): Path<(String, String)>,
+) -> Result<CustomJsonResponse<Role>, ErrorJsonResponse> {
+    RoleManager::delete_permission_from_role(&role, &permission).await?;
+    get_role(Path(role)).await
+}
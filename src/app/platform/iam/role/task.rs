@@ -2,136 +2,63 @@ use crate::app::{
     platform::iam::permission::cache::PermissionCache,
     service::cache::{notify_cache_hit, notify_cache_miss, LocalizedCache},
 };
+use std::time::Duration;
+
+use ark_macros::Task;
 use axum::async_trait;
 use serde::{Deserialize, Serialize};
 
 use crate::app::{
     database::postgres::PostgresDatabase,
+    platform::iam::sync::{IamCacheChange, IamCacheSync},
     service::task::{
         error::TaskError,
         message::{TaskRequest, TaskResponse, TaskStatus},
-        Task, TaskHandler,
+        registry::TaskDefinition,
+        retry::Transient,
+        schedule::{Schedule, ScheduledJob, Scheduler},
+        Task,
     },
 };
 
 use super::{cache::RoleCache, model::Role};
 
-pub struct RoleTaskHandler;
-
-#[async_trait]
-impl TaskHandler<PostgresDatabase> for RoleTaskHandler {
-    async fn handle(pg: &PostgresDatabase, task_request: TaskRequest) -> TaskResponse {
-        if task_request.task_action.eq("role_create") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<RoleCreateTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return RoleCreateTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("role_update") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<RoleUpdateTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return RoleUpdateTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("role_delete") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<RoleDeleteTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return RoleDeleteTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("role_read") {
-            let payload = match TaskRequest::intepret_request_payload::<RoleReadTask>(&task_request)
-            {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return RoleReadTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("role_add_permission") {
-            let payload = match TaskRequest::intepret_request_payload::<RolePermissionLinkToRole>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return RolePermissionLinkToRole::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("role_delete_permission") {
-            let payload = match TaskRequest::intepret_request_payload::<
-                RolePermissionDeleteLinkToRole,
-            >(&task_request)
-            {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return RolePermissionDeleteLinkToRole::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("role_preload_cache") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<RolePreloadCache>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return RolePreloadCache::run(pg, task_request, payload).await;
-        }
+/// Registers every role task with the `TaskRegistry`.
+///
+/// # Examples
+/// ```
+/// register_tasks();
+/// ```
+pub fn register_tasks() {
+    RoleCreateTask::register();
+    RoleUpdateTask::register();
+    RoleDeleteTask::register();
+    RoleReadTask::register();
+    RolePermissionLinkToRole::register();
+    RolePermissionDeleteLinkToRole::register();
+    RolePreloadCache::register();
+}
 
-        return TaskResponse::throw_failed_response(
-            task_request,
-            vec![TaskError::FailedToFindAction.to_string()],
-        );
-    }
+/// Defines the scheduled role jobs.
+///
+/// # Examples
+/// ```
+/// register_jobs();
+/// ```
+pub fn register_jobs() {
+    // `ROLE_CACHE` lives in every process, so every instance rebuilds its own.
+    Scheduler::register(
+        ScheduledJob::new("role_rebuild_cache", Schedule::every(Duration::from_secs(300)), || {
+            RolePreloadCache {}.into_request()
+        })
+        .per_instance(),
+    );
 }
 
 // create role
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_create")]
 pub struct RoleCreateTask {
     pub role_id: String,
     pub role_name: String,
@@ -156,18 +83,24 @@ impl Task<PostgresDatabase, TaskRequest, RoleCreateTask> for RoleCreateTask {
         param: RoleCreateTask,
     ) -> TaskResponse {
         // somehow integrate role_permissions
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare("INSERT INTO iam_roles (id, role_name) VALUES ($1, $2)")
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool
             .execute(&stmt, &[&param.role_id, &param.role_name])
             .await
         {
             Ok(_) => {
                 let role = Role::from(param);
-                RoleCache::add(role.clone());
+                IamCacheSync::apply(IamCacheChange::RoleSaved(role.clone())).await;
                 return TaskResponse::compose_response(
                     request,
                     TaskStatus::Completed,
@@ -175,6 +108,9 @@ impl Task<PostgresDatabase, TaskRequest, RoleCreateTask> for RoleCreateTask {
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -186,7 +122,8 @@ impl Task<PostgresDatabase, TaskRequest, RoleCreateTask> for RoleCreateTask {
 }
 
 // role update permission
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_update")]
 pub(super) struct RoleUpdateTask {
     pub search_by: String,
     pub update_for: String,
@@ -200,7 +137,10 @@ impl Task<PostgresDatabase, TaskRequest, RoleUpdateTask> for RoleUpdateTask {
         request: TaskRequest,
         param: RoleUpdateTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         if param.update_for.eq_ignore_ascii_case("id") {
             return TaskResponse::throw_failed_response(
                 request,
@@ -223,6 +163,9 @@ impl Task<PostgresDatabase, TaskRequest, RoleUpdateTask> for RoleUpdateTask {
             .await
         {
             Ok(v) => v,
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -235,19 +178,18 @@ impl Task<PostgresDatabase, TaskRequest, RoleUpdateTask> for RoleUpdateTask {
             .await
         {
             Ok(v) => {
-                if v.len() != 0 {
-                    let old_role = RoleCache::get(v.get(0)).unwrap();
-                    RoleCache::remove(&old_role.role_name).unwrap();
-                    // TODO THIS....
-                    // TODO THIS....
-                    // TODO THIS....
-                    // TODO THIS....
-                    // NEED TO BE ABLE TO ADD SPECIFIC PERMISSIONS TO THIS
-                    RoleCache::add(Role::new(
-                        v.get(0),
-                        v.get(1),
-                        RoleCache::get(v.get(0)).unwrap().role_permissions,
-                    ));
+                if !v.is_empty() {
+                    // replaces the old name along with the role, a role this
+                    // process does not hold is dropped everywhere and read again.
+                    let change = match RoleCache::get(v.get(0)) {
+                        Ok(old_role) => IamCacheChange::RoleSaved(Role::new(
+                            v.get(0),
+                            v.get(1),
+                            old_role.role_permissions,
+                        )),
+                        Err(_) => IamCacheChange::RoleRemoved(v.get(0)),
+                    };
+                    IamCacheSync::apply(change).await;
                     return TaskResponse::compose_response(
                         request,
                         TaskStatus::Completed,
@@ -260,6 +202,9 @@ impl Task<PostgresDatabase, TaskRequest, RoleUpdateTask> for RoleUpdateTask {
                     vec![TaskError::RoleNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -271,7 +216,8 @@ impl Task<PostgresDatabase, TaskRequest, RoleUpdateTask> for RoleUpdateTask {
 }
 
 // update role
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_delete")]
 pub(super) struct RoleDeleteTask {
     pub identifier: String,
 }
@@ -283,19 +229,26 @@ impl Task<PostgresDatabase, TaskRequest, RoleDeleteTask> for RoleDeleteTask {
         request: TaskRequest,
         param: RoleDeleteTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare(
                 "DELETE FROM iam_roles
                     WHERE id = $1
                        OR role_name = $1",
             )
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool.execute(&stmt, &[&param.identifier]).await {
             Ok(v) => {
                 if v != 0 {
-                    RoleCache::remove(&param.identifier).unwrap();
+                    IamCacheSync::apply(IamCacheChange::RoleRemoved(param.identifier.clone()))
+                        .await;
                     return TaskResponse::compose_response(
                         request,
                         TaskStatus::Completed,
@@ -308,6 +261,9 @@ impl Task<PostgresDatabase, TaskRequest, RoleDeleteTask> for RoleDeleteTask {
                     vec![TaskError::RoleNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -318,7 +274,8 @@ impl Task<PostgresDatabase, TaskRequest, RoleDeleteTask> for RoleDeleteTask {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_read", idempotent, read_only)]
 pub(super) struct RoleReadTask {
     pub identifier: String,
 }
@@ -326,7 +283,10 @@ pub(super) struct RoleReadTask {
 #[async_trait]
 impl Task<PostgresDatabase, TaskRequest, RoleReadTask> for RoleReadTask {
     async fn run(db: &PostgresDatabase, request: TaskRequest, param: RoleReadTask) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match RoleCache::get(&param.identifier) {
             Ok(role) => {
                 notify_cache_hit("RoleCache", "RoleReadTask", &request.task_id);
@@ -338,29 +298,40 @@ impl Task<PostgresDatabase, TaskRequest, RoleReadTask> for RoleReadTask {
                 );
             }
             Err(_) => {
-                let stmt = pool
+                let stmt = match pool
                     .prepare(
                         "SELECT * FROM iam_roles WHERE id = $1
         OR role_name = $1;",
                     )
                     .await
-                    .unwrap();
+                {
+                    Ok(stmt) => stmt,
+                    Err(error) => return TaskResponse::throw_database_failure(request, &error),
+                };
                 match pool.query_one(&stmt, &[&param.identifier]).await {
                     Ok(row) => {
                         notify_cache_miss("RoleCache", "RoleReadTask", &request.task_id);
                         let mut role_permissions: Vec<String> = Vec::new();
-                        let stmt = pool
+                        let stmt = match pool
                             .prepare(
                                 "SELECT permission_id FROM iam_role_permission WHERE role_id = $1",
                             )
                             .await
-                            .unwrap();
+                        {
+                            Ok(stmt) => stmt,
+                            Err(error) => {
+                                return TaskResponse::throw_database_failure(request, &error)
+                            }
+                        };
                         match pool.query(&stmt, &[&row.get::<usize, String>(0)]).await {
                             Ok(permissions) => {
                                 for permission in permissions {
                                     role_permissions.push(permission.get(0))
                                 }
                             }
+                            Err(error) if error.is_transient() => {
+                                return TaskResponse::throw_database_failure(request, &error)
+                            }
                             Err(er) => {
                                 println!("{}", er);
                             }
@@ -374,6 +345,9 @@ impl Task<PostgresDatabase, TaskRequest, RoleReadTask> for RoleReadTask {
                             Vec::default(),
                         );
                     }
+                    Err(error) if error.is_transient() => {
+                        return TaskResponse::throw_database_failure(request, &error)
+                    }
                     Err(_) => {
                         return TaskResponse::throw_failed_response(
                             request,
@@ -386,7 +360,8 @@ impl Task<PostgresDatabase, TaskRequest, RoleReadTask> for RoleReadTask {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_preload_cache", idempotent, read_only)]
 pub(super) struct RolePreloadCache;
 #[async_trait]
 impl Task<PostgresDatabase, TaskRequest, RolePreloadCache> for RolePreloadCache {
@@ -395,31 +370,51 @@ impl Task<PostgresDatabase, TaskRequest, RolePreloadCache> for RolePreloadCache
         request: TaskRequest,
         _: RolePreloadCache,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool.prepare("SELECT * FROM iam_roles").await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool.prepare("SELECT * FROM iam_roles").await {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         match pool.query(&stmt, &[]).await {
             Ok(rows) => {
                 let mut amt_items = 0;
+                let mut role_ids: Vec<String> = Vec::new();
                 for row in rows {
                     let mut role_permissions: Vec<String> = Vec::new();
-                    let stmt = pool
+                    let stmt = match pool
                         .prepare("SELECT permission_id FROM iam_role_permission WHERE role_id = $1")
                         .await
-                        .unwrap();
+                    {
+                        Ok(stmt) => stmt,
+                        Err(error) => return TaskResponse::throw_database_failure(request, &error),
+                    };
                     match pool.query(&stmt, &[&row.get::<usize, String>(0)]).await {
                         Ok(permissions) => {
                             for permission in permissions {
                                 role_permissions.push(permission.get(0))
                             }
                         }
+                        Err(error) if error.is_transient() => {
+                            return TaskResponse::throw_database_failure(request, &error)
+                        }
                         Err(er) => {
                             println!("{}", er);
                         }
                     }
                     RoleCache::add(Role::new(row.get(0), row.get(1), role_permissions.clone()));
+                    role_ids.push(row.get(0));
                     amt_items += 1;
                 }
+                // drop the roles deleted since the cache was last loaded.
+                for role in RoleCache::all() {
+                    if !role_ids.contains(&role.role_id) {
+                        let _ = RoleCache::remove(&role.role_id);
+                    }
+                }
                 println!("[CACHE] cached {} role(s) cache.", amt_items);
                 return TaskResponse::compose_response(
                     request,
@@ -428,6 +423,9 @@ impl Task<PostgresDatabase, TaskRequest, RolePreloadCache> for RolePreloadCache
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -438,7 +436,8 @@ impl Task<PostgresDatabase, TaskRequest, RolePreloadCache> for RolePreloadCache
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_add_permission")]
 pub(super) struct RolePermissionLinkToRole {
     pub role_id: String,
     pub permission_id: String,
@@ -450,7 +449,10 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionLinkToRole> for RolePermi
         request: TaskRequest,
         param: RolePermissionLinkToRole,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         let role_to_id = match RoleCache::get(&param.role_id) {
             Ok(v) => v.role_id,
@@ -471,17 +473,21 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionLinkToRole> for RolePermi
             }
         };
 
-        let stmt = pool
+        let stmt = match pool
             .prepare("INSERT INTO iam_role_permission (role_id, permission_id) VALUES ($1, $2)")
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool.query(&stmt, &[&role_to_id, &permission_to_id]).await {
             Ok(_) => {
                 // overrides the existing role (because value is the value is a shared state(arc))
                 // it should reflect throughout the rest of the application
-                let mut role = RoleCache::get(&param.role_id).unwrap();
-                role.role_permissions.push(permission_to_id);
-                RoleCache::add(role);
+                if let Ok(mut role) = RoleCache::get(&param.role_id) {
+                    role.role_permissions.push(permission_to_id);
+                    IamCacheSync::apply(IamCacheChange::RoleSaved(role)).await;
+                }
                 return TaskResponse::compose_response(
                     request,
                     TaskStatus::Completed,
@@ -489,6 +495,9 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionLinkToRole> for RolePermi
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -499,7 +508,8 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionLinkToRole> for RolePermi
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Role, action = "role_delete_permission")]
 pub(super) struct RolePermissionDeleteLinkToRole {
     pub role_id: String,
     pub permission_id: String,
@@ -513,7 +523,10 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionDeleteLinkToRole>
         request: TaskRequest,
         param: RolePermissionDeleteLinkToRole,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         // role to id conversion incase the param is not an id.
         let role_to_id = match RoleCache::get(&param.role_id) {
             Ok(v) => v.role_id,
@@ -534,22 +547,26 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionDeleteLinkToRole>
             }
         };
         // conversion ends here..
-        let stmt = pool
+        let stmt = match pool
             .prepare(
                 "DELETE FROM iam_role_permission
             WHERE role_id = $1
                AND permission_id = $2",
             )
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool.query(&stmt, &[&role_to_id, &permission_to_id]).await {
             Ok(_) => {
                 // overrides the existing role (because value is the value is a shared state(arc))
                 // it should reflect throughout the rest of the application
-                let mut role = RoleCache::get(&param.role_id).unwrap();
-                role.role_permissions
-                    .retain(|permission| permission != &permission_to_id);
-                RoleCache::add(role);
+                if let Ok(mut role) = RoleCache::get(&param.role_id) {
+                    role.role_permissions
+                        .retain(|permission| permission != &permission_to_id);
+                    IamCacheSync::apply(IamCacheChange::RoleSaved(role)).await;
+                }
                 return TaskResponse::compose_response(
                     request,
                     TaskStatus::Completed,
@@ -557,6 +574,9 @@ impl Task<PostgresDatabase, TaskRequest, RolePermissionDeleteLinkToRole>
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(er) => {
                 println!("{}", er);
                 return TaskResponse::throw_failed_response(
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    Extension,
+};
+use tower_cookies::Cookies;
+
+use crate::app::{
+    ark::{ArkState, SESSION_COOKIE_NAME},
+    platform::{
+        iam::user::{manager::UserManager, model::User},
+        response::ErrorJsonResponse,
+    },
+    service::task::error::TaskError,
+};
+
+use super::{manager::SessionManager, model::SessionCookie};
+
+/// The user that owns the session attached to the request.
+///
+/// The private `pl.session` cookie is decrypted, checked against the
+/// `session:{token}:{user_id}` key in Redis and the user is then loaded
+/// through `UserManager::get_user`. Requests without a valid session
+/// are rejected with a 401, while a failing Redis or database answers with
+/// its own error so the user stays signed in.
+///
+/// # Examples
+/// ```
+/// async fn handler(CurrentUser { user, .. }: CurrentUser) -> String {
+///     user.info.user_id
+/// }
+/// ```
+#[derive(Clone)]
+pub struct CurrentUser {
+    pub user: User,
+    pub session: SessionCookie,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CurrentUser {
+    type Rejection = ErrorJsonResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // the user may have already been resolved by a layer or another extractor.
+        if let Some(current_user) = parts.extensions.get::<CurrentUser>() {
+            return Ok(current_user.clone());
+        }
+        let Extension(ark) = Extension::<Arc<ArkState>>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| {
+                ErrorJsonResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "ArkState is missing from the router.",
+                )
+            })?;
+        let cookies = Cookies::from_request_parts(parts, state)
+            .await
+            .map_err(|(status_code, message)| ErrorJsonResponse::new(status_code, message))?;
+
+        let session = match cookies
+            .private(&ark.key)
+            .get(SESSION_COOKIE_NAME)
+            .and_then(|cookie| serde_json::from_str::<SessionCookie>(cookie.value()).ok())
+        {
+            Some(session) => session,
+            None => return Err(Self::unauthorized()),
+        };
+        // only a missing session or user signs out, failures answer as they are.
+        match SessionManager::get_session(&session.token, &session.user_id).await {
+            Ok(_) => {}
+            Err(TaskError::SessionNotFound) => return Err(Self::unauthorized()),
+            Err(er) => return Err(er.into()),
+        }
+        let user = match UserManager::get_user(&session.user_id).await {
+            Ok(user) => user,
+            Err(TaskError::UserNotFound) => return Err(Self::unauthorized()),
+            Err(er) => return Err(er.into()),
+        };
+
+        let current_user = CurrentUser { user, session };
+        parts.extensions.insert(current_user.clone());
+        Ok(current_user)
+    }
+}
+
+impl CurrentUser {
+    fn unauthorized() -> ErrorJsonResponse {
+        ErrorJsonResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "You must be signed in to access this resource.",
+        )
+    }
+}
@@ -3,10 +3,11 @@ use uuid::Uuid;
 use crate::app::service::task::{
     error::TaskResult,
     manager::TaskManager,
-    message::{TaskRequest, TaskType},
+    message::{TaskRequest, TaskStatus},
+    registry::TaskDefinition,
 };
 
-use super::{model::UserSession, task::{SessionCreateTask, SessionRevocationTask}};
+use super::{model::UserSession, task::{SessionCreateTask, SessionReadTask, SessionRevocationTask}};
 
 pub struct SessionManager;
 
@@ -23,13 +24,13 @@ impl SessionManager {
     ///     .build();
     /// create_role(role);
     /// ```
-    pub fn create_session(user_id: &str) -> TaskResult<UserSession> {
+    pub async fn create_session(user_id: &str) -> TaskResult<UserSession> {
         let task_request = Self::create_session_request(UserSession {
             token: Uuid::new_v4().as_simple().to_string(),
             expires_in: 604800,
             user_id: user_id.to_string(),
         });
-        TaskManager::process_task_with_result::<UserSession>(task_request)
+        TaskManager::process_task_with_result::<UserSession>(task_request).await
     }
 
     /// Composes a user session create request.
@@ -42,30 +43,27 @@ impl SessionManager {
     /// Self::create_role_request(role)
     /// ```
     fn create_session_request(session: UserSession) -> TaskRequest {
-        TaskRequest::compose_request(
-            SessionCreateTask {
-                token: session.token,
-                expires_in: session.expires_in,
-                user_id: session.user_id,
-            },
-            TaskType::Session,
-            "session_create",
-        )
+        SessionCreateTask {
+            token: session.token,
+            expires_in: session.expires_in,
+            user_id: session.user_id,
+        }
+        .into_request()
     }
 
-    /// Revoke a user session.
+    /// Revoke every session of a user.
     ///
     /// # Arguments
-    /// - `user_id`: who to create the sessionf or.
+    /// - `user_id`: whose sessions to revoke.
     ///
     /// # Examples
     /// ```
-    /// SessionManager::revoke_session("XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX").unwrap();
-    /// SessionManager::revoke_session("XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX").unwrap();
+    /// SessionManager::revoke_session("XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX").await.unwrap();
+    /// SessionManager::revoke_session("XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX").await.unwrap();
     /// ```
-    pub fn revoke_session(user_id: &str) -> TaskResult<UserSession> {
+    pub async fn revoke_session(user_id: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::revoke_session_request(user_id);
-        TaskManager::process_task_with_result::<UserSession>(task_request)
+        TaskManager::process_task(task_request).await
     }
 
     /// Composes a user session revocation request.
@@ -78,12 +76,42 @@ impl SessionManager {
     /// Self::revoke_session_request(user_id)
     /// ```
     fn revoke_session_request(id: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            SessionRevocationTask {
-                user_id: String::from(id),
-            },
-            TaskType::Session,
-            "session_revocation",
-        )
+        SessionRevocationTask {
+            user_id: String::from(id),
+        }
+        .into_request()
+    }
+
+    /// Read a user session, fails if the session does not exist or has expired.
+    ///
+    /// # Arguments
+    /// - `token`: the session token.
+    /// - `user_id`: the user the session belongs to.
+    ///
+    /// # Examples
+    /// ```
+    /// SessionManager::get_session("XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX", "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX").await.unwrap();
+    /// ```
+    pub async fn get_session(token: &str, user_id: &str) -> TaskResult<UserSession> {
+        let task_request = Self::get_session_request(token, user_id);
+        TaskManager::process_task_with_result::<UserSession>(task_request).await
+    }
+
+    /// Composes a user session read request.
+    ///
+    /// # Arguments
+    /// - `token`: the session token.
+    /// - `user_id`: the user id.
+    ///
+    /// # Examples
+    /// ```
+    /// Self::get_session_request(token, user_id)
+    /// ```
+    fn get_session_request(token: &str, user_id: &str) -> TaskRequest {
+        SessionReadTask {
+            token: String::from(token),
+            user_id: String::from(user_id),
+        }
+        .into_request()
     }
 }
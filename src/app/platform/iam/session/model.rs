@@ -16,4 +16,36 @@ impl UserSession {
             user_id: String::from(user_id)
         }
     }
-}
\ No newline at end of file
+}
+
+/// The contents of the private `pl.session` cookie.
+///
+/// `access_token` is the provider's token, kept so it can be revoked at the
+/// provider on logout.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionCookie {
+    pub token: String,
+    pub user_id: String,
+    pub provider: String,
+    #[serde(default)]
+    pub access_token: String,
+}
+
+impl SessionCookie {
+    pub fn new(session: &UserSession, provider: &str, access_token: &str) -> Self {
+        Self {
+            token: session.token.clone(),
+            user_id: session.user_id.clone(),
+            provider: String::from(provider),
+            access_token: String::from(access_token),
+        }
+    }
+}
+
+/// The contents of the private `pl.integrity` cookie, which ties the
+/// OAuth callback back to the login that started it.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityCookie {
+    pub csrf_token: String,
+    pub provider: String,
+}
@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::Redirect,
+    routing::{get, post},
+    Extension, Router,
+};
+use oauth2::TokenResponse;
+use serde::Deserialize;
+use tower_cookies::{
+    cookie::{time::Duration, SameSite},
+    Cookie, Cookies,
+};
+
+use crate::app::{
+    ark::{ArkState, INTEGRITY_COOKIE_NAME, SESSION_COOKIE_NAME},
+    platform::{
+        iam::user::{manager::UserManager, model::User},
+        response::ErrorJsonResponse,
+    },
+    service::task::error::TaskError,
+};
+
+use super::{
+    manager::SessionManager,
+    model::{IntegrityCookie, SessionCookie},
+};
+
+/// How long a login attempt has to complete at the provider, in seconds.
+static INTEGRITY_COOKIE_MAX_AGE: i64 = 600;
+
+/// The query parameters the provider sends back to `OAUTH2_REDIRECT_URL`.
+#[derive(Deserialize)]
+pub struct OAuthCallback {
+    pub code: String,
+    pub state: String,
+}
+
+/// Routes used to sign a user in through an OAuth provider.
+///
+/// # Routes
+///
+/// * `GET /auth/login/:provider` - Redirects to the provider's authorize url.
+/// * `GET /auth/callback` - Exchanges the code and issues the session cookie.
+/// * `POST /auth/logout` - Revokes the session and the provider token.
+pub fn router() -> Router {
+    Router::new()
+        .route("/auth/login/:provider", get(login))
+        .route("/auth/callback", get(callback))
+        .route("/auth/logout", post(logout))
+}
+
+/// Redirects the user to the authorize url of the requested provider.
+///
+/// The csrf token is stored inside of the private `pl.integrity` cookie
+/// so the callback can make sure it was the one who started the login.
+async fn login(
+    Extension(state): Extension<Arc<ArkState>>,
+    Path(provider): Path<String>,
+    cookies: Cookies,
+) -> Result<Redirect, ErrorJsonResponse> {
+    let provider = match state.auth.get_from(&provider) {
+        Ok(provider) => provider,
+        Err(er) => {
+            return Err(ErrorJsonResponse::new(
+                StatusCode::NOT_FOUND,
+                &er.to_string(),
+            ))
+        }
+    };
+    let (authorize_url, csrf_token) = provider.authorize_url();
+    let integrity = IntegrityCookie {
+        csrf_token: csrf_token.secret().to_string(),
+        provider: provider.name.clone(),
+    };
+    cookies.private(&state.key).add(
+        Cookie::build((
+            INTEGRITY_COOKIE_NAME,
+            serde_json::to_string(&integrity).unwrap(),
+        ))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(Duration::seconds(INTEGRITY_COOKIE_MAX_AGE))
+        .build(),
+    );
+    Ok(Redirect::to(&authorize_url))
+}
+
+/// Completes the login started by `login`.
+///
+/// Exchanges the code for a token, finds the user linked to the provider
+/// account (or creates one) and issues the private `pl.session` cookie.
+async fn callback(
+    Extension(state): Extension<Arc<ArkState>>,
+    Query(query): Query<OAuthCallback>,
+    cookies: Cookies,
+) -> Result<Redirect, ErrorJsonResponse> {
+    let private_cookies = cookies.private(&state.key);
+    let integrity = match private_cookies
+        .get(INTEGRITY_COOKIE_NAME)
+        .and_then(|cookie| serde_json::from_str::<IntegrityCookie>(cookie.value()).ok())
+    {
+        Some(integrity) => integrity,
+        None => {
+            return Err(ErrorJsonResponse::new(
+                StatusCode::BAD_REQUEST,
+                "The login session has expired, please try again.",
+            ))
+        }
+    };
+    private_cookies.remove(Cookie::build(INTEGRITY_COOKIE_NAME).path("/").build());
+    if integrity.csrf_token != query.state {
+        return Err(ErrorJsonResponse::new(
+            StatusCode::BAD_REQUEST,
+            "The state returned by the provider does not match.",
+        ));
+    }
+
+    let provider = match state.auth.get_from(&integrity.provider) {
+        Ok(provider) => provider,
+        Err(er) => {
+            return Err(ErrorJsonResponse::new(
+                StatusCode::NOT_FOUND,
+                &er.to_string(),
+            ))
+        }
+    };
+    let token = match provider.exchange_code(&query.code).await {
+        Ok(token) => token,
+        Err(er) => {
+            return Err(ErrorJsonResponse::new(
+                StatusCode::BAD_GATEWAY,
+                &er.to_string(),
+            ))
+        }
+    };
+    let user_info = match provider
+        .fetch_user_info(token.access_token().secret())
+        .await
+    {
+        Ok(user_info) => user_info,
+        Err(er) => {
+            return Err(ErrorJsonResponse::new(
+                StatusCode::BAD_GATEWAY,
+                &er.to_string(),
+            ))
+        }
+    };
+
+    // find the user linked to the provider account, otherwise create one.
+    let user_id = match UserManager::exchange_oauth_for_id(&user_info.id, &provider.name).await {
+        Ok(user_id) => user_id,
+        Err(TaskError::UserOAuthIdNotFound) => {
+            let mut builder = User::builder()
+                .oauth_id(&user_info.id)
+                .oauth_provider(&provider.name);
+            if let Some(username) = &user_info.username {
+                builder = builder.username(username);
+            }
+            if let Some(email) = &user_info.email {
+                builder = builder.email(email);
+            }
+            match sign_up(builder.build()).await {
+                Ok(user_id) => user_id,
+                // the username or email of the provider is taken by another user,
+                // sign up without them rather than refusing the login.
+                Err(TaskError::UserUniqueConstraint) => {
+                    sign_up(
+                        User::builder()
+                            .oauth_id(&user_info.id)
+                            .oauth_provider(&provider.name)
+                            .build(),
+                    )
+                    .await?
+                }
+                Err(er) => return Err(er.into()),
+            }
+        }
+        Err(er) => return Err(er.into()),
+    };
+
+    let session = match SessionManager::create_session(&user_id).await {
+        Ok(session) => session,
+        Err(_) => {
+            return Err(ErrorJsonResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create the session.",
+            ))
+        }
+    };
+    private_cookies.add(
+        Cookie::build((
+            SESSION_COOKIE_NAME,
+            serde_json::to_string(&SessionCookie::new(
+                &session,
+                &provider.name,
+                token.access_token().secret(),
+            ))
+            .unwrap(),
+        ))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(Duration::seconds(session.expires_in))
+        .build(),
+    );
+    Ok(Redirect::to("/"))
+}
+
+/// Creates the user of a provider account and answers its id. When another
+/// callback of the same account created it first, answers the id of that one.
+///
+/// # Arguments
+/// - `user`: the user to create, linked to the provider account.
+///
+/// # Examples
+/// ```
+/// let user_id = sign_up(user).await?;
+/// ```
+async fn sign_up(user: User) -> Result<String, TaskError> {
+    match UserManager::create_user(user.clone()).await {
+        Ok(_) => Ok(user.info.user_id),
+        Err(TaskError::UserAlreadyExists) => {
+            UserManager::exchange_oauth_for_id(&user.auth.oauth_id, &user.auth.oauth_provider)
+                .await
+        }
+        Err(er) => Err(er),
+    }
+}
+
+/// Signs the user out.
+///
+/// Revokes the provider's access token through its revocation url, revokes
+/// the user's sessions and clears the `pl.session` and `pl.integrity`
+/// cookies. A failed revocation at the provider does not keep the user
+/// signed in here.
+async fn logout(Extension(state): Extension<Arc<ArkState>>, cookies: Cookies) -> Redirect {
+    let private_cookies = cookies.private(&state.key);
+    let session = private_cookies
+        .get(SESSION_COOKIE_NAME)
+        .and_then(|cookie| serde_json::from_str::<SessionCookie>(cookie.value()).ok());
+    private_cookies.remove(Cookie::build(SESSION_COOKIE_NAME).path("/").build());
+    private_cookies.remove(Cookie::build(INTEGRITY_COOKIE_NAME).path("/").build());
+
+    let session = match session {
+        Some(session) => session,
+        None => return Redirect::to("/"),
+    };
+    // the session may have already expired, which is fine when signing out.
+    match SessionManager::revoke_session(&session.user_id).await {
+        Ok(_) | Err(TaskError::SessionNotFound) => {}
+        Err(er) => println!(
+            "[ARK] Failed to revoke the session of user {}. Error: {}",
+            session.user_id, er
+        ),
+    }
+    if !session.access_token.is_empty() {
+        if let Ok(provider) = state.auth.get_from(&session.provider) {
+            if let Err(er) = provider.revoke_token(&session.access_token).await {
+                println!(
+                    "[ARK] Failed to revoke the access token at {}. Error: {}",
+                    session.provider, er
+                );
+            }
+        }
+    }
+    Redirect::to("/")
+}
@@ -1,5 +1,6 @@
 use std::mem;
 
+use ark_macros::Task;
 use axum::async_trait;
 use bb8_redis::redis::{AsyncCommands, AsyncIter};
 use serde::{Deserialize, Serialize};
@@ -9,53 +10,44 @@ use crate::app::{
     service::task::{
         error::TaskError,
         message::{TaskRequest, TaskResponse, TaskStatus},
-        Task, TaskHandler,
+        registry::TaskDefinition,
+        retry::Transient,
+        schedule::{Schedule, ScheduledJob, Scheduler},
+        Task,
     },
 };
 
 use super::model::UserSession;
 
-pub struct SessionTaskHandler;
-
-#[async_trait]
-impl TaskHandler<RedisDatabase> for SessionTaskHandler {
-    async fn handle(redis: &RedisDatabase, task_request: TaskRequest) -> TaskResponse {
-        if task_request.task_action.eq("session_create") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<SessionCreateTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return SessionCreateTask::run(redis, task_request, payload).await;
-        }
-        
-        if task_request.task_action.eq("session_revocation") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<SessionRevocationTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return SessionRevocationTask::run(redis, task_request, payload).await;
-        }
+/// Registers every session task with the `TaskRegistry`.
+///
+/// # Examples
+/// ```
+/// register_tasks();
+/// ```
+pub fn register_tasks() {
+    SessionCreateTask::register();
+    SessionRevocationTask::register();
+    SessionReadTask::register();
+    SessionPruneTask::register();
+}
 
-        return TaskResponse::throw_failed_response(
-            task_request,
-            vec![TaskError::FailedToFindAction.to_string()],
-        );
-    }
+/// Defines the scheduled session jobs.
+///
+/// # Examples
+/// ```
+/// register_jobs();
+/// ```
+pub fn register_jobs() {
+    Scheduler::register(ScheduledJob::new(
+        "session_prune",
+        Schedule::cron("0 0 * * * *"),
+        || SessionPruneTask {}.into_request(),
+    ));
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Session, action = "session_create", db = RedisDatabase)]
 pub struct SessionCreateTask {
     pub token: String,
     pub expires_in: i64,
@@ -69,7 +61,10 @@ impl Task<RedisDatabase, TaskRequest, SessionCreateTask> for SessionCreateTask {
         request: TaskRequest,
         param: SessionCreateTask,
     ) -> TaskResponse {
-        let mut pool = db.pool.get().await.unwrap();
+        let mut pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         //let hash: Vec<(String, String)> = pool.hgetall("user-sessions").await.unwrap();
         //if let Some(existing_key) =
         //    hash.iter()
@@ -81,7 +76,10 @@ impl Task<RedisDatabase, TaskRequest, SessionCreateTask> for SessionCreateTask {
         //        .unwrap();
         //}
         let pattern = format!("session:*:{}", param.user_id);
-        let mut scan_result: AsyncIter<String> = pool.scan_match(&pattern).await.unwrap();
+        let mut scan_result: AsyncIter<String> = match pool.scan_match(&pattern).await {
+            Ok(scan_result) => scan_result,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         // need to collect into a vec to go around rust borrowing rules
         let mut sessions_to_invalidate: Vec<String> = Vec::new();
         while let Some(key_result) = scan_result.next_item().await {
@@ -91,7 +89,9 @@ impl Task<RedisDatabase, TaskRequest, SessionCreateTask> for SessionCreateTask {
         mem::drop(scan_result);
         // invalidate any existing session.
         for key in sessions_to_invalidate.iter() {
-            let _: () = pool.del(key).await.unwrap();
+            if let Err(error) = pool.del::<&str, ()>(key).await {
+                return TaskResponse::throw_database_failure(request, &error);
+            }
         }
         // Set the new session token for the user
         //  let hset_result = pool
@@ -108,9 +108,16 @@ impl Task<RedisDatabase, TaskRequest, SessionCreateTask> for SessionCreateTask {
             .await;
         match hset_result {
             Ok(_) => {
-                pool.expire::<&str, i64>(&session_key, param.expires_in) // 7 days in seconds
+                // 7 days in seconds
+                if let Err(error) = pool
+                    .expire::<&str, ()>(&session_key, param.expires_in)
                     .await
-                    .unwrap();
+                {
+                    return TaskResponse::throw_database_failure(request, &error);
+                }
+            }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error);
             }
             Err(_) => {
                 // should not happen...
@@ -129,7 +136,8 @@ impl Task<RedisDatabase, TaskRequest, SessionCreateTask> for SessionCreateTask {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Session, action = "session_revocation", db = RedisDatabase)]
 pub struct SessionRevocationTask {
     pub user_id: String,
 }
@@ -142,16 +150,22 @@ impl Task<RedisDatabase, TaskRequest, SessionRevocationTask> for SessionRevocati
         request: TaskRequest,
         param: SessionRevocationTask,
     ) -> TaskResponse {
-        let mut pool = db.pool.get().await.unwrap();
+        let mut pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         let pattern = format!("session:*:{}", param.user_id);
-        let mut scan_result: AsyncIter<String> = pool.scan_match(&pattern).await.unwrap();
+        let mut scan_result: AsyncIter<String> = match pool.scan_match(&pattern).await {
+            Ok(scan_result) => scan_result,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         // need to collect into a vec to go around rust borrowing rules
         let mut sessions_to_invalidate: Vec<String> = Vec::new();
         while let Some(key_result) = scan_result.next_item().await {
             sessions_to_invalidate.push(key_result);
         }
         // return session not found if session is not found by user id.
-        if sessions_to_invalidate.len() == 0 {
+        if sessions_to_invalidate.is_empty() {
             return TaskResponse::throw_failed_response(
                 request,
                 vec![TaskError::SessionNotFound.to_string()],
@@ -160,7 +174,9 @@ impl Task<RedisDatabase, TaskRequest, SessionRevocationTask> for SessionRevocati
         mem::drop(scan_result);
         // revocate session.
         for key in sessions_to_invalidate.iter() {
-            let _: () = pool.del(key).await.unwrap();
+            if let Err(error) = pool.del::<&str, ()>(key).await {
+                return TaskResponse::throw_database_failure(request, &error);
+            }
         }
         return TaskResponse::compose_response(
             request,
@@ -169,4 +185,92 @@ impl Task<RedisDatabase, TaskRequest, SessionRevocationTask> for SessionRevocati
             Vec::default(),
         );
     }
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Session, action = "session_read", db = RedisDatabase, idempotent, read_only)]
+pub struct SessionReadTask {
+    pub token: String,
+    pub user_id: String,
+}
+
+#[async_trait]
+impl Task<RedisDatabase, TaskRequest, SessionReadTask> for SessionReadTask {
+    async fn run(
+        db: &RedisDatabase,
+        request: TaskRequest,
+        param: SessionReadTask,
+    ) -> TaskResponse {
+        let mut pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let session_key = format!("session:{}:{}", param.token, param.user_id);
+        // -2 means the key does not exist (or has already expired).
+        match pool.ttl::<&str, i64>(&session_key).await {
+            Ok(-2) => {
+                return TaskResponse::throw_failed_response(
+                    request,
+                    vec![TaskError::SessionNotFound.to_string()],
+                );
+            }
+            Ok(ttl) => {
+                return TaskResponse::compose_response(
+                    request,
+                    TaskStatus::Completed,
+                    UserSession::new(&param.token, ttl, &param.user_id),
+                    Vec::default(),
+                );
+            }
+            // a Redis failure must not sign the user out.
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        }
+    }
+}
+
+/// Deletes the `session:*` keys left without an expiry, which happens when
+/// the `EXPIRE` after creating a session fails. Every other session expires
+/// by itself.
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = Session, action = "session_prune", db = RedisDatabase, idempotent)]
+pub struct SessionPruneTask;
+
+#[async_trait]
+impl Task<RedisDatabase, TaskRequest, SessionPruneTask> for SessionPruneTask {
+    async fn run(db: &RedisDatabase, request: TaskRequest, _: SessionPruneTask) -> TaskResponse {
+        let mut pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let mut scan_result: AsyncIter<String> = match pool.scan_match("session:*").await {
+            Ok(scan_result) => scan_result,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        // need to collect into a vec to go around rust borrowing rules
+        let mut sessions: Vec<String> = Vec::new();
+        while let Some(key_result) = scan_result.next_item().await {
+            sessions.push(key_result);
+        }
+        mem::drop(scan_result);
+        let mut amt_items = 0;
+        for key in sessions.iter() {
+            // -1 means the key exists without an expiry.
+            match pool.ttl::<&str, i64>(key).await {
+                Ok(-1) => {}
+                Ok(_) => continue,
+                Err(error) => return TaskResponse::throw_database_failure(request, &error),
+            }
+            if let Err(error) = pool.del::<&str, ()>(key).await {
+                return TaskResponse::throw_database_failure(request, &error);
+            }
+            amt_items += 1;
+        }
+        println!("[TASK] pruned {} stale session(s).", amt_items);
+        return TaskResponse::compose_response(
+            request,
+            TaskStatus::Completed,
+            String::default(),
+            Vec::default(),
+        );
+    }
+}
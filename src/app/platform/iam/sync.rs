@@ -0,0 +1,245 @@
+use bb8_redis::redis::{
+    streams::{StreamMaxlen, StreamRangeReply, StreamReadOptions},
+    AsyncCommands, RedisResult,
+};
+use nanoid::nanoid;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    database::redis::RedisDatabase,
+    service::{
+        cache::LocalizedCache,
+        task::stream::{from_run_error, read_stream},
+    },
+};
+
+use super::{
+    permission::{cache::PermissionCache, model::Permission},
+    role::{cache::RoleCache, model::Role},
+};
+
+/// The stream every change of the in-memory IAM caches is added to.
+static CHANGE_STREAM: &str = "ark:iam:cache-changes";
+
+/// The field of a stream entry holding an `IamCacheChange`.
+static CHANGE_FIELD: &str = "change";
+
+/// The field of a stream entry holding the process that made the change.
+static ORIGIN_FIELD: &str = "origin";
+
+/// How many changes the stream keeps about, readers only ever need the latest.
+static MAX_CHANGES: usize = 10_000;
+
+/// How long a read waits for new changes, in milliseconds.
+static BLOCK_MS: usize = 5000;
+
+/// The name of this process inside of the stream, so it skips its own changes.
+static ORIGIN: Lazy<String> = Lazy::new(|| nanoid!(12));
+
+/// Where changes are shared with the other processes, set by `IamCacheSync::share`.
+static SHARED: OnceCell<RedisDatabase> = OnceCell::new();
+
+/// A change of the `PermissionCache` or `RoleCache` made by a task.
+///
+/// A saved item replaces every key the cached one had, so a renamed
+/// permission or role is no longer found under its old name.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum IamCacheChange {
+    PermissionSaved(Permission),
+    /// Holds the id, name or key of the permission.
+    PermissionRemoved(String),
+    RoleSaved(Role),
+    /// Holds the id or name of the role.
+    RoleRemoved(String),
+}
+
+impl IamCacheChange {
+    fn apply_locally(self) {
+        match self {
+            IamCacheChange::PermissionSaved(permission) => {
+                let _ = PermissionCache::remove(&permission.permission_id);
+                PermissionCache::add(permission);
+            }
+            IamCacheChange::PermissionRemoved(identifier) => {
+                let _ = PermissionCache::remove(&identifier);
+            }
+            IamCacheChange::RoleSaved(role) => {
+                let _ = RoleCache::remove(&role.role_id);
+                RoleCache::add(role);
+            }
+            IamCacheChange::RoleRemoved(identifier) => {
+                let _ = RoleCache::remove(&identifier);
+            }
+        }
+    }
+}
+
+/// Keeps the in-memory IAM caches of every process using the same Redis in
+/// step, so permission, role, user and batch tasks may run on an `ark-worker`
+/// while the API nodes check access against their own caches.
+pub struct IamCacheSync;
+
+impl IamCacheSync {
+    /// Starts applying the changes other processes make. Only the first call
+    /// is used, call it before the caches are preloaded so no change made in
+    /// between is missed.
+    ///
+    /// # Arguments
+    /// - `redis`: the `RedisDatabase` the changes go through.
+    ///
+    /// # Examples
+    /// ```
+    /// IamCacheSync::share(redis).await;
+    /// ```
+    pub async fn share(redis: RedisDatabase) {
+        if SHARED.set(redis.clone()).is_err() {
+            return;
+        }
+        // changes made before this process started are in the database already.
+        let mut last_id = Self::latest_id(&redis)
+            .await
+            .unwrap_or_else(|_| String::from("$"));
+        tokio::spawn(async move {
+            let options = StreamReadOptions::default().block(BLOCK_MS);
+            loop {
+                match read_stream(&redis, CHANGE_STREAM, &last_id, &options).await {
+                    Ok(entries) => {
+                        for entry in entries {
+                            last_id = entry.id.clone();
+                            if entry.get::<String>(ORIGIN_FIELD).as_deref() == Some(&ORIGIN) {
+                                continue;
+                            }
+                            if let Some(change) =
+                                entry.get::<String>(CHANGE_FIELD).and_then(|change| {
+                                    serde_json::from_str::<IamCacheChange>(&change).ok()
+                                })
+                            {
+                                change.apply_locally();
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        println!(
+                            "[ARK] Failed to read the IAM cache changes. Error: {}",
+                            error
+                        );
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+        println!("[ARK] IAM caches are now shared through {}.", CHANGE_STREAM);
+    }
+
+    /// Applies a change to the caches of this process, then shares it with
+    /// the other processes when `share` was called.
+    ///
+    /// # Arguments
+    /// - `change`: what changed in the caches.
+    ///
+    /// # Examples
+    /// ```
+    /// IamCacheSync::apply(IamCacheChange::RoleSaved(role)).await;
+    /// ```
+    pub async fn apply(change: IamCacheChange) {
+        let Some(redis) = SHARED.get() else {
+            change.apply_locally();
+            return;
+        };
+        let payload = serde_json::to_string(&change).unwrap();
+        change.apply_locally();
+        let result: RedisResult<String> = async {
+            let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+            connection
+                .xadd_maxlen(
+                    CHANGE_STREAM,
+                    StreamMaxlen::Approx(MAX_CHANGES),
+                    "*",
+                    &[
+                        (CHANGE_FIELD, payload.as_str()),
+                        (ORIGIN_FIELD, ORIGIN.as_str()),
+                    ],
+                )
+                .await
+        }
+        .await;
+        if let Err(error) = result {
+            println!(
+                "[ARK] Failed to share an IAM cache change, other processes keep the old one until they preload. Error: {}",
+                error
+            );
+        }
+    }
+
+    /// The id of the latest change, `0` when there is none yet.
+    async fn latest_id(redis: &RedisDatabase) -> RedisResult<String> {
+        let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+        let latest: StreamRangeReply = connection
+            .xrevrange_count(CHANGE_STREAM, "+", "-", 1)
+            .await?;
+        Ok(latest
+            .ids
+            .first()
+            .map_or(String::from("0"), |entry| entry.id.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn a_saved_permission_replaces_its_old_keys() {
+        let permission = Permission::new(
+            &Uuid::new_v4().to_string(),
+            &format!("before-{}", Uuid::new_v4()),
+            &format!("before.{}", Uuid::new_v4()),
+        );
+        PermissionCache::add(permission.clone());
+        let renamed = Permission::new(
+            &permission.permission_id,
+            &format!("after-{}", Uuid::new_v4()),
+            &permission.permission_key,
+        );
+
+        IamCacheChange::PermissionSaved(renamed.clone()).apply_locally();
+
+        assert!(PermissionCache::get(&permission.permission_name).is_err());
+        assert_eq!(
+            PermissionCache::get(&renamed.permission_name).unwrap(),
+            renamed
+        );
+        assert_eq!(
+            PermissionCache::get(&renamed.permission_key).unwrap(),
+            renamed
+        );
+    }
+
+    #[test]
+    fn a_removed_role_is_gone_under_every_key() {
+        let role = Role::new(
+            &Uuid::new_v4().to_string(),
+            &format!("role-{}", Uuid::new_v4()),
+            Vec::new(),
+        );
+        RoleCache::add(role.clone());
+
+        IamCacheChange::RoleRemoved(role.role_name.clone()).apply_locally();
+
+        assert!(RoleCache::get(&role.role_id).is_err());
+        assert!(RoleCache::get(&role.role_name).is_err());
+    }
+
+    #[test]
+    fn changes_round_trip_through_json() {
+        let change = IamCacheChange::RoleSaved(Role::new("1", "Admin", vec![String::from("2")]));
+        let json = serde_json::to_string(&change).unwrap();
+        assert_eq!(
+            serde_json::from_str::<IamCacheChange>(&json).unwrap(),
+            change
+        );
+    }
+}
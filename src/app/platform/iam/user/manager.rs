@@ -12,16 +12,17 @@ use crate::app::{
         task::{
             error::TaskResult,
             manager::TaskManager,
-            message::{TaskRequest, TaskStatus, TaskType},
+            message::{TaskRequest, TaskStatus},
+            registry::TaskDefinition,
         },
     },
 };
 
 use super::{
     cache::{UserAddToCache, UserReadFromCache},
-    model::{User, UserSecurity},
+    model::{User, UserPatch, UserSecurity},
     task::{
-        UserAddPermission, UserAddRole, UserCreateSecurityToken, UserCreateTask, UserDeletePermission, UserDeleteRole, UserExchangeOAuthIdForId, UserPreloadCache, UserReadTask, UserUpdateTask
+        UserAddPermission, UserAddRole, UserCreateSecurityToken, UserCreateTask, UserDeletePermission, UserDeleteRole, UserExchangeOAuthIdForId, UserPatchTask, UserPreloadCache, UserReadTask, UserUpdateTask
     },
 };
 
@@ -35,13 +36,13 @@ impl UserManager {
     ///
     /// # Examples
     /// ```
-    /// create_user(user);
+    /// create_user(user).await;
     /// ```
-    pub fn create_user(user: User) -> TaskResult<TaskStatus> {
+    pub async fn create_user(user: User) -> TaskResult<TaskStatus> {
         let task_request = Self::create_user_request(user.clone());
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
         /*
-        match TaskManager::process_task(task_request) {
+        match TaskManager::process_task(task_request).await {
             Ok(v) => {
                 UserCacheManager::create_user_cache(user).unwrap();
                 return Ok(v)
@@ -61,7 +62,7 @@ impl UserManager {
     /// create_user_request(user);
     /// ```
     fn create_user_request(user: User) -> TaskRequest {
-        TaskRequest::compose_request(UserCreateTask { user }, TaskType::User, "user_create")
+        UserCreateTask { user }.into_request()
     }
 
     /// Retrieve information about a specific user by their uuid
@@ -71,11 +72,11 @@ impl UserManager {
     ///
     /// # Examples
     /// ```
-    /// get_user("2f4afce2-ec56-429a-96b1-480c0b20943a");
+    /// get_user("2f4afce2-ec56-429a-96b1-480c0b20943a").await;
     /// ```
-    pub fn get_user(identifier: &str) -> TaskResult<User> {
+    pub async fn get_user(identifier: &str) -> TaskResult<User> {
         let task_request = Self::get_user_request(&String::from(identifier));
-        TaskManager::process_task_with_result::<User>(task_request)
+        TaskManager::process_task_with_result::<User>(task_request).await
     }
 
     /// get user request
@@ -88,13 +89,10 @@ impl UserManager {
     /// get_user_request("2f4afce2-ec56-429a-96b1-480c0b20943a");
     /// ```
     fn get_user_request(identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserReadTask {
-                identifier: String::from(identifier),
-            },
-            TaskType::User,
-            "user_read",
-        )
+        UserReadTask {
+            identifier: String::from(identifier),
+        }
+        .into_request()
     }
 
     /// Update a specific field for the specified user.
@@ -106,11 +104,11 @@ impl UserManager {
     ///
     /// # Examples
     /// ```
-    /// update_user("chomnr", "email", "newchomnr@gmail.com");
-    /// update_user("2f4afce2-ec56-429a-96b1-480c0b20943a", "email", "newchomnr@gmail.com");
-    /// update_user("chomnr@gmail.com", "email", "newchomnr@gmail.com");
+    /// update_user("chomnr", "email", "newchomnr@gmail.com").await;
+    /// update_user("2f4afce2-ec56-429a-96b1-480c0b20943a", "email", "newchomnr@gmail.com").await;
+    /// update_user("chomnr@gmail.com", "email", "newchomnr@gmail.com").await;
     /// ``
-    pub fn update_user(search_by: &str, update_for: &str, value: &str) -> TaskResult<TaskStatus> {
+    pub async fn update_user(search_by: &str, update_for: &str, value: &str) -> TaskResult<TaskStatus> {
         let mut cache_request =
             Self::update_user_task_request::<String>(search_by, update_for, value);
         if update_for.eq_ignore_ascii_case("verified") {
@@ -122,7 +120,40 @@ impl UserManager {
         {
             cache_request = Self::update_user_task_request::<i64>(search_by, update_for, value);
         }
-        TaskManager::process_task(cache_request)
+        TaskManager::process_task(cache_request).await
+    }
+
+    /// Update every field of a patch at once, a patch that fails changes nothing.
+    ///
+    /// # Arguments
+    /// - `search_by`: the user identifier, id, username or email.
+    /// - `patch`: the fields to update.
+    ///
+    /// # Examples
+    /// ```
+    /// patch_user("chomnr", UserPatch::from_fields(&fields)?).await;
+    /// ```
+    pub async fn patch_user(search_by: &str, patch: UserPatch) -> TaskResult<TaskStatus> {
+        let task_request = Self::patch_user_request(search_by, patch);
+        TaskManager::process_task(task_request).await
+    }
+
+    /// Composes a patch user request.
+    ///
+    /// # Arguments
+    /// - `search_by`: the user identifier, id, username or email.
+    /// - `patch`: the fields to update.
+    ///
+    /// # Examples
+    /// ```
+    /// patch_user_request("chomnr", patch);
+    /// ```
+    fn patch_user_request(search_by: &str, patch: UserPatch) -> TaskRequest {
+        UserPatchTask {
+            search_by: String::from(search_by),
+            patch,
+        }
+        .into_request()
     }
 
     /// Read user from cache request.
@@ -144,43 +175,34 @@ impl UserManager {
         value: &str,
     ) -> TaskRequest {
         if TypeId::of::<T>() == TypeId::of::<str>() || TypeId::of::<T>() == TypeId::of::<String>() {
-            return TaskRequest::compose_request(
-                UserUpdateTask {
-                    search_by: String::from(search_by),
-                    update_for: String::from(update_for),
-                    value: String::from(value),
-                },
-                TaskType::User,
-                "user_update",
-            );
+            return UserUpdateTask {
+                search_by: String::from(search_by),
+                update_for: String::from(update_for),
+                value: String::from(value),
+            }
+            .into_request();
         }
 
         if TypeId::of::<T>() == TypeId::of::<bool>() {
-            return TaskRequest::compose_request(
-                UserUpdateAsBooleanTask {
-                    search_by: String::from(search_by),
-                    update_for: String::from(update_for),
-                    value: value
-                        .parse::<bool>()
-                        .expect("[ARC] update_user_task_request value is not of a bool type."),
-                },
-                TaskType::User,
-                "user_update_as_boolean",
-            );
+            return UserUpdateAsBooleanTask {
+                search_by: String::from(search_by),
+                update_for: String::from(update_for),
+                value: value
+                    .parse::<bool>()
+                    .expect("[ARC] update_user_task_request value is not of a bool type."),
+            }
+            .into_request();
         }
 
         if TypeId::of::<T>() == TypeId::of::<i64>() {
-            return TaskRequest::compose_request(
-                UserUpdateAsIntegerTask {
-                    search_by: String::from(search_by),
-                    update_for: String::from(update_for),
-                    value: value
-                        .parse::<i64>()
-                        .expect("[ARC] update_user_task_request value is not of a integer type."),
-                },
-                TaskType::User,
-                "user_update_as_integer",
-            );
+            return UserUpdateAsIntegerTask {
+                search_by: String::from(search_by),
+                update_for: String::from(update_for),
+                value: value
+                    .parse::<i64>()
+                    .expect("[ARC] update_user_task_request value is not of a integer type."),
+            }
+            .into_request();
         }
         panic!("[ARC] update_user_task_request unsupported conversion type")
     }
@@ -198,9 +220,9 @@ impl UserManager {
     /// // Assuming `permission` is a reference to a valid Permission
     /// preload_user_cache_request();
     /// ```
-    pub fn create_security_token(search_by: &str, action: &str) -> TaskResult<UserSecurity> {
+    pub async fn create_security_token(search_by: &str, action: &str) -> TaskResult<UserSecurity> {
         let task_request = Self::create_security_token_request(search_by, action);
-        TaskManager::process_task_with_result::<UserSecurity>(task_request)
+        TaskManager::process_task_with_result::<UserSecurity>(task_request).await
     }
 
     /// Composes a security token request.
@@ -210,14 +232,11 @@ impl UserManager {
     /// let task_response = preload_permission_request();
     /// ```
     fn create_security_token_request(search_by: &str, action: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserCreateSecurityToken {
-                search_by: String::from(search_by),
-                action: String::from(action),
-            },
-            TaskType::User,
-            "user_create_security_token",
-        )
+        UserCreateSecurityToken {
+            search_by: String::from(search_by),
+            action: String::from(action),
+        }
+        .into_request()
     }
 
     /// Exchange an oauth_id for a user_id.
@@ -228,11 +247,11 @@ impl UserManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// exchange_oauth_for_id("oauth_id_here", "discord");
+    /// exchange_oauth_for_id("oauth_id_here", "discord").await;
     /// ```
-    pub fn exchange_oauth_for_id(oauth_id: &str, oauth_provider: &str) -> TaskResult<String> {
+    pub async fn exchange_oauth_for_id(oauth_id: &str, oauth_provider: &str) -> TaskResult<String> {
         let task_request = Self::exchange_oauth_for_id_request(oauth_id, oauth_provider);
-        TaskManager::process_task_with_result::<String>(task_request)
+        TaskManager::process_task_with_result::<String>(task_request).await
     }
 
     /// Composes a exchange oauth for id request.
@@ -246,14 +265,11 @@ impl UserManager {
     /// let task_response = preload_permission_request();
     /// ```
     fn exchange_oauth_for_id_request(oauth_id: &str, oauth_provider: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserExchangeOAuthIdForId {
-                oauth_id: String::from(oauth_id),
-                provider: String::from(oauth_provider),
-            },
-            TaskType::User,
-            "user_exchange_oauthid_for_id",
-        )
+        UserExchangeOAuthIdForId {
+            oauth_id: String::from(oauth_id),
+            provider: String::from(oauth_provider),
+        }
+        .into_request()
     }
 
     /// Add permission to user.
@@ -264,11 +280,11 @@ impl UserManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// add_permission_to_user("oauth_id_here", "discord");
+    /// add_permission_to_user("oauth_id_here", "discord").await;
     /// ```
-    pub fn add_permission_to_user(user_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn add_permission_to_user(user_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::add_permission_to_user_request(user_identifier, permission_identifier);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
     
     /// Composes a add user permission to a user request.
@@ -282,14 +298,11 @@ impl UserManager {
     /// add_permission_to_user_request("user_identifier", "permission_identifier");
     /// ```
     fn add_permission_to_user_request(user_identifier: &str, permission_identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserAddPermission {
-                target_user_id: String::from(user_identifier),
-                permission_identifier: String::from(permission_identifier),
-            },
-            TaskType::User,
-            "user_add_permission",
-        )
+        UserAddPermission {
+            target_user_id: String::from(user_identifier),
+            permission_identifier: String::from(permission_identifier),
+        }
+        .into_request()
     }
 
     /// Add permission to user.
@@ -300,11 +313,11 @@ impl UserManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// delete_permission_from_user("oauth_id_here", "discord");
+    /// delete_permission_from_user("oauth_id_here", "discord").await;
     /// ```
-    pub fn delete_permission_from_user(user_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn delete_permission_from_user(user_identifier: &str, permission_identifier: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::delete_permission_from_user_request(user_identifier, permission_identifier);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
     
     /// Composes a delete permission from user request.
@@ -318,14 +331,11 @@ impl UserManager {
     /// delete_permission_from_user_request("user_identifier", "permission_identifier");
     /// ```
     fn delete_permission_from_user_request(user_identifier: &str, permission_identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserDeletePermission {
-                target_user_id: String::from(user_identifier),
-                permission_identifier: String::from(permission_identifier),
-            },
-            TaskType::User,
-            "user_delete_permission",
-        )
+        UserDeletePermission {
+            target_user_id: String::from(user_identifier),
+            permission_identifier: String::from(permission_identifier),
+        }
+        .into_request()
     }
 
     /// Add role to user.
@@ -336,11 +346,11 @@ impl UserManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// add_role_to_user("user_uuid", "role_identiifer");
+    /// add_role_to_user("user_uuid", "role_identiifer").await;
     /// ```
-    pub fn add_role_to_user(user_identifier: &str, role_identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn add_role_to_user(user_identifier: &str, role_identifier: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::add_role_from_user_request(user_identifier, role_identifier);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
     
     /// Composes a add role from user request.
@@ -354,14 +364,11 @@ impl UserManager {
     /// add_role_from_user_request("user_identifier", "permission_identifier");
     /// ```
     fn add_role_from_user_request(user_identifier: &str, role_identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserAddRole {
-                target_user_id: String::from(user_identifier),
-                role_identifier: String::from(role_identifier),
-            },
-            TaskType::User,
-            "user_add_role",
-        )
+        UserAddRole {
+            target_user_id: String::from(user_identifier),
+            role_identifier: String::from(role_identifier),
+        }
+        .into_request()
     }
 
     /// Delete role from user.
@@ -372,11 +379,11 @@ impl UserManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// delete_role_from_user("user_uuid", "role_identiifer");
+    /// delete_role_from_user("user_uuid", "role_identiifer").await;
     /// ```
-    pub fn delete_role_from_user(user_identifier: &str, role_identifier: &str) -> TaskResult<TaskStatus> {
+    pub async fn delete_role_from_user(user_identifier: &str, role_identifier: &str) -> TaskResult<TaskStatus> {
         let task_request = Self::delete_role_from_user_request(user_identifier, role_identifier);
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
     
     /// Composes a delete role from user request.
@@ -390,14 +397,11 @@ impl UserManager {
     /// delete_permission_from_user_request("user_identifier", "permission_identifier");
     /// ```
     fn delete_role_from_user_request(user_identifier: &str, role_identifier: &str) -> TaskRequest {
-        TaskRequest::compose_request(
-            UserDeleteRole {
-                target_user_id: String::from(user_identifier),
-                role_identifier: String::from(role_identifier),
-            },
-            TaskType::User,
-            "user_delete_role",
-        )
+        UserDeleteRole {
+            target_user_id: String::from(user_identifier),
+            role_identifier: String::from(role_identifier),
+        }
+        .into_request()
     }
 
     /// Preload user cache.
@@ -407,9 +411,9 @@ impl UserManager {
     /// // Assuming `permission` is a reference to a valid Permission
     /// preload_user_cache_request();
     /// ```
-    pub fn preload_user_cache() -> TaskResult<TaskStatus> {
+    pub async fn preload_user_cache() -> TaskResult<TaskStatus> {
         let task_request = Self::preload_user_cache_request();
-        TaskManager::process_task(task_request)
+        TaskManager::process_task(task_request).await
     }
 
     /// Composes a user preload cache request.
@@ -419,11 +423,11 @@ impl UserManager {
     /// let task_response = preload_permission_request();
     /// ```
     fn preload_user_cache_request() -> TaskRequest {
-        TaskRequest::compose_request(UserPreloadCache {}, TaskType::User, "user_preload_cache")
+        UserPreloadCache {}.into_request()
     }
 }
 
-pub(super) struct UserCacheManager;
+pub(crate) struct UserCacheManager;
 
 impl UserCacheManager {
     /// Add user to cache.
@@ -433,11 +437,11 @@ impl UserCacheManager {
     ///
     /// # Examples
     /// ```
-    /// add_user_to_cache(user);
+    /// add_user_to_cache(user).await;
     /// ```
-    pub fn add_user_to_cache(user: User) -> CacheResult<CacheStatus> {
+    pub async fn add_user_to_cache(user: User) -> CacheResult<CacheStatus> {
         let cache_request = Self::create_user_cache_request(user);
-        CacheManager::process_cache(cache_request)
+        CacheManager::process_cache(cache_request).await
     }
 
     /// Add user to cache request.
@@ -464,11 +468,11 @@ impl UserCacheManager {
     ///
     /// # Examples
     /// ```
-    /// read_user_from_cache("2f4afce2-ec56-429a-96b1-480c0b20943a");
+    /// read_user_from_cache("2f4afce2-ec56-429a-96b1-480c0b20943a").await;
     /// ``
-    pub fn read_user_from_cache(identifier: &str) -> CacheResult<User> {
+    pub async fn read_user_from_cache(identifier: &str) -> CacheResult<User> {
         let cache_request = Self::read_user_cache_request(identifier);
-        CacheManager::process_cache_with_result::<User>(cache_request)
+        CacheManager::process_cache_with_result::<User>(cache_request).await
     }
 
     /// Read user from cache request.
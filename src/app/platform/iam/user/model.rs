@@ -1,9 +1,12 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use crate::app::{platform::iam::access::EffectiveAccess, service::task::error::TaskError};
+
 /// Represents a user's basic information.
 ///
 /// Includes identification, contact details, and account timestamps.
@@ -55,8 +58,7 @@ impl UserSecurity {
             .as_millis() as i64;
         hasher.update(time.to_string());
         let no_hex_stamp = hasher.finalize();
-        let hex_stamp = hex::encode(no_hex_stamp);
-        hex_stamp
+        hex::encode(no_hex_stamp)
     }
 }
 
@@ -91,10 +93,7 @@ impl SecurityToken {
 
     /// Deserialize a security token
     pub fn decode_then_deserialize(security_token: Option<String>) -> Option<SecurityToken> {
-        if security_token.is_none() {
-            return None;
-        }
-        let decode_data = hex::decode(security_token.unwrap());
+        let decode_data = hex::decode(security_token?);
         let decode_data_result = match decode_data {
             Ok(data) => data,
             Err(_) => return None,
@@ -104,10 +103,7 @@ impl SecurityToken {
             Ok(data) => data,
             Err(_) => return None,
         };
-        match serde_json::from_str::<SecurityToken>(&from_utf8_data_result) {
-            Ok(data) => Some(data),
-            Err(_) => return None,
-        }
+        serde_json::from_str::<SecurityToken>(&from_utf8_data_result).ok()
     }
 
     /// Serializes then encodes the serialization via hex.
@@ -128,8 +124,7 @@ impl SecurityToken {
         let key_f = format!("{}:{}", security_stamp, action);
         hasher.update(key_f);
         let no_hex_token = hasher.finalize();
-        let hex_token = hex::encode(no_hex_token);
-        hex_token
+        hex::encode(no_hex_token)
     }
 
 
@@ -219,6 +214,7 @@ pub struct User {
 }
 
 impl User {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_id: String,
         username: Option<String>,
@@ -338,8 +334,8 @@ impl UserBuilder {
         self
     }
 
-    /// Runs the builder result through the validator ensuring
-    /// it meets the criteria.
+    // Runs the builder result through the validator ensuring
+    // it meets the criteria.
     /*
     pub fn validate_and_build(self) -> ValidationResult<User> {
         UserValidator::validate(User {
@@ -359,3 +355,201 @@ impl UserBuilder {
         }
     }
 }
+
+/// What the admin routes answer about a user, everything but its security
+/// token and stamp.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserAccount {
+    pub info: UserInfo,
+    pub auth: UserAuthInfo,
+    pub access: UserAccessInfo,
+}
+
+impl From<User> for UserAccount {
+    fn from(user: User) -> Self {
+        Self {
+            info: user.info,
+            auth: user.auth,
+            access: user.access,
+        }
+    }
+}
+
+/// The fields of a user an update changes, the ones left out are kept.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+impl UserPatch {
+    /// Checks every field of an update before any is applied. `verified`
+    /// must be a boolean, the timestamps integers and the rest strings.
+    ///
+    /// # Arguments
+    /// - `fields`: the fields to update, named as the columns of `iam_users`.
+    ///
+    /// # Examples
+    /// ```
+    /// let patch = UserPatch::from_fields(&fields)?;
+    /// ```
+    pub fn from_fields(fields: &Map<String, Value>) -> Result<Self, TaskError> {
+        let mut patch = UserPatch::default();
+        for (field, value) in fields {
+            match (field.to_ascii_lowercase().as_str(), value) {
+                ("id", _) => return Err(TaskError::UserCannotUpdateId),
+                ("username", Value::String(v)) => patch.username = Some(v.clone()),
+                ("email", Value::String(v)) => patch.email = Some(v.clone()),
+                ("verified", Value::Bool(v)) => patch.verified = Some(*v),
+                ("created_at", Value::Number(v)) if v.is_i64() => patch.created_at = v.as_i64(),
+                ("updated_at", Value::Number(v)) if v.is_i64() => patch.updated_at = v.as_i64(),
+                ("username" | "email" | "verified" | "created_at" | "updated_at", _) => {
+                    return Err(TaskError::UserUpdateIncompatiableType)
+                }
+                _ => return Err(TaskError::UserFieldNotFound),
+            }
+        }
+        Ok(patch)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.eq(&UserPatch::default())
+    }
+
+    /// Sets the fields of the patch on a user.
+    pub fn apply(&self, user: &mut User) {
+        if let Some(username) = &self.username {
+            user.info.username = Some(username.clone());
+        }
+        if let Some(email) = &self.email {
+            user.info.email = Some(email.clone());
+        }
+        if let Some(verified) = self.verified {
+            user.info.verified = verified;
+        }
+        if let Some(created_at) = self.created_at {
+            user.info.created_at = created_at;
+        }
+        if let Some(updated_at) = self.updated_at {
+            user.info.updated_at = updated_at;
+        }
+    }
+}
+
+/// What a user sees about themselves.
+///
+/// Unlike `UserAccessInfo`, which holds ids, `roles` holds role names and
+/// `permissions` holds the keys of every effective permission, including
+/// the ones granted through roles.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub info: UserInfo,
+    pub auth: UserAuthInfo,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl UserProfile {
+    /// Resolves the user's access against the `RoleCache`/`PermissionCache`.
+    ///
+    /// # Arguments
+    /// - `user`: the user to build the profile for.
+    ///
+    /// # Examples
+    /// ```
+    /// let profile = UserProfile::new(&user);
+    /// ```
+    pub fn new(user: &User) -> Self {
+        let access = EffectiveAccess::resolve(&user.access);
+        Self {
+            info: user.info.clone(),
+            auth: user.auth.clone(),
+            roles: access.roles.into_iter().map(|role| role.role_name).collect(),
+            permissions: access
+                .permissions
+                .into_iter()
+                .map(|permission| permission.permission_key)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fields(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn reads_every_field_of_an_update() {
+        let patch = UserPatch::from_fields(&fields(json!({
+            "Username": "chomnr",
+            "email": "chomnr@gmail.com",
+            "verified": true,
+            "created_at": 1,
+            "updated_at": 2,
+        })))
+        .unwrap();
+
+        assert_eq!(
+            patch,
+            UserPatch {
+                username: Some(String::from("chomnr")),
+                email: Some(String::from("chomnr@gmail.com")),
+                verified: Some(true),
+                created_at: Some(1),
+                updated_at: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_the_whole_update_for_a_single_bad_field() {
+        let refused = |value: Value| UserPatch::from_fields(&fields(value)).unwrap_err();
+
+        assert!(matches!(
+            refused(json!({"username": "chomnr", "verified": "yes"})),
+            TaskError::UserUpdateIncompatiableType
+        ));
+        assert!(matches!(
+            refused(json!({"email": "chomnr@gmail.com", "created_at": 1.5})),
+            TaskError::UserUpdateIncompatiableType
+        ));
+        assert!(matches!(
+            refused(json!({"username": "chomnr", "password": "x"})),
+            TaskError::UserFieldNotFound
+        ));
+        assert!(matches!(
+            refused(json!({"id": "x"})),
+            TaskError::UserCannotUpdateId
+        ));
+    }
+
+    #[test]
+    fn applies_only_the_fields_it_holds() {
+        let mut user = User::builder().username("before").email("before@gmail.com").build();
+        let patch = UserPatch {
+            email: Some(String::from("after@gmail.com")),
+            ..UserPatch::default()
+        };
+
+        patch.apply(&mut user);
+
+        assert_eq!(user.info.username, Some(String::from("before")));
+        assert_eq!(user.info.email, Some(String::from("after@gmail.com")));
+        assert!(!patch.is_empty());
+        assert!(UserPatch::default().is_empty());
+    }
+}
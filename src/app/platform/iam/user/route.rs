@@ -0,0 +1,132 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::app::{
+    platform::{
+        iam::{
+            access::{layer::RequirePermission, ADMIN_PERMISSION},
+            session::extractor::CurrentUser,
+        },
+        response::{CustomJsonResponse, ErrorJsonResponse},
+    },
+};
+
+use super::{
+    manager::UserManager,
+    model::{UserAccount, UserPatch, UserProfile, UserSecurity},
+};
+
+#[derive(Deserialize)]
+pub struct UserRoleBody {
+    pub role: String,
+}
+
+#[derive(Deserialize)]
+pub struct UserPermissionBody {
+    pub permission: String,
+}
+
+#[derive(Deserialize)]
+pub struct UserSecurityTokenBody {
+    pub action: String,
+}
+
+/// Routes over `UserManager`, all but `/me` are guarded by `ADMIN_PERMISSION`.
+///
+/// # Routes
+///
+/// * `GET /me` - Read the signed in user with its role names and permission keys.
+/// * `GET /iam/users/:user_id` - Read a user, without its security token and stamp.
+/// * `PATCH /iam/users/:user_id` - Update the fields inside of the body, all or none ex: `{"email": "..."}`.
+/// * `POST /iam/users/:user_id/roles` - Add a role ex: `{"role": "Admin"}`.
+/// * `DELETE /iam/users/:user_id/roles/:role` - Remove a role.
+/// * `POST /iam/users/:user_id/permissions` - Add a permission ex: `{"permission": "ban.user"}`.
+/// * `DELETE /iam/users/:user_id/permissions/:permission` - Remove a permission.
+/// * `POST /iam/users/:user_id/security-token` - Create a security token ex: `{"action": "email_reset"}`.
+pub fn router() -> Router {
+    Router::new()
+        .route("/iam/users/:user_id", get(get_user).patch(update_user))
+        .route("/iam/users/:user_id/roles", post(add_role))
+        .route("/iam/users/:user_id/roles/:role", delete(delete_role))
+        .route("/iam/users/:user_id/permissions", post(add_permission))
+        .route(
+            "/iam/users/:user_id/permissions/:permission",
+            delete(delete_permission),
+        )
+        .route(
+            "/iam/users/:user_id/security-token",
+            post(create_security_token),
+        )
+        .route_layer(RequirePermission(ADMIN_PERMISSION))
+        // added after `route_layer` so only a session is required.
+        .route("/me", get(get_me))
+}
+
+async fn get_me(CurrentUser { user, .. }: CurrentUser) -> CustomJsonResponse<UserProfile> {
+    CustomJsonResponse::new(StatusCode::OK, UserProfile::new(&user))
+}
+
+async fn get_user(
+    Path(user_id): Path<String>,
+) -> Result<CustomJsonResponse<UserAccount>, ErrorJsonResponse> {
+    let user = UserManager::get_user(&user_id).await?;
+    Ok(CustomJsonResponse::new(StatusCode::OK, UserAccount::from(user)))
+}
+
+/// Applies every field of the body at once, `verified` must be a boolean and
+/// the timestamps must be integers; everything else is sent as a string. A
+/// body with a single bad field changes nothing.
+async fn update_user(
+    Path(user_id): Path<String>,
+    Json(fields): Json<Map<String, Value>>,
+) -> Result<CustomJsonResponse<UserAccount>, ErrorJsonResponse> {
+    let patch = UserPatch::from_fields(&fields)?;
+    if !patch.is_empty() {
+        UserManager::patch_user(&user_id, patch).await?;
+    }
+    get_user(Path(user_id)).await
+}
+
+async fn add_role(
+    Path(user_id): Path<String>,
+    Json(body): Json<UserRoleBody>,
+) -> Result<CustomJsonResponse<UserAccount>, ErrorJsonResponse> {
+    UserManager::add_role_to_user(&user_id, &body.role).await?;
+    get_user(Path(user_id)).await
+}
+
+async fn delete_role(
+    Path((user_id, role)): Path<(String, String)>,
+) -> Result<CustomJsonResponse<UserAccount>, ErrorJsonResponse> {
+    UserManager::delete_role_from_user(&user_id, &role).await?;
+    get_user(Path(user_id)).await
+}
+
+async fn add_permission(
+    Path(user_id): Path<String>,
+    Json(body): Json<UserPermissionBody>,
+) -> Result<CustomJsonResponse<UserAccount>, ErrorJsonResponse> {
+    UserManager::add_permission_to_user(&user_id, &body.permission).await?;
+    get_user(Path(user_id)).await
+}
+
+async fn delete_permission(
+    Path((user_id, permission)): Path<(String, String)>,
+) -> Result<CustomJsonResponse<UserAccount>, ErrorJsonResponse> {
+    UserManager::delete_permission_from_user(&user_id, &permission).await?;
+    get_user(Path(user_id)).await
+}
+
+async fn create_security_token(
+    Path(user_id): Path<String>,
+    Json(body): Json<UserSecurityTokenBody>,
+) -> Result<CustomJsonResponse<UserSecurity>, ErrorJsonResponse> {
+    let security = UserManager::create_security_token(&user_id, &body.action).await?;
+    Ok(CustomJsonResponse::new(StatusCode::CREATED, security))
+}
@@ -1,218 +1,83 @@
 // todo: make a user_update_cache so I don't have to retrieve all the results every time
 // will reduce lines of code by at least 200
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use ark_macros::Task;
 use axum::async_trait;
+use bb8_postgres::tokio_postgres::{error::SqlState, types::ToSql};
 use serde::{Deserialize, Serialize};
 
 use crate::app::{
     database::postgres::PostgresDatabase,
     platform::iam::{
-        permission::{cache::PermissionCache, model::Permission},
-        role::{cache::RoleCache, model::Role},
+        permission::cache::PermissionCache,
+        role::cache::RoleCache,
     },
     service::{
         cache::{error::CacheError, notify_cache_hit, notify_cache_miss, LocalizedCache},
         task::{
             error::TaskError,
             message::{TaskRequest, TaskResponse, TaskStatus},
-            Task, TaskHandler,
+            registry::TaskDefinition,
+            retry::Transient,
+            schedule::{Schedule, ScheduledJob, Scheduler},
+            Task,
         },
     },
 };
 
 use super::{
     manager::UserCacheManager,
-    model::{SecurityToken, User, UserSecurity},
+    model::{SecurityToken, User, UserPatch, UserSecurity},
 };
 
-pub struct UserTaskHandler;
-
-#[async_trait]
-impl TaskHandler<PostgresDatabase> for UserTaskHandler {
-    async fn handle(pg: &PostgresDatabase, task_request: TaskRequest) -> TaskResponse {
-        if task_request.task_action.eq("user_create") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserCreateTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserCreateTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_read") {
-            let payload = match TaskRequest::intepret_request_payload::<UserReadTask>(&task_request)
-            {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return UserReadTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_update") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserUpdateTask>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserUpdateTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_update_as_boolean") {
-            let payload = match TaskRequest::intepret_request_payload::<UserUpdateAsBooleanTask>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return UserUpdateAsBooleanTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_update_as_integer") {
-            let payload = match TaskRequest::intepret_request_payload::<UserUpdateAsIntegerTask>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return UserUpdateAsIntegerTask::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_create_security_token") {
-            let payload = match TaskRequest::intepret_request_payload::<UserCreateSecurityToken>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return UserCreateSecurityToken::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_exchange_oauthid_for_id") {
-            let payload = match TaskRequest::intepret_request_payload::<UserExchangeOAuthIdForId>(
-                &task_request,
-            ) {
-                Ok(p) => p,
-                Err(_) => {
-                    return TaskResponse::throw_failed_response(
-                        task_request,
-                        vec![TaskError::FailedToInterpretPayload.to_string()],
-                    )
-                }
-            };
-            return UserExchangeOAuthIdForId::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_add_role") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserAddRole>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserAddRole::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_delete_role") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserDeleteRole>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserDeleteRole::run(pg, task_request, payload).await;
-        }
-
-
-        if task_request.task_action.eq("user_add_permission") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserAddPermission>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserAddPermission::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_delete_permission") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserDeletePermission>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserDeletePermission::run(pg, task_request, payload).await;
-        }
-
-        if task_request.task_action.eq("user_preload_cache") {
-            let payload =
-                match TaskRequest::intepret_request_payload::<UserPreloadCache>(&task_request) {
-                    Ok(p) => p,
-                    Err(_) => {
-                        return TaskResponse::throw_failed_response(
-                            task_request,
-                            vec![TaskError::FailedToInterpretPayload.to_string()],
-                        )
-                    }
-                };
-            return UserPreloadCache::run(pg, task_request, payload).await;
-        }
+/// Registers every user task with the `TaskRegistry`.
+///
+/// # Examples
+/// ```
+/// register_tasks();
+/// ```
+pub fn register_tasks() {
+    UserCreateTask::register();
+    UserReadTask::register();
+    UserUpdateTask::register();
+    UserUpdateAsBooleanTask::register();
+    UserUpdateAsIntegerTask::register();
+    UserPatchTask::register();
+    UserCreateSecurityToken::register();
+    UserExchangeOAuthIdForId::register();
+    UserAddRole::register();
+    UserDeleteRole::register();
+    UserAddPermission::register();
+    UserDeletePermission::register();
+    UserPreloadCache::register();
+    UserClearExpiredSecurityTokens::register();
+}
 
-        return TaskResponse::throw_failed_response(
-            task_request,
-            vec![TaskError::FailedToFindAction.to_string()],
-        );
-    }
+/// Defines the scheduled user jobs.
+///
+/// # Examples
+/// ```
+/// register_jobs();
+/// ```
+pub fn register_jobs() {
+    Scheduler::register(ScheduledJob::new(
+        "user_clear_expired_security_tokens",
+        Schedule::cron("0 */15 * * * *"),
+        || UserClearExpiredSecurityTokens {}.into_request(),
+    ));
+    // cached users expire after 15 minutes, refreshing every 10 keeps the
+    // recently active ones warm.
+    Scheduler::register(ScheduledJob::new(
+        "user_refresh_cache",
+        Schedule::every(Duration::from_secs(600)),
+        || UserPreloadCache {}.into_request().with_timeout(Duration::from_secs(120)),
+    ));
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_create")]
 pub(super) struct UserCreateTask {
     pub user: User,
 }
@@ -226,20 +91,39 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateTask> for UserCreateTask {
     ) -> TaskResponse {
         // because of how the users create their account (through oauth)
         // this operation should never fail.
-        let mut pool = db.pool.get().await.unwrap();
+        let mut pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         // dont include this as part of the transaction because if it fails the transaction fails.
 
-        let transaction = pool.transaction().await.unwrap();
-        transaction.execute(
-            "INSERT INTO iam_users (id, verified, created_at, updated_at) VALUES ($1, $2, $3, $4)",
-            &[&param.user.info.user_id, &param.user.info.verified, &param.user.info.created_at, &param.user.info.updated_at]
-        ).await.unwrap();
+        let transaction = match pool.transaction().await {
+            Ok(transaction) => transaction,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        match transaction.execute(
+            "INSERT INTO iam_users (id, username, email, verified, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6)",
+            &[&param.user.info.user_id, &param.user.info.username, &param.user.info.email, &param.user.info.verified, &param.user.info.created_at, &param.user.info.updated_at]
+        ).await {
+            Ok(_) => {},
+            // the username or email is taken by another user.
+            Err(error) if error.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                return TaskResponse::throw_failed_response(
+                    request,
+                    vec![TaskError::UserUniqueConstraint.to_string()],
+                )
+            }
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        }
 
         match transaction.execute(
             "INSERT INTO iam_user_oauth (user_id, oauth_id, oauth_provider) VALUES ($1, $2, $3)",
             &[&param.user.info.user_id, &param.user.auth.oauth_id, &param.user.auth.oauth_provider]
         ).await {
             Ok(_) => {},
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => return TaskResponse::throw_failed_response(
                 request,
                 vec![TaskError::UserAlreadyExists.to_string()],
@@ -247,39 +131,39 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateTask> for UserCreateTask {
         }
         if !param.user.access.role.is_empty() {
             for role_identifier in &param.user.access.role {
-                let role: Option<Role> = match RoleCache::get(role_identifier) {
-                    Ok(v) => Some(v),
-                    Err(_) => None,
-                };
-                if role != None {
-                    transaction
+                if let Ok(role) = RoleCache::get(role_identifier) {
+                    if let Err(error) = transaction
                         .execute(
                             "INSERT INTO iam_user_role (user_id, role_id) VALUES ($1, $2)",
-                            &[&param.user.info.user_id, &role.unwrap().role_id],
+                            &[&param.user.info.user_id, &role.role_id],
                         )
                         .await
-                        .unwrap();
+                    {
+                        return TaskResponse::throw_database_failure(request, &error);
+                    }
                 }
             }
         }
         if !param.user.access.permission.is_empty() {
             for permission_identifier in &param.user.access.permission {
-                let permission: Option<Permission> =
-                    match PermissionCache::get(permission_identifier) {
-                        Ok(v) => Some(v),
-                        Err(_) => None,
-                    };
-                if permission != None {
-                    transaction.execute(
-                    "INSERT INTO iam_user_permission (user_id, permission_id) VALUES ($1, $2)",
-                    &[&param.user.info.user_id, &permission.unwrap().permission_id],
-                ).await.unwrap();
+                if let Ok(permission) = PermissionCache::get(permission_identifier) {
+                    if let Err(error) = transaction
+                        .execute(
+                            "INSERT INTO iam_user_permission (user_id, permission_id) VALUES ($1, $2)",
+                            &[&param.user.info.user_id, &permission.permission_id],
+                        )
+                        .await
+                    {
+                        return TaskResponse::throw_database_failure(request, &error);
+                    }
                 }
             }
         }
         match transaction.commit().await {
             Ok(_) => {
-                UserCacheManager::add_user_to_cache(param.user.clone()).unwrap();
+                if let Err(error) = UserCacheManager::add_user_to_cache(param.user.clone()).await {
+                    return TaskResponse::throw_cache_failure(request, &error);
+                }
                 return TaskResponse::compose_response(
                     request,
                     TaskStatus::Completed,
@@ -287,6 +171,9 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateTask> for UserCreateTask {
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -297,7 +184,8 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateTask> for UserCreateTask {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_read", idempotent, read_only)]
 pub(super) struct UserReadTask {
     pub identifier: String,
 }
@@ -307,9 +195,12 @@ impl Task<PostgresDatabase, TaskRequest, UserReadTask> for UserReadTask {
     async fn run(db: &PostgresDatabase, request: TaskRequest, param: UserReadTask) -> TaskResponse {
         // very messy what we should do propogate/push onto call back the error but the way i built the system... yeah that won't work.
 
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
-        match UserCacheManager::read_user_from_cache(&param.identifier) {
+        match UserCacheManager::read_user_from_cache(&param.identifier).await {
             Ok(user) => {
                 notify_cache_hit("UserRead", "UserCache", &request.task_id);
                 return TaskResponse::compose_response(
@@ -327,7 +218,7 @@ impl Task<PostgresDatabase, TaskRequest, UserReadTask> for UserReadTask {
                     );
                 }
 
-                let fallback_stmt = pool
+                let fallback_stmt = match pool
                     .prepare(
                         "SELECT 
                         u.id, 
@@ -350,7 +241,10 @@ impl Task<PostgresDatabase, TaskRequest, UserReadTask> for UserReadTask {
                     GROUP BY u.id, o.oauth_id, o.oauth_provider;",
                     )
                     .await
-                    .unwrap();
+                {
+                    Ok(fallback_stmt) => fallback_stmt,
+                    Err(error) => return TaskResponse::throw_database_failure(request, &error),
+                };
                 let fallback_query = pool.query_one(&fallback_stmt, &[&param.identifier]).await;
                 match fallback_query {
                     Ok(row) => {
@@ -372,7 +266,13 @@ impl Task<PostgresDatabase, TaskRequest, UserReadTask> for UserReadTask {
                                 row.get(11),
                             ),
                         );
-                        UserCacheManager::add_user_to_cache(user.clone()).unwrap();
+                        // the user is read all the same, the next read caches it again.
+                        if let Err(error) = UserCacheManager::add_user_to_cache(user.clone()).await {
+                            println!(
+                                "[TASK] Task: {} failed to cache user {}. Error: {}",
+                                request.task_id, user.info.user_id, error
+                            );
+                        }
                         notify_cache_miss("UserRead", "UserCache", &request.task_id);
                         return TaskResponse::compose_response(
                             request,
@@ -393,7 +293,8 @@ impl Task<PostgresDatabase, TaskRequest, UserReadTask> for UserReadTask {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_update")]
 pub(super) struct UserUpdateTask {
     pub search_by: String,
     pub update_for: String,
@@ -407,7 +308,10 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateTask> for UserUpdateTask {
         request: TaskRequest,
         param: UserUpdateTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         let stmt = match pool
             .prepare(
                 format!(
@@ -425,6 +329,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateTask> for UserUpdateTask {
             .await
         {
             Ok(v) => v,
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -447,9 +354,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateTask> for UserUpdateTask {
             .await
         {
             Ok(row) => {
-                if row.len() != 0 {
+                if !row.is_empty() {
                     // test
-                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)) {
+                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)).await {
                         Ok(mut user) => {
                             if param.update_for.eq_ignore_ascii_case("id") {
                                 return TaskResponse::throw_failed_response(
@@ -462,7 +369,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateTask> for UserUpdateTask {
                             } else if param.update_for.eq_ignore_ascii_case("email") {
                                 user.info.email = Some(param.clone().value);
                             }
-                            UserCacheManager::add_user_to_cache(user).unwrap();
+                            if let Err(error) = UserCacheManager::add_user_to_cache(user).await {
+                                return TaskResponse::throw_cache_failure(request, &error);
+                            }
                         }
                         Err(_) => {
                             /* if not found in cache then it will just update the database. */
@@ -480,6 +389,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateTask> for UserUpdateTask {
                     vec![TaskError::UserNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -490,7 +402,8 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateTask> for UserUpdateTask {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_update_as_boolean")]
 pub(super) struct UserUpdateAsBooleanTask {
     pub search_by: String,
     pub update_for: String,
@@ -504,7 +417,10 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsBooleanTask> for UserUpdate
         request: TaskRequest,
         param: UserUpdateAsBooleanTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         let stmt = match pool
             .prepare(
                 format!(
@@ -522,6 +438,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsBooleanTask> for UserUpdate
             .await
         {
             Ok(v) => v,
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -544,13 +463,15 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsBooleanTask> for UserUpdate
             .await
         {
             Ok(row) => {
-                if row.len() != 0 {
-                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)) {
+                if !row.is_empty() {
+                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)).await {
                         Ok(mut user) => {
                             if param.update_for.eq_ignore_ascii_case("verified") {
                                 user.info.verified = param.clone().value;
                             }
-                            UserCacheManager::add_user_to_cache(user).unwrap();
+                            if let Err(error) = UserCacheManager::add_user_to_cache(user).await {
+                                return TaskResponse::throw_cache_failure(request, &error);
+                            }
                         }
                         Err(_) => {
                             /* if not found in cache then it will just update the database. */
@@ -568,6 +489,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsBooleanTask> for UserUpdate
                     vec![TaskError::UserNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -578,7 +502,8 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsBooleanTask> for UserUpdate
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_update_as_integer")]
 pub(super) struct UserUpdateAsIntegerTask {
     pub search_by: String,
     pub update_for: String,
@@ -592,7 +517,10 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsIntegerTask> for UserUpdate
         request: TaskRequest,
         param: UserUpdateAsIntegerTask,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         let stmt = match pool
             .prepare(
                 format!(
@@ -610,6 +538,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsIntegerTask> for UserUpdate
             .await
         {
             Ok(v) => v,
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -632,8 +563,8 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsIntegerTask> for UserUpdate
             .await
         {
             Ok(row) => {
-                if row.len() != 0 {
-                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)) {
+                if !row.is_empty() {
+                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)).await {
                         Ok(mut user) => {
                             if param.update_for.eq_ignore_ascii_case("created_at") {
                                 user.info.created_at = param.clone().value;
@@ -641,7 +572,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsIntegerTask> for UserUpdate
                             if param.update_for.eq_ignore_ascii_case("updated_at") {
                                 user.info.updated_at = param.clone().value;
                             }
-                            UserCacheManager::add_user_to_cache(user).unwrap();
+                            if let Err(error) = UserCacheManager::add_user_to_cache(user).await {
+                                return TaskResponse::throw_cache_failure(request, &error);
+                            }
                         }
                         Err(_) => {
                             /* if not found in cache then it will just update the database. */
@@ -659,6 +592,9 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsIntegerTask> for UserUpdate
                     vec![TaskError::UserNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -669,7 +605,92 @@ impl Task<PostgresDatabase, TaskRequest, UserUpdateAsIntegerTask> for UserUpdate
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Applies every field of a `UserPatch` in a single statement, so a patch
+/// that fails leaves the user as it was.
+#[derive(Clone, Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_patch")]
+pub(super) struct UserPatchTask {
+    pub search_by: String,
+    pub patch: UserPatch,
+}
+
+#[async_trait]
+impl Task<PostgresDatabase, TaskRequest, UserPatchTask> for UserPatchTask {
+    async fn run(db: &PostgresDatabase, request: TaskRequest, param: UserPatchTask) -> TaskResponse {
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        let patch = &param.patch;
+        // the columns come from the patch itself, never from the caller.
+        let mut columns: Vec<&str> = Vec::new();
+        let mut values: Vec<&(dyn ToSql + Sync)> = Vec::new();
+        if let Some(username) = &patch.username {
+            columns.push("username");
+            values.push(username);
+        }
+        if let Some(email) = &patch.email {
+            columns.push("email");
+            values.push(email);
+        }
+        if let Some(verified) = &patch.verified {
+            columns.push("verified");
+            values.push(verified);
+        }
+        if let Some(created_at) = &patch.created_at {
+            columns.push("created_at");
+            values.push(created_at);
+        }
+        columns.push("updated_at");
+        values.push(patch.updated_at.as_ref().unwrap_or(&now));
+        values.push(&param.search_by);
+        let assignments = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| format!("{} = ${}", column, index + 1))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let statement = format!(
+            "UPDATE iam_users SET {} WHERE id = ${1} OR username = ${1} OR email = ${1} RETURNING id;",
+            assignments,
+            values.len()
+        );
+        let user_id = match pool.query_opt(&statement, &values).await {
+            Ok(Some(row)) => row.get::<_, String>(0),
+            Ok(None) => {
+                return TaskResponse::throw_failed_response(
+                    request,
+                    vec![TaskError::UserNotFound.to_string()],
+                )
+            }
+            // the username or email is taken by another user.
+            Err(error) if error.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                return TaskResponse::throw_failed_response(
+                    request,
+                    vec![TaskError::UserUniqueConstraint.to_string()],
+                )
+            }
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        if let Ok(mut user) = UserCacheManager::read_user_from_cache(&user_id).await {
+            patch.apply(&mut user);
+            if patch.updated_at.is_none() {
+                user.info.updated_at = now;
+            }
+            if let Err(error) = UserCacheManager::add_user_to_cache(user).await {
+                return TaskResponse::throw_cache_failure(request, &error);
+            }
+        }
+        TaskResponse::compose_response(request, TaskStatus::Completed, param, Vec::default())
+    }
+}
+
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_create_security_token")]
 pub(super) struct UserCreateSecurityToken {
     pub search_by: String,
     pub action: String,
@@ -682,44 +703,50 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateSecurityToken> for UserCreate
         request: TaskRequest,
         param: UserCreateSecurityToken,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         let user_security = UserSecurity::create(&param.action);
         // updating security_stam
-        let stmt_1 = pool
+        let stmt_1 = match pool
             .prepare(
-                format!(
-                    "UPDATE iam_users
+                "UPDATE iam_users
                 SET security_stamp = $1
                 WHERE id = $2
                    OR username = $2
                    OR email = $2
-                   RETURNING *;"
-                )
-                .as_str(),
+                   RETURNING *;",
             )
             .await
-            .unwrap();
-        pool.execute(
-            &stmt_1,
-            &[&user_security.clone().stamp.unwrap(), &param.search_by],
-        )
-        .await
-        .unwrap();
+        {
+            Ok(stmt_1) => stmt_1,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        if let Err(error) = pool
+            .execute(
+                &stmt_1,
+                &[&user_security.clone().stamp.unwrap(), &param.search_by],
+            )
+            .await
+        {
+            return TaskResponse::throw_database_failure(request, &error);
+        }
         // updating security_token
-        let stmt_2 = pool
+        let stmt_2 = match pool
             .prepare(
-                format!(
-                    "UPDATE iam_users
+                "UPDATE iam_users
                 SET security_token = $1
                 WHERE id = $2
                    OR username = $2
                    OR email = $2
-                   RETURNING *;"
-                )
-                .as_str(),
+                   RETURNING *;",
             )
             .await
-            .unwrap();
+        {
+            Ok(stmt_2) => stmt_2,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool
             .query_one(
                 &stmt_2,
@@ -731,11 +758,13 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateSecurityToken> for UserCreate
             .await
         {
             Ok(row) => {
-                if row.len() != 0 {
-                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)) {
+                if !row.is_empty() {
+                    match UserCacheManager::read_user_from_cache(&row.get::<_, String>(0)).await {
                         Ok(mut user) => {
                             user.security = user_security.clone();
-                            UserCacheManager::add_user_to_cache(user).unwrap();
+                            if let Err(error) = UserCacheManager::add_user_to_cache(user).await {
+                                return TaskResponse::throw_cache_failure(request, &error);
+                            }
                         }
                         Err(_) => {
                             /* if not found in cache then it will just update the database. */
@@ -753,6 +782,9 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateSecurityToken> for UserCreate
                     vec![TaskError::UserNotFound.to_string()],
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -763,7 +795,8 @@ impl Task<PostgresDatabase, TaskRequest, UserCreateSecurityToken> for UserCreate
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_exchange_oauthid_for_id", idempotent, read_only)]
 pub(super) struct UserExchangeOAuthIdForId {
     pub oauth_id: String,
     pub provider: String,
@@ -777,13 +810,19 @@ impl Task<PostgresDatabase, TaskRequest, UserExchangeOAuthIdForId> for UserExcha
         param: UserExchangeOAuthIdForId,
     ) -> TaskResponse {
         // retrieves directly from database.
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare(
                 "SELECT user_id FROM iam_user_oauth WHERE oauth_id = $1 AND oauth_provider = $2",
             )
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match pool
             .query_one(&stmt, &[&param.oauth_id, &param.provider])
             .await
@@ -796,6 +835,9 @@ impl Task<PostgresDatabase, TaskRequest, UserExchangeOAuthIdForId> for UserExcha
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -806,7 +848,8 @@ impl Task<PostgresDatabase, TaskRequest, UserExchangeOAuthIdForId> for UserExcha
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_add_permission")]
 pub(super) struct UserAddPermission {
     pub target_user_id: String,
     pub permission_identifier: String,
@@ -819,14 +862,20 @@ impl Task<PostgresDatabase, TaskRequest, UserAddPermission> for UserAddPermissio
         request: TaskRequest,
         param: UserAddPermission,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare("INSERT INTO iam_user_permission (user_id, permission_id) VALUES ($1, $2)")
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         match PermissionCache::get(&param.permission_identifier) {
-            Ok(permission) => match UserCacheManager::read_user_from_cache(&param.target_user_id) {
+            Ok(permission) => match UserCacheManager::read_user_from_cache(&param.target_user_id).await {
                 Ok(mut cached_user) => match pool
                     .execute(
                         &stmt,
@@ -836,7 +885,9 @@ impl Task<PostgresDatabase, TaskRequest, UserAddPermission> for UserAddPermissio
                 {
                     Ok(_) => {
                         cached_user.access.permission.push(permission.permission_id);
-                        UserCacheManager::add_user_to_cache(cached_user).unwrap();
+                        if let Err(error) = UserCacheManager::add_user_to_cache(cached_user).await {
+                            return TaskResponse::throw_cache_failure(request, &error);
+                        }
                         // user exists in the cache so we need to update
                         return TaskResponse::compose_response(
                             request,
@@ -845,6 +896,9 @@ impl Task<PostgresDatabase, TaskRequest, UserAddPermission> for UserAddPermissio
                             Vec::default(),
                         );
                     }
+                    Err(error) if error.is_transient() => {
+                        return TaskResponse::throw_database_failure(request, &error)
+                    }
                     Err(_) => {
                         return TaskResponse::throw_failed_response(
                             request,
@@ -872,6 +926,9 @@ impl Task<PostgresDatabase, TaskRequest, UserAddPermission> for UserAddPermissio
                                 Vec::default(),
                             );
                         }
+                        Err(error) if error.is_transient() => {
+                            return TaskResponse::throw_database_failure(request, &error)
+                        }
                         Err(_) => {
                             return TaskResponse::throw_failed_response(
                                 request,
@@ -892,7 +949,8 @@ impl Task<PostgresDatabase, TaskRequest, UserAddPermission> for UserAddPermissio
 }
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_delete_permission")]
 pub(super) struct UserDeletePermission {
     pub target_user_id: String,
     pub permission_identifier: String,
@@ -905,13 +963,19 @@ impl Task<PostgresDatabase, TaskRequest, UserDeletePermission> for UserDeletePer
         request: TaskRequest,
         param: UserDeletePermission,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare("DELETE FROM iam_user_permission WHERE user_id = $1 and permission_id = $2")
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
         match PermissionCache::get(&param.permission_identifier) {
-            Ok(permission) => match UserCacheManager::read_user_from_cache(&param.target_user_id) {
+            Ok(permission) => match UserCacheManager::read_user_from_cache(&param.target_user_id).await {
                 Ok(mut cached_user) => match pool
                     .execute(
                         &stmt,
@@ -921,7 +985,9 @@ impl Task<PostgresDatabase, TaskRequest, UserDeletePermission> for UserDeletePer
                 {
                     Ok(_) => {
                         cached_user.access.permission.retain(|perm| !perm.eq(&permission.permission_id) );
-                        UserCacheManager::add_user_to_cache(cached_user).unwrap();
+                        if let Err(error) = UserCacheManager::add_user_to_cache(cached_user).await {
+                            return TaskResponse::throw_cache_failure(request, &error);
+                        }
                         // user exists in the cache so we need to update
                         return TaskResponse::compose_response(
                             request,
@@ -930,6 +996,9 @@ impl Task<PostgresDatabase, TaskRequest, UserDeletePermission> for UserDeletePer
                             Vec::default(),
                         );
                     }
+                    Err(error) if error.is_transient() => {
+                        return TaskResponse::throw_database_failure(request, &error)
+                    }
                     Err(_) => {
                         return TaskResponse::throw_failed_response(
                             request,
@@ -957,6 +1026,9 @@ impl Task<PostgresDatabase, TaskRequest, UserDeletePermission> for UserDeletePer
                                 Vec::default(),
                             );
                         }
+                        Err(error) if error.is_transient() => {
+                            return TaskResponse::throw_database_failure(request, &error)
+                        }
                         Err(_) => {
                             return TaskResponse::throw_failed_response(
                                 request,
@@ -976,7 +1048,8 @@ impl Task<PostgresDatabase, TaskRequest, UserDeletePermission> for UserDeletePer
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_add_role")]
 pub(super) struct UserAddRole {
     pub target_user_id: String,
     pub role_identifier: String,
@@ -989,14 +1062,20 @@ impl Task<PostgresDatabase, TaskRequest, UserAddRole> for UserAddRole {
         request: TaskRequest,
         param: UserAddRole,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare("INSERT INTO iam_user_role (user_id, role_id) VALUES ($1, $2)")
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         match RoleCache::get(&param.role_identifier) {
-            Ok(role) => match UserCacheManager::read_user_from_cache(&param.target_user_id) {
+            Ok(role) => match UserCacheManager::read_user_from_cache(&param.target_user_id).await {
                 Ok(mut cached_user) => match pool
                     .execute(
                         &stmt,
@@ -1006,7 +1085,9 @@ impl Task<PostgresDatabase, TaskRequest, UserAddRole> for UserAddRole {
                 {
                     Ok(_) => {
                         cached_user.access.role.push(role.role_id);
-                        UserCacheManager::add_user_to_cache(cached_user).unwrap();
+                        if let Err(error) = UserCacheManager::add_user_to_cache(cached_user).await {
+                            return TaskResponse::throw_cache_failure(request, &error);
+                        }
                         // user exists in the cache so we need to update
                         return TaskResponse::compose_response(
                             request,
@@ -1015,6 +1096,9 @@ impl Task<PostgresDatabase, TaskRequest, UserAddRole> for UserAddRole {
                             Vec::default(),
                         );
                     }
+                    Err(error) if error.is_transient() => {
+                        return TaskResponse::throw_database_failure(request, &error)
+                    }
                     Err(_) => {
                         return TaskResponse::throw_failed_response(
                             request,
@@ -1038,6 +1122,9 @@ impl Task<PostgresDatabase, TaskRequest, UserAddRole> for UserAddRole {
                                 Vec::default(),
                             );
                         }
+                        Err(error) if error.is_transient() => {
+                            return TaskResponse::throw_database_failure(request, &error)
+                        }
                         Err(_) => {
                             return TaskResponse::throw_failed_response(
                                 request,
@@ -1057,7 +1144,8 @@ impl Task<PostgresDatabase, TaskRequest, UserAddRole> for UserAddRole {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_delete_role")]
 pub(super) struct UserDeleteRole {
     pub target_user_id: String,
     pub role_identifier: String,
@@ -1070,14 +1158,20 @@ impl Task<PostgresDatabase, TaskRequest, UserDeleteRole> for UserDeleteRole {
         request: TaskRequest,
         param: UserDeleteRole,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool
             .prepare("DELETE FROM iam_user_role WHERE user_id = $1 and role_id = $2")
             .await
-            .unwrap();
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         match RoleCache::get(&param.role_identifier) {
-            Ok(role) => match UserCacheManager::read_user_from_cache(&param.target_user_id) {
+            Ok(role) => match UserCacheManager::read_user_from_cache(&param.target_user_id).await {
                 Ok(mut cached_user) => match pool
                     .execute(
                         &stmt,
@@ -1087,7 +1181,9 @@ impl Task<PostgresDatabase, TaskRequest, UserDeleteRole> for UserDeleteRole {
                 {
                     Ok(_) => {
                         cached_user.access.role.retain(|perm| !perm.eq(&role.role_id) );
-                        UserCacheManager::add_user_to_cache(cached_user).unwrap();
+                        if let Err(error) = UserCacheManager::add_user_to_cache(cached_user).await {
+                            return TaskResponse::throw_cache_failure(request, &error);
+                        }
                         // user exists in the cache so we need to update
                         return TaskResponse::compose_response(
                             request,
@@ -1096,6 +1192,9 @@ impl Task<PostgresDatabase, TaskRequest, UserDeleteRole> for UserDeleteRole {
                             Vec::default(),
                         );
                     }
+                    Err(error) if error.is_transient() => {
+                        return TaskResponse::throw_database_failure(request, &error)
+                    }
                     Err(_) => {
                         return TaskResponse::throw_failed_response(
                             request,
@@ -1119,6 +1218,9 @@ impl Task<PostgresDatabase, TaskRequest, UserDeleteRole> for UserDeleteRole {
                                 Vec::default(),
                             );
                         }
+                        Err(error) if error.is_transient() => {
+                            return TaskResponse::throw_database_failure(request, &error)
+                        }
                         Err(_) => {
                             return TaskResponse::throw_failed_response(
                                 request,
@@ -1138,7 +1240,8 @@ impl Task<PostgresDatabase, TaskRequest, UserDeleteRole> for UserDeleteRole {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_preload_cache", idempotent, read_only)]
 pub(super) struct UserPreloadCache;
 
 #[async_trait]
@@ -1148,8 +1251,11 @@ impl Task<PostgresDatabase, TaskRequest, UserPreloadCache> for UserPreloadCache
         request: TaskRequest,
         _param: UserPreloadCache,
     ) -> TaskResponse {
-        let pool = db.pool.get().await.unwrap();
-        let stmt = pool.prepare(
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let stmt = match pool.prepare(
             "SELECT 
             u.id, 
             u.username, 
@@ -1169,7 +1275,10 @@ impl Task<PostgresDatabase, TaskRequest, UserPreloadCache> for UserPreloadCache
         LEFT JOIN iam_user_oauth o ON u.id = o.user_id
         WHERE updated_at >= EXTRACT(EPOCH FROM NOW()) - 604800
         GROUP BY u.id, o.oauth_id, o.oauth_provider;",
-        ).await.unwrap();
+        ).await {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
 
         match pool.query(&stmt, &[]).await {
             Ok(rows) => {
@@ -1194,7 +1303,9 @@ impl Task<PostgresDatabase, TaskRequest, UserPreloadCache> for UserPreloadCache
                             row.get(11),
                         ),
                     );
-                    UserCacheManager::add_user_to_cache(user.clone()).unwrap();
+                    if let Err(error) = UserCacheManager::add_user_to_cache(user.clone()).await {
+                        return TaskResponse::throw_cache_failure(request, &error);
+                    }
                 }
                 println!("[ARK] cached {} user(s) cache.", amt_items);
                 return TaskResponse::compose_response(
@@ -1204,6 +1315,9 @@ impl Task<PostgresDatabase, TaskRequest, UserPreloadCache> for UserPreloadCache
                     Vec::default(),
                 );
             }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
             Err(_) => {
                 return TaskResponse::throw_failed_response(
                     request,
@@ -1213,3 +1327,61 @@ impl Task<PostgresDatabase, TaskRequest, UserPreloadCache> for UserPreloadCache
         }
     }
 }
+
+/// Clears the `security_token` of every user whose token has expired.
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "user_clear_expired_security_tokens", idempotent)]
+pub(super) struct UserClearExpiredSecurityTokens;
+
+#[async_trait]
+impl Task<PostgresDatabase, TaskRequest, UserClearExpiredSecurityTokens>
+    for UserClearExpiredSecurityTokens
+{
+    async fn run(
+        db: &PostgresDatabase,
+        request: TaskRequest,
+        _: UserClearExpiredSecurityTokens,
+    ) -> TaskResponse {
+        let pool = match db.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        // the token is stored as hex encoded json, see `SecurityToken::serialize_then_hex`.
+        let stmt = match pool
+            .prepare(
+                "UPDATE iam_users
+                SET security_token = NULL
+                WHERE security_token IS NOT NULL
+                  AND (convert_from(decode(security_token, 'hex'), 'UTF8')::json->>'expiry')::numeric < $1::BIGINT;",
+            )
+            .await
+        {
+            Ok(stmt) => stmt,
+            Err(error) => return TaskResponse::throw_database_failure(request, &error),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        match pool.execute(&stmt, &[&now]).await {
+            Ok(amt_items) => {
+                println!("[TASK] cleared {} expired security token(s).", amt_items);
+                return TaskResponse::compose_response(
+                    request,
+                    TaskStatus::Completed,
+                    String::default(),
+                    Vec::default(),
+                );
+            }
+            Err(error) if error.is_transient() => {
+                return TaskResponse::throw_database_failure(request, &error)
+            }
+            Err(_) => {
+                return TaskResponse::throw_failed_response(
+                    request,
+                    vec![TaskError::UserFailedToClearSecurityTokens.to_string()],
+                )
+            }
+        }
+    }
+}
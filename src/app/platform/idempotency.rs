@@ -0,0 +1,143 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{FromRequestParts, Request},
+    http::{header::CONTENT_TYPE, response::Parts, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    platform::{iam::session::extractor::CurrentUser, response::ErrorJsonResponse},
+    service::task::{
+        error::TaskError,
+        idempotency::{Idempotency, IdempotencyOutcome},
+    },
+};
+
+/// The header a client sends its idempotency key in.
+pub static IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// The header set on a response answered with the first result of its key.
+pub static IDEMPOTENT_REPLAYED_HEADER: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// The largest body of a keyed request, the same as the default body limit of axum.
+static MAX_BODY_LEN: usize = 2 * 1024 * 1024;
+
+/// A response kept under an idempotency key.
+#[derive(Serialize, Deserialize)]
+struct HttpRecord {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    /// The body, base64 encoded.
+    body: String,
+}
+
+impl HttpRecord {
+    fn new(parts: &Parts, body: &[u8]) -> Self {
+        Self {
+            status: parts.status.as_u16(),
+            content_type: parts
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .map(String::from),
+            body: STANDARD.encode(body),
+        }
+    }
+}
+
+impl IntoResponse for HttpRecord {
+    fn into_response(self) -> Response {
+        let mut response =
+            Response::new(Body::from(STANDARD.decode(self.body).unwrap_or_default()));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let headers = response.headers_mut();
+        if let Some(content_type) = self
+            .content_type
+            .and_then(|value| HeaderValue::from_str(&value).ok())
+        {
+            headers.insert(CONTENT_TYPE, content_type);
+        }
+        headers.insert(
+            IDEMPOTENT_REPLAYED_HEADER.clone(),
+            HeaderValue::from_static("true"),
+        );
+        response
+    }
+}
+
+/// Answers a signed-in client retrying a `POST`, `PUT`, `PATCH` or `DELETE`
+/// under the same `Idempotency-Key` header with the response of its first
+/// request, without running it again.
+///
+/// Keys are scoped to the user and compared on the method, path and body,
+/// so a retry must send the same request. Responses other than server side
+/// failures are kept, those are given back so the request can be retried
+/// under the same key. Requests without a session run as they are.
+///
+/// # Examples
+/// ```
+/// Router::new().layer(axum::middleware::from_fn(idempotency::guard));
+/// ```
+pub async fn guard(request: Request, next: Next) -> Response {
+    if !matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    ) {
+        return next.run(request).await;
+    }
+    let key = match request.headers().get(&IDEMPOTENCY_KEY_HEADER) {
+        Some(key) => key
+            .to_str()
+            .ok()
+            .filter(|key| Idempotency::is_valid_key(key))
+            .map(String::from),
+        None => return next.run(request).await,
+    };
+    let Some(key) = key else {
+        return TaskError::IdempotencyKeyInvalid.into_response();
+    };
+    let (mut parts, body) = request.into_parts();
+    let Ok(current_user) = CurrentUser::from_request_parts(&mut parts, &()).await else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+    let Ok(body) = to_bytes(body, MAX_BODY_LEN).await else {
+        return ErrorJsonResponse::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "The request body is too large.",
+        )
+        .into_response();
+    };
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or(parts.uri.path(), |path| path.as_str());
+    let fingerprint =
+        Idempotency::fingerprint(&[parts.method.as_str().as_bytes(), path.as_bytes(), &body]);
+    let scope = format!("http:{}", current_user.user.info.user_id);
+    let claim = match Idempotency::claim::<HttpRecord>(&scope, &key, fingerprint, None).await {
+        None => return next.run(Request::from_parts(parts, Body::from(body))).await,
+        Some(IdempotencyOutcome::Run(claim)) => claim,
+        Some(IdempotencyOutcome::Replay(record)) => return record.into_response(),
+        Some(IdempotencyOutcome::Reject(error)) => return error.into_response(),
+    };
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if response.status().is_server_error() {
+        Idempotency::release(claim).await;
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    match to_bytes(body, usize::MAX).await {
+        Ok(body) => {
+            Idempotency::keep(claim, &HttpRecord::new(&parts, &body)).await;
+            Response::from_parts(parts, Body::from(body))
+        }
+        Err(_) => {
+            Idempotency::release(claim).await;
+            TaskError::TaskInternalError.into_response()
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    env,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{header::RETRY_AFTER, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use bb8_redis::redis::{AsyncCommands, RedisResult};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    database::redis::RedisDatabase,
+    platform::{
+        iam::{
+            access::{layer::RequirePermission, EffectiveAccess, ADMIN_PERMISSION},
+            session::extractor::CurrentUser,
+        },
+        response::{CustomJsonResponse, ErrorJsonResponse},
+    },
+    service::task::stream::from_run_error,
+};
+
+/// Whether the server is currently in maintenance.
+static MAINTENANCE: AtomicBool = AtomicBool::new(false);
+
+/// Where maintenance is shared with the other processes, set by `Maintenance::share`.
+static SHARED: OnceCell<RedisDatabase> = OnceCell::new();
+
+/// The key that exists while maintenance is on.
+static MAINTENANCE_KEY: &str = "ark:maintenance";
+
+/// How often a process reads the shared maintenance state.
+static SYNC_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long clients are told to wait before retrying, in seconds.
+pub static RETRY_AFTER_SECONDS: u64 = 300;
+
+/// The permission that lets its holders through while in maintenance,
+/// read from `MAINTENANCE_BYPASS_PERMISSION`.
+pub static MAINTENANCE_BYPASS_PERMISSION: Lazy<String> = Lazy::new(|| {
+    env::var("MAINTENANCE_BYPASS_PERMISSION").unwrap_or(String::from("iam.maintenance.bypass"))
+});
+
+/// Routes that keep answering while in maintenance, so probes keep working
+/// and admins can still sign in and turn maintenance off. Each one covers
+/// the routes nested under it, ex: `/auth` covers `/auth/callback`.
+static EXEMPT_PATHS: [&str; 5] = ["/healthz", "/readyz", "/metrics", "/auth", "/maintenance"];
+
+#[derive(Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+}
+
+pub struct Maintenance;
+
+impl Maintenance {
+    /// Whether the server is currently in maintenance.
+    pub fn is_enabled() -> bool {
+        MAINTENANCE.load(Ordering::SeqCst)
+    }
+
+    /// Shares maintenance with every process using the same Redis, API nodes
+    /// and `ark-worker`s alike: reads the shared state then keeps this process
+    /// in step with it. Only the first call is used.
+    ///
+    /// # Arguments
+    /// - `redis`: the `RedisDatabase` the state is kept in.
+    ///
+    /// # Examples
+    /// ```
+    /// Maintenance::share(redis).await;
+    /// ```
+    pub async fn share(redis: RedisDatabase) {
+        if SHARED.set(redis).is_err() {
+            return;
+        }
+        Self::sync().await;
+        tokio::spawn(async {
+            let start = tokio::time::Instant::now() + SYNC_INTERVAL;
+            let mut interval = tokio::time::interval_at(start, SYNC_INTERVAL);
+            loop {
+                interval.tick().await;
+                Self::sync().await;
+            }
+        });
+    }
+
+    /// Puts the server in or out of maintenance, every process sharing it
+    /// follows within `SYNC_INTERVAL`.
+    ///
+    /// # Arguments
+    /// - `enabled`: whether maintenance should be on.
+    ///
+    /// # Examples
+    /// ```
+    /// Maintenance::set_enabled(true).await?;
+    /// ```
+    pub async fn set_enabled(enabled: bool) -> RedisResult<()> {
+        Self::store(enabled);
+        let Some(redis) = SHARED.get() else {
+            return Ok(());
+        };
+        let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+        match enabled {
+            true => connection.set(MAINTENANCE_KEY, 1).await,
+            false => connection.del(MAINTENANCE_KEY).await,
+        }
+    }
+
+    async fn sync() {
+        match Self::read_shared().await {
+            Ok(enabled) if enabled != Self::is_enabled() => Self::store(enabled),
+            Ok(_) => {}
+            Err(error) => println!(
+                "[ARK] Failed to read the shared maintenance state. Error: {}",
+                error
+            ),
+        }
+    }
+
+    async fn read_shared() -> RedisResult<bool> {
+        let Some(redis) = SHARED.get() else {
+            return Ok(Self::is_enabled());
+        };
+        let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+        connection.exists(MAINTENANCE_KEY).await
+    }
+
+    fn store(enabled: bool) {
+        MAINTENANCE.store(enabled, Ordering::SeqCst);
+        println!(
+            "[ARK] maintenance {}.",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+}
+
+/// Whether a path is one of `EXEMPT_PATHS` or nested under one, matched
+/// segment by segment so `/metricsfoo` or `/authorize` are not exempt.
+fn is_exempt(path: &str) -> bool {
+    EXEMPT_PATHS.iter().any(|exempt| {
+        path.strip_prefix(exempt)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Answers every request with a 503 and a `Retry-After` header while in
+/// maintenance, unless the caller holds `MAINTENANCE_BYPASS_PERMISSION`.
+///
+/// # Examples
+/// ```
+/// Router::new().layer(axum::middleware::from_fn(maintenance::guard));
+/// ```
+pub async fn guard(request: Request, next: Next) -> Response {
+    if !Maintenance::is_enabled() || is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let (mut parts, body) = request.into_parts();
+    if let Ok(current_user) = CurrentUser::from_request_parts(&mut parts, &()).await {
+        if EffectiveAccess::resolve(&current_user.user.access)
+            .has_permission(&MAINTENANCE_BYPASS_PERMISSION)
+        {
+            return next.run(Request::from_parts(parts, body)).await;
+        }
+    }
+    (
+        [(RETRY_AFTER, RETRY_AFTER_SECONDS.to_string())],
+        ErrorJsonResponse::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The server is under maintenance, please try again later.",
+        ),
+    )
+        .into_response()
+}
+
+/// Admin routes used to toggle maintenance at runtime, guarded by `ADMIN_PERMISSION`.
+///
+/// # Routes
+///
+/// * `GET /maintenance` - Read whether maintenance is on.
+/// * `PUT /maintenance` - Turn maintenance on or off ex: `{"enabled": true}`.
+pub fn router() -> Router {
+    Router::new()
+        .route("/maintenance", get(get_status).put(set_status))
+        .route_layer(RequirePermission(ADMIN_PERMISSION))
+}
+
+async fn get_status() -> CustomJsonResponse<MaintenanceStatus> {
+    CustomJsonResponse::new(
+        StatusCode::OK,
+        MaintenanceStatus {
+            enabled: Maintenance::is_enabled(),
+        },
+    )
+}
+
+/// Answers 503 when the other processes could not be told, this one
+/// still follows the request.
+async fn set_status(
+    Json(body): Json<MaintenanceStatus>,
+) -> Result<CustomJsonResponse<MaintenanceStatus>, ErrorJsonResponse> {
+    if let Err(error) = Maintenance::set_enabled(body.enabled).await {
+        println!(
+            "[ARK] Failed to share the maintenance state. Error: {}",
+            error
+        );
+        return Err(ErrorJsonResponse::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The maintenance state could not be shared, please try again later.",
+        ));
+    }
+    Ok(get_status().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exempts_the_listed_paths_and_the_ones_nested_under_them() {
+        assert!(is_exempt("/healthz"));
+        assert!(is_exempt("/maintenance"));
+        assert!(is_exempt("/auth/callback"));
+        assert!(is_exempt("/auth/login/github"));
+    }
+
+    #[test]
+    fn does_not_exempt_paths_sharing_a_prefix() {
+        assert!(!is_exempt("/metricsfoo"));
+        assert!(!is_exempt("/authorize"));
+        assert!(!is_exempt("/maintenance-window"));
+        assert!(!is_exempt("/iam/roles"));
+        assert!(!is_exempt("/"));
+    }
+}
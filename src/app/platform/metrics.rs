@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{http::header::CONTENT_TYPE, response::IntoResponse, routing::get, Extension, Router};
+
+use crate::app::{ark::ArkState, service::metrics::Metrics};
+
+/// The content type of the Prometheus text format.
+static CONTENT_TYPE_PROMETHEUS: &str = "text/plain; version=0.0.4";
+
+/// Routes scraped by Prometheus, it does not require a session.
+///
+/// # Routes
+///
+/// * `GET /metrics` - Task and cache counters and latencies, queue depths and pool stats.
+pub fn router() -> Router {
+    Router::new().route("/metrics", get(metrics))
+}
+
+async fn metrics(Extension(state): Extension<Arc<ArkState>>) -> impl IntoResponse {
+    let pools = [
+        ("postgres", state.postgres.pool.state()),
+        ("redis", state.redis.pool.state()),
+    ];
+    (
+        [(CONTENT_TYPE, CONTENT_TYPE_PROMETHEUS)],
+        Metrics::render(&pools),
+    )
+}
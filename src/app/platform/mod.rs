@@ -1,2 +1,7 @@
+pub mod dead_letter;
+pub mod health;
+pub mod idempotency;
 pub mod iam;
+pub mod maintenance;
+pub mod metrics;
 pub mod response;
\ No newline at end of file
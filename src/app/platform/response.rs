@@ -6,6 +6,8 @@ use serde::{Serialize, Serializer};
 pub struct ErrorJsonResponse {
     #[serde(serialize_with = "serialize_status_code")]
     status_code: StatusCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
     message: String,
 }
 
@@ -13,9 +15,16 @@ impl ErrorJsonResponse {
     pub fn new(status_code: StatusCode, message: &str) -> Self {
         Self {
             status_code,
+            code: None,
             message: message.to_string(),
         }
     }
+
+    /// Attaches a machine-readable code, ex: `UserNotFound`.
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(code.to_string());
+        self
+    }
 }
 
 impl IntoResponse for ErrorJsonResponse {
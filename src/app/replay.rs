@@ -0,0 +1,427 @@
+use std::{collections::HashMap, env, io};
+
+use serde_json::Value;
+
+use super::{
+    ark::ArkServer,
+    database::{postgres::PostgresDatabase, redis::RedisDatabase},
+    service::{
+        cache::manager::CacheManager,
+        task::{
+            manager::{TaskManager, TaskManagerConfig},
+            message::{TaskRequest, TaskResponse},
+            recorder::{RecordedTask, TaskRecorder},
+        },
+    },
+};
+
+/// How a recording is compared against the responses of a fresh database.
+///
+/// # Fields
+///
+/// * `ignore_fields` - Fields of a result that are generated on every run,
+///   ex: ids and timestamps. They are not compared, and a generated id is
+///   swapped into the payloads of the later requests that refer to it.
+pub struct ReplayConfig {
+    pub ignore_fields: Vec<String>,
+}
+
+impl Default for ReplayConfig {
+    /// Reads `TASK_REPLAY_IGNORE_FIELDS` ex: `role_id,created_at`, which
+    /// defaults to the ids, timestamps and tokens of the IAM models.
+    fn default() -> Self {
+        let ignore_fields = env::var("TASK_REPLAY_IGNORE_FIELDS").unwrap_or(String::from(
+            "role_id,permission_id,user_id,created_at,updated_at,token,stamp,expiry,expires_in,csrf_token,access_token",
+        ));
+        Self {
+            ignore_fields: ignore_fields
+                .split(',')
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+/// A recorded task whose response differs from the one it got on replay.
+pub struct ReplayDifference {
+    pub index: usize,
+    pub task_id: String,
+    pub task_action: String,
+    pub mismatches: Vec<String>,
+}
+
+/// The outcome of a replay.
+pub struct ReplayReport {
+    pub replayed: usize,
+    pub differences: Vec<ReplayDifference>,
+}
+
+/// Feeds a recording made with `TASK_RECORD` to the tasks of this build,
+/// one request at a time in the order they completed, and reports every
+/// response that differs from the recorded one. Run it against an empty
+/// database and Redis, the schema applied.
+///
+/// # Example
+///
+/// ```
+/// let report = ArkReplay::new(ReplayConfig::default()).run(pg, redis, "recordings/ark.jsonl").await?;
+/// ```
+pub struct ArkReplay {
+    config: ReplayConfig,
+}
+
+impl ArkReplay {
+    pub fn new(config: ReplayConfig) -> Self {
+        Self { config }
+    }
+
+    /// Registers the tasks then replays the recording. The dead letters,
+    /// idempotency keys and recorder of the server are left off, so the
+    /// replay runs the tasks alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `pg` - An instance of `PostgresDatabase`, empty, the tasks run against.
+    /// * `redis` - An instance of `RedisDatabase`, empty, the tasks use.
+    /// * `path` - The JSONL file written by `TaskRecorder`.
+    pub async fn run(
+        self,
+        pg: PostgresDatabase,
+        redis: RedisDatabase,
+        path: &str,
+    ) -> io::Result<ReplayReport> {
+        let recording = TaskRecorder::read(path)?;
+        println!(
+            "[REPLAY] replaying {} tasks from {}.",
+            recording.len(),
+            path
+        );
+        ArkServer::register_tasks();
+        TaskManager::new(pg, redis.clone(), TaskManagerConfig::default()).listen();
+        CacheManager::new(redis).listen();
+        ArkServer::preload_necessities().await;
+        // generated values of the recording, mapped to the ones generated on replay.
+        let mut substitutions: HashMap<String, String> = HashMap::new();
+        let mut differences = Vec::new();
+        for (index, recorded) in recording.iter().enumerate() {
+            let task_request = Self::compose_request(recorded, &substitutions);
+            let task_response = TaskManager::execute_locally(task_request).await;
+            let mismatches =
+                self.compare(&recorded.task_response, &task_response, &mut substitutions);
+            if mismatches.is_empty() {
+                continue;
+            }
+            println!(
+                "[REPLAY] #{} {} ({}) differs: {}",
+                index,
+                recorded.task_request.task_action,
+                recorded.task_request.task_id,
+                mismatches.join("; ")
+            );
+            differences.push(ReplayDifference {
+                index,
+                task_id: recorded.task_request.task_id.clone(),
+                task_action: recorded.task_request.task_action.clone(),
+                mismatches,
+            });
+        }
+        println!(
+            "[REPLAY] {} tasks replayed, {} differ.",
+            recording.len(),
+            differences.len()
+        );
+        Ok(ReplayReport {
+            replayed: recording.len(),
+            differences,
+        })
+    }
+
+    /// The recorded request, with the generated values it refers to swapped
+    /// for the ones generated on replay.
+    fn compose_request(
+        recorded: &RecordedTask,
+        substitutions: &HashMap<String, String>,
+    ) -> TaskRequest {
+        let mut task_request = recorded.task_request.clone();
+        task_request.task_reply_to = None;
+        if let Ok(mut payload) = serde_json::from_str::<Value>(&task_request.task_payload) {
+            substitute(&mut payload, substitutions);
+            task_request.task_payload = payload.to_string();
+        }
+        task_request
+    }
+
+    /// What differs between the recorded response and the one of the replay,
+    /// learning the generated values of the replay along the way.
+    fn compare(
+        &self,
+        recorded: &TaskResponse,
+        replayed: &TaskResponse,
+        substitutions: &mut HashMap<String, String>,
+    ) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if recorded.task_status != replayed.task_status {
+            mismatches.push(format!(
+                "status {:?} became {:?}",
+                recorded.task_status, replayed.task_status
+            ));
+        }
+        if recorded.task_error != replayed.task_error {
+            mismatches.push(format!(
+                "error {:?} became {:?}",
+                recorded.task_error, replayed.task_error
+            ));
+        }
+        match (
+            serde_json::from_str::<Value>(&recorded.task_result),
+            serde_json::from_str::<Value>(&replayed.task_result),
+        ) {
+            (Ok(mut recorded_result), Ok(replayed_result)) => {
+                self.learn(&recorded_result, &replayed_result, substitutions);
+                substitute(&mut recorded_result, substitutions);
+                self.diff("$", &recorded_result, &replayed_result, &mut mismatches);
+            }
+            _ if recorded.task_result != replayed.task_result => {
+                mismatches.push(String::from("result differs"));
+            }
+            _ => {}
+        }
+        mismatches
+    }
+
+    /// Maps the ignored values of the recording to the ones of the replay.
+    fn learn(
+        &self,
+        recorded: &Value,
+        replayed: &Value,
+        substitutions: &mut HashMap<String, String>,
+    ) {
+        match (recorded, replayed) {
+            (Value::Object(recorded), Value::Object(replayed)) => {
+                for (field, recorded_value) in recorded {
+                    let Some(replayed_value) = replayed.get(field) else {
+                        continue;
+                    };
+                    match (recorded_value, replayed_value) {
+                        (Value::String(from), Value::String(to))
+                            if self.is_ignored(field) && from.ne(to) =>
+                        {
+                            substitutions.insert(from.clone(), to.clone());
+                        }
+                        _ => self.learn(recorded_value, replayed_value, substitutions),
+                    }
+                }
+            }
+            (Value::Array(recorded), Value::Array(replayed)) => {
+                for (recorded_value, replayed_value) in recorded.iter().zip(replayed) {
+                    self.learn(recorded_value, replayed_value, substitutions);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Pushes the path of every value that differs, ignored fields aside.
+    fn diff(&self, path: &str, recorded: &Value, replayed: &Value, mismatches: &mut Vec<String>) {
+        match (recorded, replayed) {
+            (Value::Object(recorded_fields), Value::Object(replayed_fields)) => {
+                for field in recorded_fields.keys().chain(
+                    replayed_fields
+                        .keys()
+                        .filter(|field| !recorded_fields.contains_key(*field)),
+                ) {
+                    if self.is_ignored(field) {
+                        continue;
+                    }
+                    self.diff(
+                        &format!("{}.{}", path, field),
+                        recorded_fields.get(field).unwrap_or(&Value::Null),
+                        replayed_fields.get(field).unwrap_or(&Value::Null),
+                        mismatches,
+                    );
+                }
+            }
+            (Value::Array(recorded_values), Value::Array(replayed_values))
+                if recorded_values.len() == replayed_values.len() =>
+            {
+                // rows read back from the database come in no particular order.
+                let (recorded_values, replayed_values) =
+                    (sorted(recorded_values), sorted(replayed_values));
+                for (index, (recorded_value, replayed_value)) in recorded_values
+                    .iter()
+                    .zip(replayed_values.iter())
+                    .enumerate()
+                {
+                    self.diff(
+                        &format!("{}[{}]", path, index),
+                        recorded_value,
+                        replayed_value,
+                        mismatches,
+                    );
+                }
+            }
+            _ if recorded != replayed => {
+                mismatches.push(format!("{} {} became {}", path, recorded, replayed));
+            }
+            _ => {}
+        }
+    }
+
+    fn is_ignored(&self, field: &str) -> bool {
+        self.config
+            .ignore_fields
+            .iter()
+            .any(|ignored| ignored.eq(field))
+    }
+}
+
+/// Swaps every string of a value found in the substitutions.
+fn substitute(value: &mut Value, substitutions: &HashMap<String, String>) {
+    match value {
+        Value::String(text) => {
+            if let Some(substitute) = substitutions.get(text) {
+                *text = substitute.clone();
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| substitute(value, substitutions)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|value| substitute(value, substitutions)),
+        _ => {}
+    }
+}
+
+/// Sorts the values of an array of scalars, arrays of objects keep their order.
+fn sorted(values: &[Value]) -> Vec<Value> {
+    let mut values = values.to_vec();
+    if values
+        .iter()
+        .all(|value| !value.is_object() && !value.is_array())
+    {
+        values.sort_by_key(|value| value.to_string());
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn replay() -> ArkReplay {
+        ArkReplay::new(ReplayConfig {
+            ignore_fields: vec![String::from("role_id"), String::from("created_at")],
+        })
+    }
+
+    fn diff(recorded: Value, replayed: Value) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        replay().diff("$", &recorded, &replayed, &mut mismatches);
+        mismatches
+    }
+
+    #[test]
+    fn diff_skips_ignored_fields() {
+        let mismatches = diff(
+            json!({"role_id": "a", "created_at": 1, "role_name": "Moderator"}),
+            json!({"role_id": "b", "created_at": 2, "role_name": "Moderator"}),
+        );
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_the_path_of_each_difference() {
+        let mismatches = diff(
+            json!({"role": {"role_name": "Moderator", "permissions": ["read"]}}),
+            json!({"role": {"role_name": "Admin", "permissions": ["write"]}}),
+        );
+        assert_eq!(
+            mismatches,
+            vec![
+                String::from(r#"$.role.permissions[0] "read" became "write""#),
+                String::from(r#"$.role.role_name "Moderator" became "Admin""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_reports_missing_and_extra_fields() {
+        let mismatches = diff(json!({"a": 1}), json!({"b": 2}));
+        assert_eq!(
+            mismatches,
+            vec![
+                String::from("$.a 1 became null"),
+                String::from("$.b null became 2")
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_ignores_the_order_of_scalars() {
+        assert!(diff(json!(["b", "a", 1]), json!([1, "a", "b"])).is_empty());
+        assert_eq!(
+            diff(json!([{"a": 1}, {"a": 2}]), json!([{"a": 2}, {"a": 1}])).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn diff_reports_arrays_of_other_lengths_whole() {
+        assert_eq!(
+            diff(json!(["a"]), json!(["a", "b"])),
+            vec![String::from(r#"$ ["a"] became ["a","b"]"#)]
+        );
+    }
+
+    #[test]
+    fn learn_maps_ignored_values_only() {
+        let mut substitutions = HashMap::new();
+        replay().learn(
+            &json!({"role_id": "old", "role_name": "Moderator", "roles": [{"role_id": "old2"}]}),
+            &json!({"role_id": "new", "role_name": "Admin", "roles": [{"role_id": "new2"}]}),
+            &mut substitutions,
+        );
+        assert_eq!(
+            substitutions,
+            HashMap::from([
+                (String::from("old"), String::from("new")),
+                (String::from("old2"), String::from("new2")),
+            ])
+        );
+    }
+
+    #[test]
+    fn learn_skips_values_that_did_not_change() {
+        let mut substitutions = HashMap::new();
+        replay().learn(
+            &json!({"role_id": "same", "created_at": 1}),
+            &json!({"role_id": "same", "created_at": 2}),
+            &mut substitutions,
+        );
+        assert!(substitutions.is_empty());
+    }
+
+    #[test]
+    fn substitute_swaps_strings_at_any_depth() {
+        let substitutions = HashMap::from([(String::from("old"), String::from("new"))]);
+        let mut value = json!({"role_id": "old", "roles": ["old", "other"], "nested": {"id": "old"}, "count": 1});
+        substitute(&mut value, &substitutions);
+        assert_eq!(
+            value,
+            json!({"role_id": "new", "roles": ["new", "other"], "nested": {"id": "new"}, "count": 1})
+        );
+    }
+
+    #[test]
+    fn substitute_keeps_partial_matches() {
+        let substitutions = HashMap::from([(String::from("old"), String::from("new"))]);
+        let mut value = json!({"role_name": "old role"});
+        substitute(&mut value, &substitutions);
+        assert_eq!(value, json!({"role_name": "old role"}));
+    }
+}
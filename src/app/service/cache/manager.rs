@@ -15,11 +15,14 @@
 
 // CacheRequest {cache_id, cache_action, cache}
 
+use std::{sync::atomic::Ordering, time::Instant};
+
 use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot::{self, Receiver, Sender};
 
-use crate::app::{database::redis::RedisDatabase, platform::iam::user::cache::UserCacheHandler, service::cache::INBOUND_CACHE};
+use crate::app::{database::redis::RedisDatabase, platform::iam::user::cache::UserCacheHandler, service::{cache::{CACHE_LISTENER, INBOUND_CACHE, INBOUND_CACHE_DEPTH}, metrics::Metrics}};
 
-use super::{error::{CacheError, CacheResult}, message::{CacheLocation, CacheRequest, CacheResponse, CacheStatus}, CacheHandler, OUTBOUND_CACHE};
+use super::{error::{CacheError, CacheResult}, message::{CacheLocation, CacheRequest, CacheResponse, CacheStatus}, CacheHandler};
 
 
 pub struct CacheManager {
@@ -43,7 +46,8 @@ impl CacheManager {
         self.initialize_listener(redis_clone);
     }
 
-    /// Sends a cache_request to the cache channel.
+    /// Sends a cache_request to the cache channel and waits for its completion
+    /// without blocking the runtime.
     ///
     /// # Arguments
     /// - `cache_request`: The `CacheRequest` object containing details about the task to be handled.       
@@ -51,25 +55,41 @@ impl CacheManager {
     /// # Examples
     /// ```
     /// // Assume `redis` is a reference to a RedisDatabase and `cache_request` is a valid CacheRequest
-    /// self.send(cache_request).await;
+    /// CacheManager::send(cache_request).await;
     /// ```
-    pub fn send(cache_request: CacheRequest) -> CacheResponse {
-        INBOUND_CACHE.0.send(cache_request.clone()).unwrap();
-        Self::wait_for_cache_completion(&cache_request)
+    pub async fn send(mut cache_request: CacheRequest) -> CacheResponse {
+        let started = Instant::now();
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        cache_request.cache_reply = Some(reply_sender);
+        let cache_id = cache_request.cache_id.clone();
+        let cache_action = cache_request.cache_action.clone();
+        // when the listener is gone the request, and its reply channel, are
+        // dropped here which fails the caller instead of leaving it waiting.
+        // counted before it is sent so the listener never takes it below zero.
+        INBOUND_CACHE_DEPTH.fetch_add(1, Ordering::Relaxed);
+        if INBOUND_CACHE.0.send(cache_request).is_err() {
+            INBOUND_CACHE_DEPTH.fetch_sub(1, Ordering::Relaxed);
+        }
+        let cache_response = Self::wait_for_cache_completion(&cache_id, reply_receiver).await;
+        Metrics::observe_cache(&cache_action, &cache_response.cache_status, started.elapsed());
+        cache_response
     }
 
-    /// Sends a cache_response to the cache_response channel.
+    /// Sends a cache_response back to the caller through the request's reply channel.
     ///
     /// # Arguments
-    /// - `cache_response`: The `CacheResponse` object containing details about the task to be handled.       
+    /// - `cache_reply`: The reply channel taken out of the `CacheRequest`.
+    /// - `cache_response`: The `CacheResponse` object containing details about the task to be handled.
     ///
     /// # Examples
     /// ```
-    /// // Assume `redis` is a reference to a RedisDatabase and `cache_response` is a valid CacheRequest
-    /// self.send_response(cache_request).await;
+    /// Self::send_response(cache_request.cache_reply.take(), cache_response);
     /// ```
-    fn send_response(cache_response: CacheResponse) {
-        OUTBOUND_CACHE.0.send(cache_response).unwrap();
+    fn send_response(cache_reply: Option<Sender<CacheResponse>>, cache_response: CacheResponse) {
+        // the caller may have gone away, there is no one left to tell.
+        if let Some(cache_reply) = cache_reply {
+            let _ = cache_reply.send(cache_response);
+        }
     }
 
     /// Process task.
@@ -80,10 +100,10 @@ impl CacheManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// Self::process_permission_task(request)
+    /// CacheManager::process_cache(request).await
     /// ```
-    pub fn process_cache(request: CacheRequest) -> CacheResult<CacheStatus> {
-        let cache_response = Self::send(request);
+    pub async fn process_cache(request: CacheRequest) -> CacheResult<CacheStatus> {
+        let cache_response = Self::send(request).await;
         match cache_response.cache_status {
             CacheStatus::Completed => Ok(CacheStatus::Completed),
             CacheStatus::Failed => Err(CacheError::FailedToCompleteCache),
@@ -98,14 +118,13 @@ impl CacheManager {
     /// # Examples
     /// ```
     /// // Assuming `user` is a reference to a valid User
-    /// Self::process_task_with_result(request) -> CacheRequest<T>
+    /// CacheManager::process_cache_with_result::<T>(request).await -> CacheResult<T>
     /// ```
-    pub fn process_cache_with_result<T: for<'a> Deserialize<'a> + Serialize>(request: CacheRequest) -> CacheResult<T> {
-        let cache_response = Self::send(request);
+    pub async fn process_cache_with_result<T: for<'a> Deserialize<'a> + Serialize>(request: CacheRequest) -> CacheResult<T> {
+        let cache_response = Self::send(request).await;
         match cache_response.cache_status {
             CacheStatus::Completed => {
-                let response = CacheResponse::intepret_response_result::<T>(&cache_response);
-                response
+                CacheResponse::intepret_response_result::<T>(&cache_response)
             },
             CacheStatus::Failed => Err(CacheError::FailedToCompleteCache),
         }
@@ -168,26 +187,63 @@ impl CacheManager {
     /// self.initialize_listener(pg_clone);
     /// ```
     fn initialize_listener(self, redis_clone: RedisDatabase) {
-        tokio::task::spawn(async move {
-            let inbound_receiver = &INBOUND_CACHE.1;
+        let mut inbound_receiver = match INBOUND_CACHE.1.lock().unwrap().take() {
+            Some(inbound_receiver) => inbound_receiver,
+            None => return,
+        };
+        let listener = tokio::task::spawn(async move {
             println!("[ARK] Cache initialized, now listening to incoming requests.");
-            while let Ok(cache_request) = inbound_receiver.recv() {
+            while let Some(cache_request) = inbound_receiver.recv().await {
+                INBOUND_CACHE_DEPTH.fetch_sub(1, Ordering::Relaxed);
                 self.process_incoming_request(&redis_clone, cache_request)
                     .await;
             }
         });
+        let _ = CACHE_LISTENER.set(listener);
     }
 
+    /// Whether the cache listener loop is still running.
     ///
-    /// todo comment.
-    fn wait_for_cache_completion(cache_request: &CacheRequest) -> CacheResponse {
-        for cache in OUTBOUND_CACHE.1.iter(){
-            if cache_request.cache_id.eq(&cache_request.cache_id) {
-                Self::log_cache_outcome(&cache);
-                return cache;
-            }
-        }
-        unreachable!()
+    /// # Examples
+    /// ```
+    /// if !CacheManager::is_listening() { /* not ready */ }
+    /// ```
+    pub fn is_listening() -> bool {
+        CACHE_LISTENER
+            .get()
+            .is_some_and(|listener| !listener.is_finished())
+    }
+
+    /// How many requests are waiting inside of `INBOUND_CACHE`.
+    ///
+    /// # Examples
+    /// ```
+    /// let queue_depth = CacheManager::queue_depth();
+    /// ```
+    pub fn queue_depth() -> usize {
+        INBOUND_CACHE_DEPTH.load(Ordering::Relaxed)
+    }
+
+    /// Waits for the response of a specific cache request on its reply channel.
+    ///
+    /// # Arguments
+    /// - `cache_id`: The id of the `CacheRequest` for which the completion is awaited.
+    /// - `reply_receiver`: The receiving end of the request's reply channel.
+    ///
+    /// # Examples
+    /// ```
+    /// let cache_response = Self::wait_for_cache_completion(&cache_id, reply_receiver).await;
+    /// ```
+    async fn wait_for_cache_completion(cache_id: &str, reply_receiver: Receiver<CacheResponse>) -> CacheResponse {
+        // the reply channel is dropped without an answer when the handler panics.
+        let cache_response = reply_receiver.await.unwrap_or_else(|_| CacheResponse {
+            cache_id: String::from(cache_id),
+            cache_result: String::default(),
+            cache_error: vec![CacheError::FailedToCompleteCache.to_string()],
+            cache_status: CacheStatus::Failed,
+        });
+        Self::log_cache_outcome(&cache_response);
+        cache_response
     }
 
     /// Processes an incoming cache request.
@@ -224,13 +280,12 @@ impl CacheManager {
     /// // Assume `redis` is a reference to a RedisDatabase and `cache_request` is a valid CacheRequest
     /// self.handle_cache_request(&redis, cache_request).await;
     /// ```
-    async fn handle_cache_request(&self, redis: &RedisDatabase, cache_request: CacheRequest) {
-        match cache_request.cache_location {
-            CacheLocation::User => {
-                let cache_response = UserCacheHandler::handle(redis.clone(), cache_request).await;
-                Self::send_response(cache_response);
-            }
-        }
+    async fn handle_cache_request(&self, redis: &RedisDatabase, mut cache_request: CacheRequest) {
+        let cache_reply = cache_request.cache_reply.take();
+        let cache_response = match cache_request.cache_location {
+            CacheLocation::User => UserCacheHandler::handle(redis.clone(), cache_request).await,
+        };
+        Self::send_response(cache_reply, cache_response);
     }
 
      /// Logs the outcome of a cache based on its response status.
@@ -251,7 +306,7 @@ impl CacheManager {
             ),
             CacheStatus::Failed => println!(
                 "[CACHE] Cache: {} did not complete successfully. Error: {}",
-                cache_response.cache_id, cache_response.cache_error[0]
+                cache_response.cache_id, cache_response.cache_error.join(", ")
             ),
         }
     }
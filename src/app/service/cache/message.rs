@@ -1,6 +1,7 @@
 use nanoid::nanoid;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot::Sender;
 
 use super::{error::{CacheError, CacheResult}};
 
@@ -16,7 +17,7 @@ pub enum CacheStatus {
 }
 
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CacheRequest {
     /// A unique identifier for the requested cache item.
     pub cache_id: String,
@@ -29,6 +30,24 @@ pub struct CacheRequest {
 
     /// The location to store the item in the cache.
     pub cache_location: CacheLocation,
+
+    /// Where the `CacheResponse` is sent back to, only the caller that sent the
+    /// request holds the receiving end.
+    #[serde(skip)]
+    pub cache_reply: Option<Sender<CacheResponse>>,
+}
+
+/// The reply channel can only be answered once, so it is never cloned.
+impl Clone for CacheRequest {
+    fn clone(&self) -> Self {
+        Self {
+            cache_id: self.cache_id.clone(),
+            cache_action: self.cache_action.clone(),
+            cache_payload: self.cache_payload.clone(),
+            cache_location: self.cache_location.clone(),
+            cache_reply: None,
+        }
+    }
 }
 
 impl CacheRequest {
@@ -42,6 +61,7 @@ impl CacheRequest {
             cache_payload: serde_json::to_string(&cache_payload).unwrap(),
             cache_action: String::from(cache_action),
             cache_location,
+            cache_reply: None,
         }
     }
 
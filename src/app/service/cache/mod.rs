@@ -21,33 +21,53 @@
 //                                |                       |
 //                                v                       v
 //                        +-------+-------+       +-------+--------+
-//                        | Outbound      |       | Outbound       |
+//                        | Reply         |       | Reply          |
 //                        | Channel       |       | Channel        |
 //                        | Returns Cached|       | Creates Cache  |
 //                        | Item          |       | Entry & Returns|
 //                        +---------------+       | Result         |
 //                                                +----------------+
+use std::sync::{atomic::AtomicUsize, Mutex};
+
 use axum::async_trait;
 use chrono::Utc;
-use crossbeam_channel::{unbounded, Receiver, Sender};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
 
 use self::{error::CacheResult, message::{CacheRequest, CacheResponse}};
 
+use super::metrics::Metrics;
+
 pub mod error;
 pub mod manager;
 pub mod message;
 
-static INBOUND_CACHE: Lazy<(Sender<CacheRequest>, Receiver<CacheRequest>)> =
-   Lazy::new(|| unbounded());
-static OUTBOUND_CACHE: Lazy<(Sender<CacheResponse>, Receiver<CacheResponse>)> =
-    Lazy::new(|| unbounded());
+/// The receiver is taken out by the listener once it starts.
+#[allow(clippy::type_complexity)]
+static INBOUND_CACHE: Lazy<(UnboundedSender<CacheRequest>, Mutex<Option<UnboundedReceiver<CacheRequest>>>)> =
+    Lazy::new(|| {
+        let (sender, receiver) = unbounded_channel();
+        (sender, Mutex::new(Some(receiver)))
+    });
+
+/// How many requests are waiting inside of `INBOUND_CACHE`, which being
+/// unbounded cannot tell by itself.
+static INBOUND_CACHE_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// The listener loop spawned by `CacheManager::listen`.
+static CACHE_LISTENER: OnceCell<JoinHandle<()>> = OnceCell::new();
 
 pub trait LocalizedCache<T> {
     fn add(item: T);
     fn single_add(item: T);
+    /// Removes the item along with every other key that points to it.
     fn remove(id: &str) -> CacheResult<bool>;
     fn get(id: &str) -> CacheResult<T>;
+    /// Every unique item inside of the cache.
+    fn all() -> Vec<T>;
 }
 
 #[async_trait]
@@ -62,23 +82,25 @@ pub trait CacheHandler<T> {
 }
 
 pub fn notify_cache_hit(source: &str, action: &str, task_id: &str) {
+    Metrics::cache_hit(source);
     // todo do some actual logging here...
     println!(
         "[CACHE] HIT Successfully retrieved the requested item from the cache\n - Task Id: {}\n - Cache Key: {}\n - Timestamp: {}\n - Source: {}",
         task_id,
         action,
-        Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        Utc::now().format("%Y-%m-%d %H:%M:%S"),
         source
     );
 }
 
 pub fn notify_cache_miss(source: &str, cache_key: &str, task_id: &str) {
+    Metrics::cache_miss(source);
     // todo do some actual logging here...
     println!(
         "[CACHE] MISS The requested item was not found in the cache.\n - Task Id: {}\n - Cache Key: {}\n - Timestamp: {}\n - Source: {}",
         task_id,
         cache_key,
-        Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        Utc::now().format("%Y-%m-%d %H:%M:%S"),
         source
     );
 }
@@ -0,0 +1,441 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+use bb8::State;
+use once_cell::sync::Lazy;
+
+use super::{
+    cache::{manager::CacheManager, message::CacheStatus},
+    task::{
+        manager::TaskManager,
+        message::{TaskStatus, TaskType},
+    },
+};
+
+/// The upper bounds of the latency buckets, in seconds.
+static LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Everything recorded since the process started.
+static METRICS: Lazy<Mutex<MetricsRegistry>> = Lazy::new(|| Mutex::new(MetricsRegistry::default()));
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS.iter()) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Default)]
+struct MetricsRegistry {
+    tasks: BTreeMap<Labels, u64>,
+    task_durations: BTreeMap<Labels, Histogram>,
+    caches: BTreeMap<Labels, u64>,
+    cache_durations: BTreeMap<Labels, Histogram>,
+    cache_lookups: BTreeMap<Labels, u64>,
+}
+
+/// Counters and latency histograms of the tasks and caches, served in the
+/// Prometheus text format on `/metrics`.
+pub struct Metrics;
+
+impl Metrics {
+    /// Records a finished task, timed from when its caller sent it.
+    ///
+    /// # Arguments
+    /// - `task_type`: the `TaskType` of the task.
+    /// - `task_action`: the `task_action` of the task.
+    /// - `task_status`: how the task ended.
+    /// - `elapsed`: how long the caller waited.
+    ///
+    /// # Examples
+    /// ```
+    /// Metrics::observe_task(&TaskType::User, "user_read", &TaskStatus::Completed, started.elapsed());
+    /// ```
+    pub fn observe_task(
+        task_type: &TaskType,
+        task_action: &str,
+        task_status: &TaskStatus,
+        elapsed: Duration,
+    ) {
+        let status = match task_status {
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+        };
+        let labels: Labels = vec![
+            ("type", String::from(task_type.name())),
+            ("action", String::from(task_action)),
+            ("status", String::from(status)),
+        ];
+        let mut metrics = METRICS.lock().unwrap();
+        *metrics.tasks.entry(labels.clone()).or_default() += 1;
+        metrics
+            .task_durations
+            .entry(labels)
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Records a finished cache request, timed from when its caller sent it.
+    ///
+    /// # Arguments
+    /// - `cache_action`: the `cache_action` of the request.
+    /// - `cache_status`: how the request ended.
+    /// - `elapsed`: how long the caller waited.
+    ///
+    /// # Examples
+    /// ```
+    /// Metrics::observe_cache("user_read_from_cache", &CacheStatus::Completed, started.elapsed());
+    /// ```
+    pub fn observe_cache(cache_action: &str, cache_status: &CacheStatus, elapsed: Duration) {
+        let status = match cache_status {
+            CacheStatus::Completed => "completed",
+            CacheStatus::Failed => "failed",
+        };
+        let labels: Labels = vec![
+            ("action", String::from(cache_action)),
+            ("status", String::from(status)),
+        ];
+        let mut metrics = METRICS.lock().unwrap();
+        *metrics.caches.entry(labels.clone()).or_default() += 1;
+        metrics
+            .cache_durations
+            .entry(labels)
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Records a lookup that was answered by a cache.
+    pub fn cache_hit(source: &str) {
+        Self::cache_lookup(source, "hit");
+    }
+
+    /// Records a lookup that a cache could not answer.
+    pub fn cache_miss(source: &str) {
+        Self::cache_lookup(source, "miss");
+    }
+
+    fn cache_lookup(source: &str, result: &str) {
+        let labels: Labels = vec![
+            ("source", String::from(source)),
+            ("result", String::from(result)),
+        ];
+        *METRICS
+            .lock()
+            .unwrap()
+            .cache_lookups
+            .entry(labels)
+            .or_default() += 1;
+    }
+
+    /// Renders every metric, along with the queue depths and the given pool
+    /// states, in the Prometheus text format.
+    ///
+    /// # Arguments
+    /// - `pools`: the name and `State` of every connection pool.
+    ///
+    /// # Examples
+    /// ```
+    /// let body = Metrics::render(&[("postgres", pg.pool.state()), ("redis", redis.pool.state())]);
+    /// ```
+    pub fn render(pools: &[(&str, State)]) -> String {
+        let mut out = String::new();
+        {
+            let metrics = METRICS.lock().unwrap();
+            write_counter(
+                &mut out,
+                "ark_tasks_total",
+                "Tasks finished, by type, action and status.",
+                &metrics.tasks,
+            );
+            write_histogram(
+                &mut out,
+                "ark_task_duration_seconds",
+                "How long callers waited on tasks.",
+                &metrics.task_durations,
+            );
+            write_counter(
+                &mut out,
+                "ark_cache_requests_total",
+                "Cache requests finished, by action and status.",
+                &metrics.caches,
+            );
+            write_histogram(
+                &mut out,
+                "ark_cache_request_duration_seconds",
+                "How long callers waited on cache requests.",
+                &metrics.cache_durations,
+            );
+            write_counter(
+                &mut out,
+                "ark_cache_lookups_total",
+                "Cache lookups, by source and result.",
+                &metrics.cache_lookups,
+            );
+        }
+        write_gauge(
+            &mut out,
+            "ark_queue_depth",
+            "Requests waiting to be taken in by a listener.",
+            &[
+                (
+                    vec![("queue", String::from("task"))],
+                    TaskManager::queue_depth() as u64,
+                ),
+                (
+                    vec![("queue", String::from("cache"))],
+                    CacheManager::queue_depth() as u64,
+                ),
+            ],
+        );
+        let connections: Vec<(Labels, u64)> = pools
+            .iter()
+            .map(|(pool, state)| {
+                (
+                    vec![("pool", String::from(*pool))],
+                    state.connections as u64,
+                )
+            })
+            .collect();
+        write_gauge(
+            &mut out,
+            "ark_pool_connections",
+            "Connections opened by the pool.",
+            &connections,
+        );
+        let idle: Vec<(Labels, u64)> = pools
+            .iter()
+            .map(|(pool, state)| {
+                (
+                    vec![("pool", String::from(*pool))],
+                    state.idle_connections as u64,
+                )
+            })
+            .collect();
+        write_gauge(
+            &mut out,
+            "ark_pool_idle_connections",
+            "Connections of the pool that are not in use.",
+            &idle,
+        );
+        out
+    }
+}
+
+fn write_counter(out: &mut String, name: &str, help: &str, values: &BTreeMap<Labels, u64>) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+    for (labels, value) in values {
+        let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+    }
+}
+
+fn write_gauge(out: &mut String, name: &str, help: &str, values: &[(Labels, u64)]) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} gauge", name, help, name);
+    for (labels, value) in values {
+        let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+    }
+}
+
+fn write_histogram(out: &mut String, name: &str, help: &str, values: &BTreeMap<Labels, Histogram>) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+    for (labels, histogram) in values {
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+            let le = bound.to_string();
+            let _ = writeln!(
+                out,
+                "{}_bucket{} {}",
+                name,
+                format_labels(labels, Some(&le)),
+                count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{} {}",
+            name,
+            format_labels(labels, Some("+Inf")),
+            histogram.count
+        );
+        let _ = writeln!(
+            out,
+            "{}_sum{} {}",
+            name,
+            format_labels(labels, None),
+            histogram.sum
+        );
+        let _ = writeln!(
+            out,
+            "{}_count{} {}",
+            name,
+            format_labels(labels, None),
+            histogram.count
+        );
+    }
+}
+
+/// Formats labels as `{name="value",...}`, with `le` added for histogram buckets.
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+    match pairs.is_empty() {
+        true => String::default(),
+        false => format!("{{{}}}", pairs.join(",")),
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&'static str, &str)]) -> Labels {
+        pairs
+            .iter()
+            .map(|(name, value)| (*name, String::from(*value)))
+            .collect()
+    }
+
+    #[test]
+    fn escapes_backslashes_quotes_and_newlines() {
+        assert_eq!(escape_label("plain"), "plain");
+        assert_eq!(escape_label(r"a\b"), r"a\\b");
+        assert_eq!(escape_label(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_label("two\nlines"), r"two\nlines");
+        assert_eq!(escape_label("\\\"\n"), r#"\\\"\n"#);
+    }
+
+    #[test]
+    fn formats_labels_in_order_with_le_last() {
+        let task = labels(&[("type", "User"), ("action", "user_read")]);
+        assert_eq!(
+            format_labels(&task, None),
+            r#"{type="User",action="user_read"}"#
+        );
+        assert_eq!(
+            format_labels(&task, Some("0.5")),
+            r#"{type="User",action="user_read",le="0.5"}"#
+        );
+        assert_eq!(
+            format_labels(&labels(&[("action", "a\"b")]), None),
+            r#"{action="a\"b"}"#
+        );
+    }
+
+    #[test]
+    fn formats_no_labels_as_nothing() {
+        assert_eq!(format_labels(&Vec::new(), None), "");
+        assert_eq!(format_labels(&Vec::new(), Some("+Inf")), r#"{le="+Inf"}"#);
+    }
+
+    #[test]
+    fn renders_counters_and_gauges() {
+        let mut counters = BTreeMap::new();
+        counters.insert(labels(&[("status", "completed")]), 3);
+        let mut out = String::new();
+        write_counter(&mut out, "ark_test_total", "Tests.", &counters);
+        write_gauge(
+            &mut out,
+            "ark_test_depth",
+            "Depth.",
+            &[(labels(&[("queue", "task")]), 7)],
+        );
+        assert_eq!(
+            out,
+            "# HELP ark_test_total Tests.\n\
+             # TYPE ark_test_total counter\n\
+             ark_test_total{status=\"completed\"} 3\n\
+             # HELP ark_test_depth Depth.\n\
+             # TYPE ark_test_depth gauge\n\
+             ark_test_depth{queue=\"task\"} 7\n"
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::default();
+        histogram.observe(0.003);
+        histogram.observe(0.2);
+        histogram.observe(60.0);
+        assert_eq!(histogram.count, 3);
+        assert!((histogram.sum - 60.203).abs() < 1e-9);
+        // 0.001, 0.005 ... 0.1, 0.25 ... 10.0
+        assert_eq!(histogram.buckets, [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn renders_histograms_with_an_inf_bucket_sum_and_count() {
+        let mut histogram = Histogram::default();
+        histogram.observe(0.003);
+        histogram.observe(60.0);
+        let mut histograms = BTreeMap::new();
+        histograms.insert(labels(&[("action", "user_read")]), histogram);
+        let mut out = String::new();
+        write_histogram(&mut out, "ark_test_seconds", "Latency.", &histograms);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "# HELP ark_test_seconds Latency.");
+        assert_eq!(lines[1], "# TYPE ark_test_seconds histogram");
+        assert_eq!(
+            lines[2],
+            r#"ark_test_seconds_bucket{action="user_read",le="0.001"} 0"#
+        );
+        assert_eq!(
+            lines[3],
+            r#"ark_test_seconds_bucket{action="user_read",le="0.005"} 1"#
+        );
+        assert_eq!(
+            lines[13],
+            r#"ark_test_seconds_bucket{action="user_read",le="10"} 1"#
+        );
+        assert_eq!(
+            lines[14],
+            r#"ark_test_seconds_bucket{action="user_read",le="+Inf"} 2"#
+        );
+        assert_eq!(lines[15], r#"ark_test_seconds_sum{action="user_read"} 60.003"#);
+        assert_eq!(lines[16], r#"ark_test_seconds_count{action="user_read"} 2"#);
+        assert_eq!(lines.len(), 17);
+    }
+
+    #[test]
+    fn renders_observed_tasks_with_escaped_labels() {
+        Metrics::observe_task(
+            &TaskType::Custom(String::from("Report")),
+            "report_\"render\"",
+            &TaskStatus::Failed,
+            Duration::from_millis(20),
+        );
+        let out = Metrics::render(&[]);
+        assert!(out.contains(
+            r#"ark_tasks_total{type="Report",action="report_\"render\"",status="failed"} 1"#
+        ));
+        assert!(out.contains(
+            r#"ark_task_duration_seconds_bucket{type="Report",action="report_\"render\"",status="failed",le="0.025"} 1"#
+        ));
+        assert!(out.contains(r#"ark_queue_depth{queue="task"} 0"#));
+        assert!(out.contains("# TYPE ark_pool_connections gauge\n"));
+    }
+}
@@ -0,0 +1,456 @@
+//
+// Dead Letters
+//
+// +---------------------+       +---------------------+       +---------------------+
+// | Task Fails With a   |       | Dead Letter Stream  |       | Admin API           |
+// | Server Side Error   | ----> | Keeps the Request,  | ----> | Lists, Inspects,    |
+// | ex: TransientFailure|       | Errors and Time     |       | Replays or Discards |
+// +---------------------+       +---------------------+       +---------------------+
+//
+
+use std::{env, str::FromStr};
+
+use axum::async_trait;
+use bb8_redis::redis::{
+    streams::{StreamId, StreamMaxlen, StreamRangeReply},
+    AsyncCommands, RedisError, RedisResult,
+};
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::app::database::redis::RedisDatabase;
+
+use super::{
+    error::{TaskError, TaskResult},
+    interceptor::TaskInterceptor,
+    manager::TaskManager,
+    message::{TaskRequest, TaskResponse, TaskStatus},
+    redact::{redact_fields_from_env, redact_text},
+    registry::TaskRegistry,
+    stream::from_run_error,
+};
+
+/// Where failed tasks are kept, set by `DeadLetters::enable`.
+static DEAD_LETTERS: OnceCell<DeadLetterStore> = OnceCell::new();
+
+/// The field of a dead letter holding its `TaskRequest`.
+static REQUEST_FIELD: &str = "task";
+
+/// The field of a dead letter holding the errors of the task.
+static ERROR_FIELD: &str = "error";
+
+/// The field of a dead letter holding when the task failed, in milliseconds.
+static FAILED_AT_FIELD: &str = "failed_at";
+
+/// The field of a dead letter set when secrets were redacted out of its payload.
+static REDACTED_FIELD: &str = "redacted";
+
+/// The metadata a replayed request carries, the id of the dead letter it came from.
+pub static REPLAY_OF_METADATA: &str = "replay_of";
+
+/// Where and how many failed tasks are kept.
+///
+/// # Fields
+///
+/// * `stream` - The stream failed tasks are added to.
+/// * `max_len` - Roughly how many failed tasks are kept, the oldest are trimmed first.
+/// * `redact_fields` - Fields of a payload holding secrets, kept as `[REDACTED]`
+///   at any depth, or as `null` when they are not a string.
+pub struct DeadLetterConfig {
+    pub stream: String,
+    pub max_len: usize,
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for DeadLetterConfig {
+    /// Reads `TASK_DEAD_LETTER_STREAM` (default `ark:dead_letters`),
+    /// `TASK_DEAD_LETTER_MAX_LEN` (default 10000) and
+    /// `TASK_DEAD_LETTER_REDACT_FIELDS`, ex: `token,stamp`, which defaults to
+    /// the tokens and stamps of the IAM models.
+    fn default() -> Self {
+        Self {
+            redact_fields: redact_fields_from_env("TASK_DEAD_LETTER_REDACT_FIELDS"),
+            stream: env::var("TASK_DEAD_LETTER_STREAM").unwrap_or(String::from("ark:dead_letters")),
+            max_len: env::var("TASK_DEAD_LETTER_MAX_LEN")
+                .ok()
+                .and_then(|max_len| max_len.parse().ok())
+                .unwrap_or(10_000),
+        }
+    }
+}
+
+/// A task that failed with a server side error, along with what it failed with.
+///
+/// # Fields
+///
+/// * `id` - The id of the entry inside of the stream.
+/// * `failed_at` - When the task failed, in milliseconds.
+/// * `task_error` - The `task_error` of the `TaskResponse`.
+/// * `task_request` - The request as it was sent, payload and metadata included,
+///   with the secrets of its payload redacted.
+/// * `redacted` - Whether secrets were redacted out of the payload, such a
+///   task cannot be replayed.
+#[derive(Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: String,
+    pub failed_at: i64,
+    pub task_error: Vec<String>,
+    pub task_request: TaskRequest,
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+impl DeadLetter {
+    fn from_entry(entry: &StreamId) -> Option<Self> {
+        let task_request = entry
+            .get::<String>(REQUEST_FIELD)
+            .and_then(|request| serde_json::from_str::<TaskRequest>(&request).ok())?;
+        Some(Self {
+            id: entry.id.clone(),
+            failed_at: entry.get::<i64>(FAILED_AT_FIELD).unwrap_or_default(),
+            task_error: entry
+                .get::<String>(ERROR_FIELD)
+                .and_then(|error| serde_json::from_str(&error).ok())
+                .unwrap_or_default(),
+            task_request,
+            redacted: entry.get::<String>(REDACTED_FIELD).as_deref() == Some("1"),
+        })
+    }
+
+    /// Whether the task may have run before it failed, ex: it timed out after
+    /// its transaction committed, so running it again could apply it twice.
+    fn may_have_run(&self) -> bool {
+        self.task_error
+            .first()
+            .and_then(|error| TaskError::from_str(error).ok())
+            .is_some_and(|error| {
+                matches!(
+                    error,
+                    TaskError::Timeout | TaskError::TaskAbandoned | TaskError::BatchOutcomeUnknown
+                )
+            })
+    }
+}
+
+/// The outcome of a replayed dead letter, `discarded` once the replay completed.
+#[derive(Serialize, Deserialize)]
+pub struct ReplayOutcome {
+    pub id: String,
+    pub task_id: String,
+    pub task_status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_error: Option<TaskError>,
+    pub discarded: bool,
+}
+
+struct DeadLetterStore {
+    redis: RedisDatabase,
+    config: DeadLetterConfig,
+    /// Feeds the failed tasks of this process to `DeadLetters::write`.
+    sender: UnboundedSender<(TaskRequest, Vec<String>)>,
+}
+
+/// Keeps the tasks that failed with a server side error, ex: `TransientFailure`,
+/// `Timeout` or `TaskInternalError`, in a Redis stream so they can be inspected
+/// and replayed. Errors a caller is answered with such as `UserNotFound`,
+/// `Cancelled` or `MaintenanceMode` are not kept.
+pub struct DeadLetters;
+
+impl DeadLetters {
+    /// Starts keeping the tasks this process fails, only the first call is used.
+    ///
+    /// # Arguments
+    /// - `redis`: the `RedisDatabase` the stream lives in.
+    /// - `config`: where and how many failed tasks are kept.
+    ///
+    /// # Examples
+    /// ```
+    /// DeadLetters::enable(redis, DeadLetterConfig::default());
+    /// ```
+    pub fn enable(redis: RedisDatabase, config: DeadLetterConfig) {
+        let (sender, receiver) = unbounded_channel();
+        let store = DeadLetterStore {
+            redis,
+            config,
+            sender,
+        };
+        if DEAD_LETTERS.set(store).is_ok() {
+            tokio::spawn(Self::write(receiver));
+            TaskManager::intercept(DeadLetterInterceptor);
+        }
+    }
+
+    /// Keeps the failed tasks it receives one at a time, so no reply waits
+    /// on Redis to be sent.
+    async fn write(mut receiver: UnboundedReceiver<(TaskRequest, Vec<String>)>) {
+        while let Some((task_request, task_error)) = receiver.recv().await {
+            Self::record(&task_request, &task_error).await;
+        }
+    }
+
+    /// Whether a failed task is kept, only server side errors are.
+    ///
+    /// # Arguments
+    /// - `task_response`: the response of the task.
+    pub fn is_dead_letter(task_response: &TaskResponse) -> bool {
+        match task_response.task_status {
+            TaskStatus::Completed => false,
+            TaskStatus::Failed => {
+                let error = TaskResponse::intepret_response_error(task_response);
+                error.status_code().is_server_error()
+                    && !matches!(error, TaskError::MaintenanceMode)
+            }
+        }
+    }
+
+    /// Keeps a failed task once Redis answers, a failure to keep it is only
+    /// logged. Meant for callers that must not go on before the dead letter
+    /// exists, ex: acknowledging a stream entry, the tasks of this process are
+    /// kept through the writer instead.
+    ///
+    /// # Arguments
+    /// - `task_request`: the request of the task.
+    /// - `task_error`: what the task failed with.
+    ///
+    /// # Examples
+    /// ```
+    /// DeadLetters::record(&task_request, &[TaskError::TaskAbandoned.to_string()]).await;
+    /// ```
+    pub async fn record(task_request: &TaskRequest, task_error: &[String]) {
+        let Some(store) = DEAD_LETTERS.get() else {
+            return;
+        };
+        let mut task_request = task_request.clone();
+        task_request.task_reply_to = None;
+        let (task_payload, redacted) =
+            redact_payload(&task_request.task_payload, &store.config.redact_fields);
+        task_request.task_payload = task_payload;
+        let result: RedisResult<String> = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            connection
+                .xadd_maxlen(
+                    &store.config.stream,
+                    StreamMaxlen::Approx(store.config.max_len),
+                    "*",
+                    &[
+                        (REQUEST_FIELD, serde_json::to_string(&task_request).unwrap()),
+                        (ERROR_FIELD, serde_json::to_string(task_error).unwrap()),
+                        (FAILED_AT_FIELD, Utc::now().timestamp_millis().to_string()),
+                        (REDACTED_FIELD, u8::from(redacted).to_string()),
+                    ],
+                )
+                .await
+        }
+        .await;
+        match result {
+            Ok(id) => println!(
+                "[TASK] Task: {} kept as dead letter {}.",
+                task_request.task_id, id
+            ),
+            Err(error) => println!(
+                "[TASK] Failed to keep task {} as a dead letter. Error: {}",
+                task_request.task_id, error
+            ),
+        }
+    }
+
+    /// The dead letters newest first.
+    ///
+    /// # Arguments
+    /// - `count`: how many to read.
+    /// - `before`: only read the ones older than this id, used to page.
+    ///
+    /// # Examples
+    /// ```
+    /// let dead_letters = DeadLetters::list(50, None).await?;
+    /// let older = DeadLetters::list(50, Some(&dead_letters.last().unwrap().id)).await?;
+    /// ```
+    pub async fn list(count: usize, before: Option<&str>) -> TaskResult<Vec<DeadLetter>> {
+        let store = Self::store()?;
+        let end = before.map_or(String::from("+"), |before| format!("({}", before));
+        let reply: StreamRangeReply = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            connection
+                .xrevrange_count(&store.config.stream, end, "-", count)
+                .await
+        }
+        .await
+        .map_err(Self::throw_redis_failure)?;
+        Ok(reply
+            .ids
+            .iter()
+            .filter_map(DeadLetter::from_entry)
+            .collect())
+    }
+
+    /// A single dead letter.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the dead letter.
+    ///
+    /// # Examples
+    /// ```
+    /// let dead_letter = DeadLetters::get("1700000000000-0").await?;
+    /// ```
+    pub async fn get(id: &str) -> TaskResult<DeadLetter> {
+        let store = Self::store()?;
+        let reply: StreamRangeReply = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            connection.xrange(&store.config.stream, id, id).await
+        }
+        .await
+        .map_err(Self::throw_redis_failure)?;
+        reply
+            .ids
+            .first()
+            .and_then(DeadLetter::from_entry)
+            .ok_or(TaskError::DeadLetterNotFound)
+    }
+
+    /// Removes a dead letter without running it.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the dead letter.
+    ///
+    /// # Examples
+    /// ```
+    /// DeadLetters::discard("1700000000000-0").await?;
+    /// ```
+    pub async fn discard(id: &str) -> TaskResult<()> {
+        let store = Self::store()?;
+        let deleted: usize = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            connection.xdel(&store.config.stream, &[id]).await
+        }
+        .await
+        .map_err(Self::throw_redis_failure)?;
+        match deleted {
+            0 => Err(TaskError::DeadLetterNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sends the request of a dead letter again under a new `task_id`, the
+    /// dead letter is removed once the replay completes and kept otherwise.
+    /// A task that may have run already, one that failed with `Timeout`,
+    /// `TaskAbandoned` or `BatchOutcomeUnknown`, is only replayed when it is
+    /// idempotent or when forced. A task whose secrets were redacted is never
+    /// replayed, its payload is no longer the one it was sent with.
+    ///
+    /// # Arguments
+    /// - `id`: the id of the dead letter.
+    /// - `force`: replays a task that may have run already even when it is not idempotent.
+    ///
+    /// # Examples
+    /// ```
+    /// let outcome = DeadLetters::replay("1700000000000-0", false).await?;
+    /// ```
+    pub async fn replay(id: &str, force: bool) -> TaskResult<ReplayOutcome> {
+        let dead_letter = Self::get(id).await?;
+        if dead_letter.redacted {
+            println!(
+                "[TASK] Refused to replay dead letter {}, the secrets of {} were not kept.",
+                id, dead_letter.task_request.task_action
+            );
+            return Err(TaskError::DeadLetterRedacted);
+        }
+        if !force
+            && dead_letter.may_have_run()
+            && !TaskRegistry::is_idempotent(&dead_letter.task_request.task_action)
+        {
+            println!(
+                "[TASK] Refused to replay dead letter {}, {} may have run already.",
+                id, dead_letter.task_request.task_action
+            );
+            return Err(TaskError::DeadLetterReplayUnsafe);
+        }
+        let stored = dead_letter.task_request;
+        let timeout = stored.timeout();
+        let mut task_request =
+            TaskRequest::compose_request((), stored.task_type, &stored.task_action)
+                .with_timeout(timeout);
+        task_request.task_payload = stored.task_payload;
+        task_request.task_metadata = stored.task_metadata;
+        let task_request = task_request.with_metadata(REPLAY_OF_METADATA, id);
+        let task_id = task_request.task_id.clone();
+        println!("[TASK] Replaying dead letter {} as {}.", id, task_id);
+        let (task_status, task_error) = match TaskManager::process_task(task_request).await {
+            Ok(task_status) => (task_status, None),
+            Err(error) => (TaskStatus::Failed, Some(error)),
+        };
+        // already gone when replayed twice at once, the outcome still stands.
+        let discarded = task_status == TaskStatus::Completed && Self::discard(id).await.is_ok();
+        Ok(ReplayOutcome {
+            id: String::from(id),
+            task_id,
+            task_status,
+            task_error,
+            discarded,
+        })
+    }
+
+    fn store() -> TaskResult<&'static DeadLetterStore> {
+        DEAD_LETTERS.get().ok_or(TaskError::DeadLettersDisabled)
+    }
+
+    fn throw_redis_failure(error: RedisError) -> TaskError {
+        println!("[TASK] Failed to read dead letters. Error: {}", error);
+        TaskError::TransientFailure
+    }
+}
+
+/// Keeps every task of this process that `DeadLetters::is_dead_letter`,
+/// except replays whose dead letter is still kept. They are handed to the
+/// writer of `DeadLetters::enable` so the reply is not held up by Redis.
+struct DeadLetterInterceptor;
+
+#[async_trait]
+impl TaskInterceptor for DeadLetterInterceptor {
+    async fn after(&self, task_request: &TaskRequest, task_response: &TaskResponse) {
+        if !DeadLetters::is_dead_letter(task_response)
+            || task_request.metadata(REPLAY_OF_METADATA).is_some()
+        {
+            return;
+        }
+        let Some(store) = DEAD_LETTERS.get() else {
+            return;
+        };
+        let dead_letter = (task_request.clone(), task_response.task_error.clone());
+        if store.sender.send(dead_letter).is_err() {
+            println!(
+                "[TASK] Failed to keep task {} as a dead letter. Error: the writer stopped",
+                task_request.task_id
+            );
+        }
+    }
+}
+
+/// The payload with its secrets redacted, along with whether one was there.
+fn redact_payload(task_payload: &str, redact_fields: &[String]) -> (String, bool) {
+    let redacted = redact_text(task_payload, redact_fields);
+    // a payload that lost a secret can no longer be run as it was sent.
+    let changed = serde_json::from_str::<Value>(&redacted).ok()
+        != serde_json::from_str::<Value>(task_payload).ok();
+    (redacted, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_payload_tells_whether_a_secret_was_dropped() {
+        let redact_fields = vec![String::from("token"), String::from("stamp")];
+        assert_eq!(
+            redact_payload(r#"{"user_id": "1", "stamp": null}"#, &redact_fields),
+            (String::from(r#"{"stamp":null,"user_id":"1"}"#), false)
+        );
+        assert_eq!(
+            redact_payload(r#"{"token": "abc"}"#, &redact_fields),
+            (String::from(r#"{"token":"[REDACTED]"}"#), true)
+        );
+    }
+}
@@ -1,7 +1,20 @@
-use serde::Serialize;
+use std::str::FromStr;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{de::IntoDeserializer, Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+use crate::app::platform::response::ErrorJsonResponse;
+
+/// The errors a task can be thrown with.
+///
+/// The `Display` of each variant is its name, it travels inside of
+/// `TaskResponse.task_error` and is the stable `code` of the error sent
+/// to HTTP clients, so a variant must not be renamed.
+#[derive(Error, Clone, Debug, Deserialize)]
 pub enum TaskError {
     // Internal
     #[error("FailedToInterpretPayload")]
@@ -36,6 +49,28 @@ pub enum TaskError {
     TaskInternalError,
     #[error("FieldNotMutable")]
     FieldNotMutable,
+    #[error("Timeout")]
+    Timeout,
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("TransientFailure")]
+    TransientFailure,
+    #[error("TaskAbandoned")]
+    TaskAbandoned,
+    #[error("DeadLetterNotFound")]
+    DeadLetterNotFound,
+    #[error("DeadLettersDisabled")]
+    DeadLettersDisabled,
+    #[error("DeadLetterReplayUnsafe")]
+    DeadLetterReplayUnsafe,
+    #[error("DeadLetterRedacted")]
+    DeadLetterRedacted,
+    #[error("IdempotencyKeyInvalid")]
+    IdempotencyKeyInvalid,
+    #[error("IdempotencyKeyMismatch")]
+    IdempotencyKeyMismatch,
+    #[error("IdempotencyKeyInProgress")]
+    IdempotencyKeyInProgress,
     // User
     #[error("UserNotFound")]
     UserNotFound,
@@ -51,6 +86,8 @@ pub enum TaskError {
     UserFailedToPreload,
     #[error("UserFailedToCreateSecurityToken")]
     UserFailedToCreateSecurityToken,
+    #[error("UserFailedToClearSecurityTokens")]
+    UserFailedToClearSecurityTokens,
     #[error("UserOAuthIdNotFound")]
     UserOAuthIdNotFound,
     #[error("UserCannotUpdateId")]
@@ -65,7 +102,17 @@ pub enum TaskError {
     #[error("SessionDeletionFailed")]
     SessionDeletionFailed,
     #[error("SessionNotFound")]
-    SessionNotFound
+    SessionNotFound,
+    // Batch
+    #[error("BatchEmpty")]
+    BatchEmpty,
+    #[error("BatchTooLarge")]
+    BatchTooLarge,
+    #[error("BatchOutcomeUnknown")]
+    BatchOutcomeUnknown,
+    // Server
+    #[error("MaintenanceMode")]
+    MaintenanceMode,
 }
 
 pub type TaskResult<T> = Result<T, TaskError>;
@@ -78,3 +125,274 @@ impl Serialize for TaskError {
         serializer.serialize_str(self.to_string().as_ref())
     }
 }
+
+impl FromStr for TaskError {
+    type Err = serde::de::value::Error;
+
+    /// Parses the code of a `TaskError`, ex: `"UserNotFound"`.
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        TaskError::deserialize(code.into_deserializer())
+    }
+}
+
+impl TaskError {
+    /// The stable, machine-readable code of the error.
+    pub fn code(&self) -> String {
+        self.to_string()
+    }
+
+    /// A human readable description of the error.
+    pub fn message(&self) -> &'static str {
+        match self {
+            TaskError::FailedToInterpretPayload => "The task payload could not be interpreted.",
+            TaskError::FailedToFindAction => "The task action does not exist.",
+            TaskError::FailedToCompleteTask => "The task could not be completed.",
+            TaskError::PermissionDuplication => "The permission already exists.",
+            TaskError::PermissionFieldNotFound => "The permission field does not exist.",
+            TaskError::PermissionNotFound => "The permission does not exist.",
+            TaskError::PermissionFailedToPreload => "The permissions could not be preloaded.",
+            TaskError::PermissionLinkAlreadyExist => "The permission is already linked.",
+            TaskError::RoleDuplication => "The role already exists.",
+            TaskError::RoleFieldNotFound => "The role field does not exist.",
+            TaskError::RoleNotFound => "The role does not exist.",
+            TaskError::RoleFailedToPreload => "The roles could not be preloaded.",
+            TaskError::RoleLinkFailedToLink => "The permission could not be linked to the role.",
+            TaskError::TaskInternalError => "The task failed unexpectedly.",
+            TaskError::FieldNotMutable => "The field cannot be updated.",
+            TaskError::Timeout => "The task did not complete in time.",
+            TaskError::Cancelled => "The task was cancelled.",
+            TaskError::TransientFailure => "The database is unavailable, please try again later.",
+            TaskError::TaskAbandoned => "The task was given up on after too many deliveries.",
+            TaskError::DeadLetterNotFound => "The dead letter does not exist.",
+            TaskError::DeadLettersDisabled => "Dead letters are not kept by this server.",
+            TaskError::DeadLetterReplayUnsafe => "The task may have run already and is not idempotent, replay it with force=true to run it again.",
+            TaskError::DeadLetterRedacted => "The secrets of the task were not kept, it cannot be replayed.",
+            TaskError::IdempotencyKeyInvalid => "The idempotency key must be between 1 and 255 characters.",
+            TaskError::IdempotencyKeyMismatch => "The idempotency key was used for a different request.",
+            TaskError::IdempotencyKeyInProgress => "A request with the idempotency key is still running.",
+            TaskError::UserNotFound => "The user does not exist.",
+            TaskError::UserAlreadyExists => "The user already exists.",
+            TaskError::UserUpdateIncompatiableType => "The value does not match the type of the field.",
+            TaskError::UserFieldNotFound => "The user field does not exist.",
+            TaskError::UserUniqueConstraint => "The value is already used by another user.",
+            TaskError::UserFailedToPreload => "The users could not be preloaded.",
+            TaskError::UserFailedToCreateSecurityToken => "The security token could not be created.",
+            TaskError::UserFailedToClearSecurityTokens => "The expired security tokens could not be cleared.",
+            TaskError::UserOAuthIdNotFound => "No user is linked to the OAuth account.",
+            TaskError::UserCannotUpdateId => "The user id cannot be updated.",
+            TaskError::UserPermissionAlreadyExists => "The user already has the permission.",
+            TaskError::UserRoleAlreadyExists => "The user already has the role.",
+            TaskError::SessionCreationFailed => "The session could not be created.",
+            TaskError::SessionDeletionFailed => "The session could not be deleted.",
+            TaskError::SessionNotFound => "The session does not exist.",
+            TaskError::BatchEmpty => "The batch has no steps.",
+            TaskError::BatchTooLarge => "The batch has more steps than allowed.",
+            TaskError::BatchOutcomeUnknown => "The batch may have committed, check its changes before running it again.",
+            TaskError::MaintenanceMode => "The server is under maintenance, please try again later.",
+        }
+    }
+
+    /// The HTTP status code that best describes the error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            TaskError::PermissionNotFound
+            | TaskError::RoleNotFound
+            | TaskError::UserNotFound
+            | TaskError::UserOAuthIdNotFound
+            | TaskError::SessionNotFound
+            | TaskError::DeadLetterNotFound
+            | TaskError::DeadLettersDisabled => StatusCode::NOT_FOUND,
+            TaskError::PermissionDuplication
+            | TaskError::PermissionLinkAlreadyExist
+            | TaskError::RoleDuplication
+            | TaskError::RoleLinkFailedToLink
+            | TaskError::UserAlreadyExists
+            | TaskError::UserUniqueConstraint
+            | TaskError::UserPermissionAlreadyExists
+            | TaskError::UserRoleAlreadyExists
+            | TaskError::IdempotencyKeyInProgress
+            | TaskError::DeadLetterReplayUnsafe
+            | TaskError::DeadLetterRedacted => StatusCode::CONFLICT,
+            TaskError::PermissionFieldNotFound
+            | TaskError::RoleFieldNotFound
+            | TaskError::FieldNotMutable
+            | TaskError::UserUpdateIncompatiableType
+            | TaskError::UserFieldNotFound
+            | TaskError::UserCannotUpdateId
+            | TaskError::BatchEmpty
+            | TaskError::BatchTooLarge
+            | TaskError::IdempotencyKeyInvalid => StatusCode::BAD_REQUEST,
+            TaskError::IdempotencyKeyMismatch => StatusCode::UNPROCESSABLE_ENTITY,
+            TaskError::FailedToInterpretPayload
+            | TaskError::FailedToFindAction
+            | TaskError::FailedToCompleteTask
+            | TaskError::PermissionFailedToPreload
+            | TaskError::RoleFailedToPreload
+            | TaskError::TaskInternalError
+            | TaskError::TaskAbandoned
+            | TaskError::UserFailedToPreload
+            | TaskError::UserFailedToCreateSecurityToken
+            | TaskError::UserFailedToClearSecurityTokens
+            | TaskError::SessionCreationFailed
+            | TaskError::SessionDeletionFailed
+            | TaskError::BatchOutcomeUnknown => StatusCode::INTERNAL_SERVER_ERROR,
+            TaskError::MaintenanceMode => StatusCode::SERVICE_UNAVAILABLE,
+            TaskError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            TaskError::TransientFailure => StatusCode::SERVICE_UNAVAILABLE,
+            TaskError::Cancelled => StatusCode::CONFLICT,
+        }
+    }
+}
+
+impl From<TaskError> for ErrorJsonResponse {
+    fn from(error: TaskError) -> Self {
+        ErrorJsonResponse::new(error.status_code(), error.message()).with_code(&error.code())
+    }
+}
+
+impl IntoResponse for TaskError {
+    fn into_response(self) -> Response {
+        ErrorJsonResponse::from(self).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EVERY_VARIANT: &[TaskError] = &[
+        TaskError::FailedToInterpretPayload,
+        TaskError::FailedToFindAction,
+        TaskError::FailedToCompleteTask,
+        TaskError::PermissionDuplication,
+        TaskError::PermissionFieldNotFound,
+        TaskError::PermissionNotFound,
+        TaskError::PermissionFailedToPreload,
+        TaskError::PermissionLinkAlreadyExist,
+        TaskError::RoleDuplication,
+        TaskError::RoleFieldNotFound,
+        TaskError::RoleNotFound,
+        TaskError::RoleFailedToPreload,
+        TaskError::RoleLinkFailedToLink,
+        TaskError::TaskInternalError,
+        TaskError::FieldNotMutable,
+        TaskError::Timeout,
+        TaskError::Cancelled,
+        TaskError::TransientFailure,
+        TaskError::TaskAbandoned,
+        TaskError::DeadLetterNotFound,
+        TaskError::DeadLettersDisabled,
+        TaskError::DeadLetterReplayUnsafe,
+        TaskError::DeadLetterRedacted,
+        TaskError::IdempotencyKeyInvalid,
+        TaskError::IdempotencyKeyMismatch,
+        TaskError::IdempotencyKeyInProgress,
+        TaskError::UserNotFound,
+        TaskError::UserAlreadyExists,
+        TaskError::UserUpdateIncompatiableType,
+        TaskError::UserFieldNotFound,
+        TaskError::UserUniqueConstraint,
+        TaskError::UserFailedToPreload,
+        TaskError::UserFailedToCreateSecurityToken,
+        TaskError::UserFailedToClearSecurityTokens,
+        TaskError::UserOAuthIdNotFound,
+        TaskError::UserCannotUpdateId,
+        TaskError::UserPermissionAlreadyExists,
+        TaskError::UserRoleAlreadyExists,
+        TaskError::SessionCreationFailed,
+        TaskError::SessionDeletionFailed,
+        TaskError::SessionNotFound,
+        TaskError::BatchEmpty,
+        TaskError::BatchTooLarge,
+        TaskError::BatchOutcomeUnknown,
+        TaskError::MaintenanceMode,
+    ];
+
+    /// The code each variant must keep, no wildcard so a new variant does
+    /// not compile until it is listed here and inside of `EVERY_VARIANT`.
+    fn expected_code(error: &TaskError) -> &'static str {
+        match error {
+            TaskError::FailedToInterpretPayload => "FailedToInterpretPayload",
+            TaskError::FailedToFindAction => "FailedToFindAction",
+            TaskError::FailedToCompleteTask => "FailedToCompleteTask",
+            TaskError::PermissionDuplication => "PermissionDuplication",
+            TaskError::PermissionFieldNotFound => "PermissionFieldNotFound",
+            TaskError::PermissionNotFound => "PermissionNotFound",
+            TaskError::PermissionFailedToPreload => "PermissionFailedToPreload",
+            TaskError::PermissionLinkAlreadyExist => "PermissionLinkAlreadyExist",
+            TaskError::RoleDuplication => "RoleDuplication",
+            TaskError::RoleFieldNotFound => "RoleFieldNotFound",
+            TaskError::RoleNotFound => "RoleNotFound",
+            TaskError::RoleFailedToPreload => "RoleFailedToPreload",
+            TaskError::RoleLinkFailedToLink => "RoleLinkFailedToLink",
+            TaskError::TaskInternalError => "TaskInternalError",
+            TaskError::FieldNotMutable => "FieldNotMutable",
+            TaskError::Timeout => "Timeout",
+            TaskError::Cancelled => "Cancelled",
+            TaskError::TransientFailure => "TransientFailure",
+            TaskError::TaskAbandoned => "TaskAbandoned",
+            TaskError::DeadLetterNotFound => "DeadLetterNotFound",
+            TaskError::DeadLettersDisabled => "DeadLettersDisabled",
+            TaskError::DeadLetterReplayUnsafe => "DeadLetterReplayUnsafe",
+            TaskError::DeadLetterRedacted => "DeadLetterRedacted",
+            TaskError::IdempotencyKeyInvalid => "IdempotencyKeyInvalid",
+            TaskError::IdempotencyKeyMismatch => "IdempotencyKeyMismatch",
+            TaskError::IdempotencyKeyInProgress => "IdempotencyKeyInProgress",
+            TaskError::UserNotFound => "UserNotFound",
+            TaskError::UserAlreadyExists => "UserAlreadyExists",
+            TaskError::UserUpdateIncompatiableType => "UserUpdateIncompatiableType",
+            TaskError::UserFieldNotFound => "UserFieldNotFound",
+            TaskError::UserUniqueConstraint => "UserUniqueConstraint",
+            TaskError::UserFailedToPreload => "UserFailedToPreload",
+            TaskError::UserFailedToCreateSecurityToken => "UserFailedToCreateSecurityToken",
+            TaskError::UserFailedToClearSecurityTokens => "UserFailedToClearSecurityTokens",
+            TaskError::UserOAuthIdNotFound => "UserOAuthIdNotFound",
+            TaskError::UserCannotUpdateId => "UserCannotUpdateId",
+            TaskError::UserPermissionAlreadyExists => "UserPermissionAlreadyExists",
+            TaskError::UserRoleAlreadyExists => "UserRoleAlreadyExists",
+            TaskError::SessionCreationFailed => "SessionCreationFailed",
+            TaskError::SessionDeletionFailed => "SessionDeletionFailed",
+            TaskError::SessionNotFound => "SessionNotFound",
+            TaskError::BatchEmpty => "BatchEmpty",
+            TaskError::BatchTooLarge => "BatchTooLarge",
+            TaskError::BatchOutcomeUnknown => "BatchOutcomeUnknown",
+            TaskError::MaintenanceMode => "MaintenanceMode",
+        }
+    }
+
+    #[test]
+    fn every_variant_round_trips_through_its_code() {
+        for error in EVERY_VARIANT {
+            assert_eq!(error.to_string(), expected_code(error));
+            let parsed = TaskError::from_str(&error.to_string())
+                .unwrap_or_else(|_| panic!("{} does not parse back", error));
+            assert_eq!(parsed.to_string(), error.to_string());
+            assert_eq!(parsed.code(), error.code());
+        }
+    }
+
+    #[test]
+    fn every_variant_round_trips_through_json() {
+        for error in EVERY_VARIANT {
+            let json = serde_json::to_string(error).unwrap();
+            assert_eq!(json, format!("\"{}\"", error));
+            let parsed: TaskError = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.to_string(), error.to_string());
+        }
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let mut codes: Vec<String> = EVERY_VARIANT.iter().map(TaskError::code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), EVERY_VARIANT.len());
+    }
+
+    #[test]
+    fn unknown_codes_are_rejected() {
+        assert!(TaskError::from_str("").is_err());
+        assert!(TaskError::from_str("NotAnError").is_err());
+        assert!(TaskError::from_str("usernotfound").is_err());
+    }
+}
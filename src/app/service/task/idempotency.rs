@@ -0,0 +1,367 @@
+use std::{env, time::Duration};
+
+use bb8_redis::redis::{AsyncCommands, Cmd, RedisResult};
+use once_cell::sync::OnceCell;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::app::database::redis::RedisDatabase;
+
+use super::{
+    error::TaskError,
+    message::{TaskRequest, TaskResponse, TaskStatus},
+    stream::from_run_error,
+};
+
+/// Where the results of keyed requests are kept, set by `Idempotency::enable`.
+static IDEMPOTENCY: OnceCell<IdempotencyStore> = OnceCell::new();
+
+/// How long a claim outlives the deadline of its task, so a caller that
+/// stopped while running it does not hold the key for the whole window.
+static CLAIM_GRACE: Duration = Duration::from_secs(5);
+
+/// The longest key a caller may send.
+pub static MAX_KEY_LEN: usize = 255;
+
+/// The metadata naming who sent a task, keys of different actors never meet.
+pub static ACTOR_METADATA: &str = "actor";
+
+/// How long the result of a keyed request is replayed.
+///
+/// # Fields
+///
+/// * `window` - How long the first result is kept after it is stored.
+/// * `claim` - How long a key stays claimed by an http request that has not
+///   answered yet, tasks claim theirs until their deadline.
+pub struct IdempotencyConfig {
+    pub window: Duration,
+    pub claim: Duration,
+}
+
+impl Default for IdempotencyConfig {
+    /// Reads `TASK_IDEMPOTENCY_WINDOW_SECONDS` (default 86400) and
+    /// `TASK_IDEMPOTENCY_CLAIM_SECONDS` (default 60).
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(
+                Self::from_env("TASK_IDEMPOTENCY_WINDOW_SECONDS").unwrap_or(86_400),
+            ),
+            claim: Duration::from_secs(
+                Self::from_env("TASK_IDEMPOTENCY_CLAIM_SECONDS").unwrap_or(60),
+            ),
+        }
+    }
+}
+
+impl IdempotencyConfig {
+    fn from_env(key: &str) -> Option<u64> {
+        env::var(key).ok().and_then(|value| value.parse().ok())
+    }
+}
+
+/// What is kept under a key, `result` is empty while the first request runs.
+#[derive(Serialize, Deserialize)]
+struct IdempotencyRecord<T> {
+    fingerprint: String,
+    result: Option<T>,
+}
+
+/// A key claimed by this caller, the result of its request is stored under it.
+pub struct IdempotencyClaim {
+    redis_key: String,
+    fingerprint: String,
+}
+
+/// What to do with a keyed request.
+pub enum IdempotencyOutcome<T> {
+    /// The key is new, run the request then keep its result.
+    Run(IdempotencyClaim),
+    /// The key was used before, answer with its first result.
+    Replay(T),
+    /// The key cannot be used for this request.
+    Reject(TaskError),
+}
+
+struct IdempotencyStore {
+    redis: RedisDatabase,
+    config: IdempotencyConfig,
+}
+
+/// Replays the first result of a request sent again under the same key,
+/// ex: a client retrying `POST /iam/roles` after a network error gets the
+/// role it created instead of `RoleDuplication`.
+///
+/// Keys are scoped to who sent the request and to what it is, an http
+/// request or a `task_action`, and are compared on a fingerprint of what the
+/// caller sent. The http layer keeps whole responses, see
+/// `platform::idempotency`, and a `TaskRequest` sent with
+/// `with_idempotency_key` keeps its `TaskResponse`.
+pub struct Idempotency;
+
+impl Idempotency {
+    /// Starts honoring idempotency keys, only the first call is used.
+    ///
+    /// # Arguments
+    /// - `redis`: the `RedisDatabase` the results are kept in.
+    /// - `config`: how long results are replayed.
+    ///
+    /// # Examples
+    /// ```
+    /// Idempotency::enable(redis, IdempotencyConfig::default());
+    /// ```
+    pub fn enable(redis: RedisDatabase, config: IdempotencyConfig) {
+        let _ = IDEMPOTENCY.set(IdempotencyStore { redis, config });
+    }
+
+    /// Whether a key may be used, between 1 and `MAX_KEY_LEN` characters.
+    pub fn is_valid_key(key: &str) -> bool {
+        !key.is_empty() && key.len() <= MAX_KEY_LEN
+    }
+
+    /// Tells apart two requests sent under the same key.
+    ///
+    /// # Arguments
+    /// - `parts`: what the caller sent, ex: the method, path and body of an http request.
+    ///
+    /// # Examples
+    /// ```
+    /// let fingerprint = Idempotency::fingerprint(&[method.as_bytes(), path.as_bytes(), &body]);
+    /// ```
+    pub fn fingerprint(parts: &[&[u8]]) -> String {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            // the length keeps `ab` + `c` apart from `a` + `bc`.
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Claims a key, or finds what the key was used for. Returns `None`
+    /// before `Idempotency::enable`, the request then simply runs.
+    ///
+    /// # Arguments
+    /// - `scope`: who sent the request and what it is, ex: `http:{user_id}`.
+    /// - `key`: the key sent by the caller.
+    /// - `fingerprint`: the `Idempotency::fingerprint` of the request.
+    /// - `claim_for`: how long the key stays claimed, `IdempotencyConfig::claim` when `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// match Idempotency::claim::<HttpRecord>(&scope, &key, fingerprint, None).await {
+    ///     None => { /* run the request */ }
+    ///     Some(IdempotencyOutcome::Run(claim)) => { /* run then Idempotency::keep or release */ }
+    ///     Some(IdempotencyOutcome::Replay(record)) => { /* answer with record */ }
+    ///     Some(IdempotencyOutcome::Reject(error)) => { /* fail with error */ }
+    /// }
+    /// ```
+    pub async fn claim<T: DeserializeOwned>(
+        scope: &str,
+        key: &str,
+        fingerprint: String,
+        claim_for: Option<Duration>,
+    ) -> Option<IdempotencyOutcome<T>> {
+        let store = IDEMPOTENCY.get()?;
+        if !Self::is_valid_key(key) {
+            return Some(IdempotencyOutcome::Reject(TaskError::IdempotencyKeyInvalid));
+        }
+        let claim = IdempotencyClaim {
+            redis_key: format!("ark:idempotency:{}:{}", scope, key),
+            fingerprint,
+        };
+        let pending = serde_json::to_string(&IdempotencyRecord::<()> {
+            fingerprint: claim.fingerprint.clone(),
+            result: None,
+        })
+        .unwrap();
+        let claim_for = claim_for.unwrap_or(store.config.claim);
+        let result: RedisResult<Option<String>> = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            let claimed: Option<String> = Cmd::new()
+                .arg("SET")
+                .arg(&claim.redis_key)
+                .arg(&pending)
+                .arg("NX")
+                .arg("PX")
+                .arg(claim_for.as_millis() as u64)
+                .query_async(&mut *connection)
+                .await?;
+            match claimed {
+                Some(_) => Ok(None),
+                None => connection.get(&claim.redis_key).await,
+            }
+        }
+        .await;
+        let outcome = match result {
+            Ok(None) => IdempotencyOutcome::Run(claim),
+            Ok(Some(record)) => match serde_json::from_str::<IdempotencyRecord<T>>(&record) {
+                Ok(record) if record.fingerprint.ne(&claim.fingerprint) => {
+                    IdempotencyOutcome::Reject(TaskError::IdempotencyKeyMismatch)
+                }
+                Ok(IdempotencyRecord {
+                    result: Some(result),
+                    ..
+                }) => {
+                    println!(
+                        "[TASK] Replaying the first result of idempotency key {}.",
+                        key
+                    );
+                    IdempotencyOutcome::Replay(result)
+                }
+                _ => IdempotencyOutcome::Reject(TaskError::IdempotencyKeyInProgress),
+            },
+            Err(error) => {
+                println!(
+                    "[TASK] Failed to claim idempotency key {}. Error: {}",
+                    key, error
+                );
+                IdempotencyOutcome::Reject(TaskError::TransientFailure)
+            }
+        };
+        Some(outcome)
+    }
+
+    /// Keeps the result of a claimed request for `IdempotencyConfig::window`.
+    ///
+    /// # Arguments
+    /// - `claim`: the claim returned by `Idempotency::claim`.
+    /// - `result`: what the request is answered with.
+    pub async fn keep<T: Serialize>(claim: IdempotencyClaim, result: &T) {
+        let Some(store) = IDEMPOTENCY.get() else {
+            return;
+        };
+        let record = serde_json::to_string(&IdempotencyRecord {
+            fingerprint: claim.fingerprint,
+            result: Some(result),
+        })
+        .unwrap();
+        let outcome: RedisResult<()> = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            connection
+                .pset_ex(
+                    &claim.redis_key,
+                    record,
+                    store.config.window.as_millis() as u64,
+                )
+                .await
+        }
+        .await;
+        if let Err(error) = outcome {
+            println!(
+                "[TASK] Failed to keep the result of idempotency key {}. Error: {}",
+                claim.redis_key, error
+            );
+        }
+    }
+
+    /// Gives a claimed key back without keeping a result, so the request can
+    /// be retried under it, ex: after a server side failure.
+    ///
+    /// # Arguments
+    /// - `claim`: the claim returned by `Idempotency::claim`.
+    pub async fn release(claim: IdempotencyClaim) {
+        let Some(store) = IDEMPOTENCY.get() else {
+            return;
+        };
+        let outcome: RedisResult<()> = async {
+            let mut connection = store.redis.pool.get().await.map_err(from_run_error)?;
+            connection.del(&claim.redis_key).await
+        }
+        .await;
+        if let Err(error) = outcome {
+            println!(
+                "[TASK] Failed to release idempotency key {}. Error: {}",
+                claim.redis_key, error
+            );
+        }
+    }
+
+    /// Claims the key of a `TaskRequest` sent with `with_idempotency_key`,
+    /// scoped to its `actor` metadata and `task_action`. Requests without a
+    /// key, or sent before `Idempotency::enable`, always run.
+    ///
+    /// # Arguments
+    /// - `task_request`: the request about to be sent.
+    ///
+    /// # Examples
+    /// ```
+    /// match Idempotency::begin(&task_request).await {
+    ///     Some(IdempotencyOutcome::Run(claim)) => { /* send then Idempotency::finish */ }
+    ///     Some(IdempotencyOutcome::Replay(task_response)) => task_response,
+    ///     Some(IdempotencyOutcome::Reject(error)) => { /* fail with error */ }
+    ///     None => { /* send */ }
+    /// }
+    /// ```
+    pub async fn begin(task_request: &TaskRequest) -> Option<IdempotencyOutcome<TaskResponse>> {
+        let key = task_request.task_idempotency_key.as_ref()?;
+        let scope = format!(
+            "task:{}:{}",
+            task_request.metadata(ACTOR_METADATA).unwrap_or_default(),
+            task_request.task_action
+        );
+        let fingerprint = Self::fingerprint(&[
+            task_request.task_action.as_bytes(),
+            task_request.task_payload.as_bytes(),
+        ]);
+        Self::claim(
+            &scope,
+            key,
+            fingerprint,
+            Some(task_request.timeout() + CLAIM_GRACE),
+        )
+        .await
+    }
+
+    /// Keeps the response of a claimed task, or gives the key back when a
+    /// retry could change it.
+    ///
+    /// # Arguments
+    /// - `claim`: the claim returned by `Idempotency::begin`.
+    /// - `task_response`: the response of the task.
+    pub async fn finish(claim: IdempotencyClaim, task_response: &TaskResponse) {
+        match Self::is_kept(task_response) {
+            true => Self::keep(claim, task_response).await,
+            false => Self::release(claim).await,
+        }
+    }
+
+    /// Whether a response is replayed, those a retry could change are not.
+    fn is_kept(task_response: &TaskResponse) -> bool {
+        match task_response.task_status {
+            TaskStatus::Completed => true,
+            TaskStatus::Failed => {
+                let error = TaskResponse::intepret_response_error(task_response);
+                error.status_code().is_client_error() && !matches!(error, TaskError::Cancelled)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprints_tell_apart_where_the_parts_split() {
+        let fingerprint = |parts: &[&str]| {
+            Idempotency::fingerprint(&parts.iter().map(|part| part.as_bytes()).collect::<Vec<_>>())
+        };
+        assert_ne!(fingerprint(&["ab", "c"]), fingerprint(&["a", "bc"]));
+        assert_eq!(
+            fingerprint(&["POST", "/iam/roles", "{}"]),
+            fingerprint(&["POST", "/iam/roles", "{}"])
+        );
+        assert_ne!(
+            fingerprint(&["POST", "/iam/roles", "{}"]),
+            fingerprint(&["PUT", "/iam/roles", "{}"])
+        );
+    }
+
+    #[test]
+    fn keys_are_between_one_and_max_len_characters() {
+        assert!(Idempotency::is_valid_key("5f0c2a3e"));
+        assert!(Idempotency::is_valid_key(&"k".repeat(MAX_KEY_LEN)));
+        assert!(!Idempotency::is_valid_key(""));
+        assert!(!Idempotency::is_valid_key(&"k".repeat(MAX_KEY_LEN + 1)));
+    }
+}
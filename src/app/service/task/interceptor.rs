@@ -0,0 +1,81 @@
+use std::sync::{Arc, RwLock};
+
+use axum::async_trait;
+use once_cell::sync::Lazy;
+
+use super::{
+    error::TaskError,
+    message::{TaskRequest, TaskResponse},
+};
+
+/// Every interceptor added through `TaskManager::intercept`, in the order they were added.
+static INTERCEPTORS: Lazy<RwLock<Vec<Arc<dyn TaskInterceptor>>>> =
+    Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Hooks that wrap every task the `TaskManager` runs, ex: tracing, audit
+/// records, authorization checks or fault injection.
+///
+/// `before` runs in the order the interceptors were added and `after` in the
+/// reverse order. `after` runs for every interceptor once the task has a
+/// response, including when a `before` refused it, maintenance refused it, it
+/// timed out or it was cancelled. A retried task is wrapped once, not once
+/// per attempt.
+///
+/// Interceptors run in the process that runs the task. Under a `TaskTransport`
+/// the interceptors of an API node never see the types it sends to workers,
+/// so add them to the `ark-worker` as well.
+///
+/// # Examples
+/// ```
+/// struct AuditInterceptor;
+///
+/// #[async_trait]
+/// impl TaskInterceptor for AuditInterceptor {
+///     async fn after(&self, task_request: &TaskRequest, task_response: &TaskResponse) {
+///         println!("[AUDIT] {} by {:?}: {:?}", task_request.task_action, task_request.metadata("actor"), task_response.task_status);
+///     }
+/// }
+///
+/// TaskManager::intercept(AuditInterceptor);
+/// ```
+#[async_trait]
+pub trait TaskInterceptor: Send + Sync {
+    /// Runs before the task, an error refuses the task which then fails
+    /// with that error.
+    async fn before(&self, _task_request: &TaskRequest) -> Result<(), TaskError> {
+        Ok(())
+    }
+
+    /// Runs once the task has a response.
+    async fn after(&self, _task_request: &TaskRequest, _task_response: &TaskResponse) {}
+}
+
+/// Adds an interceptor to the end of the chain.
+pub(super) fn add(interceptor: Arc<dyn TaskInterceptor>) {
+    INTERCEPTORS.write().unwrap().push(interceptor);
+}
+
+/// Runs every `before`, stopping at the first one that refuses the task.
+pub(super) async fn run_before(task_request: &TaskRequest) -> Result<(), TaskError> {
+    for interceptor in chain() {
+        interceptor.before(task_request).await?;
+    }
+    Ok(())
+}
+
+/// Runs every `after`, the last interceptor added first.
+pub(super) async fn run_after(task_request: &TaskRequest, task_response: &TaskResponse) {
+    for interceptor in chain().iter().rev() {
+        interceptor.after(task_request, task_response).await;
+    }
+}
+
+/// Whether any interceptor was added, which spares cloning every request.
+pub(super) fn is_empty() -> bool {
+    INTERCEPTORS.read().unwrap().is_empty()
+}
+
+/// The chain is copied so no lock is held while the hooks run.
+fn chain() -> Vec<Arc<dyn TaskInterceptor>> {
+    INTERCEPTORS.read().unwrap().clone()
+}
@@ -2,32 +2,120 @@
 // because you can't send a task_request within another task_request
 // so instead of sending a task request, we're sending a cache_request.
 
+use std::{
+    collections::HashMap,
+    env, future,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use serde::{Deserialize, Serialize};
-use tokio::task::{self, JoinHandle};
+use tokio::sync::{
+    mpsc,
+    oneshot::{self, Receiver, Sender},
+    Semaphore,
+};
 
 use crate::app::{
     database::{postgres::PostgresDatabase, redis::RedisDatabase},
-    platform::iam::{permission::task::PermissionTaskHandler, role::task::RoleTaskHandler, session::task::SessionTaskHandler, user::task::UserTaskHandler},
-    service::task::{
-        message::{TaskStatus, TaskType},
-        TaskHandler,
+    platform::maintenance::Maintenance,
+    service::{
+        metrics::Metrics,
+        task::{
+            message::{TaskStatus, TaskType},
+            registry::{TaskContext, TaskRegistry},
+        },
     },
 };
 
 use super::{
+    error::{TaskError, TaskResult},
+    idempotency::{Idempotency, IdempotencyOutcome},
+    interceptor::{self, TaskInterceptor},
     message::{TaskRequest, TaskResponse},
-    INBOUND, OUTBOUND, error::{TaskError, TaskResult},
+    transport::TaskTransport,
+    CANCELLATIONS, INBOUND, LISTENER, TRANSPORT,
 };
 
+/// How many tasks the `TaskManager` takes in and runs at once.
+///
+/// # Fields
+///
+/// * `inbound_capacity` - How many requests `INBOUND` holds before senders wait.
+/// * `max_concurrency` - How many tasks run at the same time, across every `TaskType`.
+/// * `type_concurrency` - How many tasks of a `TaskType` run at the same time, unlimited
+///   (besides `max_concurrency`) when the type is missing.
+pub struct TaskManagerConfig {
+    pub inbound_capacity: usize,
+    pub max_concurrency: usize,
+    pub type_concurrency: HashMap<TaskType, usize>,
+}
+
+impl Default for TaskManagerConfig {
+    /// Reads `TASK_INBOUND_CAPACITY` (default 1024), `TASK_MAX_CONCURRENCY`
+    /// (default 32) and the optional `TASK_{TYPE}_CONCURRENCY` ex: `TASK_USER_CONCURRENCY`.
+    fn default() -> Self {
+        let mut config = Self::new(
+            Self::from_env("TASK_INBOUND_CAPACITY").unwrap_or(1024),
+            Self::from_env("TASK_MAX_CONCURRENCY").unwrap_or(32),
+        );
+        for (task_type, key) in [
+            (TaskType::Permission, "TASK_PERMISSION_CONCURRENCY"),
+            (TaskType::Role, "TASK_ROLE_CONCURRENCY"),
+            (TaskType::User, "TASK_USER_CONCURRENCY"),
+            (TaskType::Session, "TASK_SESSION_CONCURRENCY"),
+            (TaskType::Batch, "TASK_BATCH_CONCURRENCY"),
+        ] {
+            if let Some(limit) = Self::from_env(key) {
+                config = config.type_concurrency(task_type, limit);
+            }
+        }
+        config
+    }
+}
+
+impl TaskManagerConfig {
+    pub fn new(inbound_capacity: usize, max_concurrency: usize) -> Self {
+        Self {
+            inbound_capacity: inbound_capacity.max(1),
+            max_concurrency: max_concurrency.max(1),
+            type_concurrency: HashMap::new(),
+        }
+    }
+
+    /// Limits how many tasks of a `TaskType` run at the same time.
+    ///
+    /// # Arguments
+    /// - `task_type`: the type to limit.
+    /// - `limit`: the amount of tasks of that type allowed to run at once.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskManagerConfig::new(1024, 32).type_concurrency(TaskType::User, 8);
+    /// ```
+    pub fn type_concurrency(mut self, task_type: TaskType, limit: usize) -> Self {
+        self.type_concurrency.insert(task_type, limit.max(1));
+        self
+    }
+
+    fn from_env<T: FromStr>(key: &str) -> Option<T> {
+        env::var(key).ok().and_then(|value| value.parse::<T>().ok())
+    }
+}
+
 /// A structure for handling tasks within the system.
 pub struct TaskManager {
-    pg: PostgresDatabase,
-    redis: RedisDatabase
+    context: TaskContext,
+    config: TaskManagerConfig,
 }
 
 impl TaskManager {
-    pub fn new(pg: PostgresDatabase, redis: RedisDatabase) -> Self {
-        Self { pg, redis }
+    pub fn new(pg: PostgresDatabase, redis: RedisDatabase, config: TaskManagerConfig) -> Self {
+        Self {
+            context: TaskContext { pg, redis },
+            config,
+        }
     }
 
     /// Starts the listening process for task requests.
@@ -38,27 +126,47 @@ impl TaskManager {
     /// self.listen();
     /// ```
     pub fn listen(self) {
-        let pg_clone = self.pg.clone();
-        let redis_clone = self.redis.clone();
-        Self::initialize_listener(pg_clone, redis_clone);
+        let (inbound_sender, inbound_receiver) = mpsc::channel(self.config.inbound_capacity);
+        // only the first listener is used.
+        if INBOUND.set(inbound_sender).is_err() {
+            return;
+        }
+        self.initialize_listener(inbound_receiver);
     }
 
-    /// Sends a task request and waits for its completion.
+    /// Adds an interceptor to the end of the chain that wraps every task this
+    /// process runs, interceptors are added before `TaskManager::listen`.
     ///
     /// # Arguments
-    /// - `task_request`: The `TaskRequest` object representing the task to be sent and processed.
+    /// - `interceptor`: The `TaskInterceptor` to add.
     ///
     /// # Examples
     /// ```
-    /// // Assuming `task_request` is a valid TaskRequest object
-    /// let task_response = send(task_request);
+    /// TaskManager::intercept(AuditInterceptor);
+    /// ```
+    pub fn intercept<I: TaskInterceptor + 'static>(interceptor: I) {
+        interceptor::add(Arc::new(interceptor));
+    }
+
+    /// Sends every request the transport handles through it instead of `INBOUND`,
+    /// only the first transport is used.
+    ///
+    /// # Arguments
+    /// - `transport`: The `TaskTransport` to send requests through.
+    ///
+    /// # Examples
+    /// ```
+    /// let transport = RedisStreamTransport::new(redis, RedisStreamConfig::default());
+    /// transport.listen();
+    /// TaskManager::use_transport(transport);
     /// ```
-    fn send(task_request: TaskRequest) -> TaskResponse {
-        Self::send_task_request(&task_request);
-        Self::wait_for_task_completion(&task_request)
+    pub fn use_transport<T: TaskTransport + 'static>(transport: T) {
+        let _ = TRANSPORT.set(Box::new(transport));
     }
 
-    /// Sends a task request and waits for its completion.
+    /// Sends a task request and waits for its completion without blocking
+    /// the runtime. A request sent with `with_idempotency_key` is answered
+    /// with the first result of its key.
     ///
     /// # Arguments
     /// - `task_request`: The `TaskRequest` object representing the task to be sent and processed.
@@ -66,14 +174,119 @@ impl TaskManager {
     /// # Examples
     /// ```
     /// // Assuming `task_request` is a valid TaskRequest object
-    /// let task_response = send_async(task_request).await;
+    /// let task_response = Self::send(task_request).await;
     /// ```
-    fn send_async(task_request: TaskRequest) -> JoinHandle<TaskResponse> {
-        let task_handle = task::spawn(async move {
-            Self::send_task_request(&task_request);
-            Self::wait_for_async_task_completion(&task_request).await.unwrap()
-        });
-        task_handle
+    async fn send(task_request: TaskRequest) -> TaskResponse {
+        let claim = match Idempotency::begin(&task_request).await {
+            None => None,
+            Some(IdempotencyOutcome::Run(claim)) => Some(claim),
+            Some(IdempotencyOutcome::Replay(task_response)) => return task_response,
+            Some(IdempotencyOutcome::Reject(error)) => {
+                return Self::failed_response(&task_request.task_id, error)
+            }
+        };
+        let task_response = Self::send_through(
+            task_request,
+            TRANSPORT.get().map(|transport| transport.as_ref()),
+        )
+        .await;
+        if let Some(claim) = claim {
+            Idempotency::finish(claim, &task_response).await;
+        }
+        task_response
+    }
+
+    /// Runs a task request on the workers of this process, even when it would
+    /// otherwise go through the transport. Used by workers taking requests
+    /// out of a `TaskTransport`.
+    ///
+    /// # Arguments
+    /// - `task_request`: The `TaskRequest` to run.
+    ///
+    /// # Examples
+    /// ```
+    /// let task_response = TaskManager::execute_locally(task_request).await;
+    /// ```
+    pub async fn execute_locally(task_request: TaskRequest) -> TaskResponse {
+        Self::send_through(task_request, None).await
+    }
+
+    async fn send_through(
+        mut task_request: TaskRequest,
+        transport: Option<&dyn TaskTransport>,
+    ) -> TaskResponse {
+        let started = Instant::now();
+        let task_type = task_request.task_type.clone();
+        let task_action = task_request.task_action.clone();
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        let (cancel_sender, cancel_receiver) = oneshot::channel();
+        task_request.task_reply = Some(reply_sender);
+        task_request.task_cancel = Some(cancel_receiver);
+        let task_id = task_request.task_id.clone();
+        let timeout = task_request.timeout();
+        CANCELLATIONS
+            .lock()
+            .unwrap()
+            .insert(task_id.clone(), cancel_sender);
+        // a request sent through the transport cannot be cancelled from here, so
+        // only its caller listens for the cancel.
+        let sent = match transport {
+            Some(transport) if transport.handles(&task_request) => {
+                let task_cancel = task_request.task_cancel.take();
+                transport.send(task_request).await;
+                Ok(task_cancel)
+            }
+            _ => Self::send_task_request(task_request, timeout)
+                .await
+                .map(|_| None),
+        };
+        let task_response = match sent {
+            // the time spent waiting for room inside of `INBOUND` counts against the deadline.
+            Ok(task_cancel) => {
+                Self::wait_for_task_completion(
+                    &task_id,
+                    timeout.saturating_sub(started.elapsed()),
+                    reply_receiver,
+                    task_cancel,
+                )
+                .await
+            }
+            Err(error) => {
+                CANCELLATIONS.lock().unwrap().remove(&task_id);
+                let task_response = Self::failed_response(&task_id, error);
+                Self::log_task_outcome(&task_response);
+                task_response
+            }
+        };
+        Metrics::observe_task(
+            &task_type,
+            &task_action,
+            &task_response.task_status,
+            started.elapsed(),
+        );
+        task_response
+    }
+
+    /// Cancels an outstanding task. A task waiting in `INBOUND` never runs,
+    /// a running task is dropped which rolls back its open transaction. A task
+    /// sent through a `TaskTransport` keeps running, its caller stops waiting.
+    /// The caller of the task receives `TaskError::Cancelled`.
+    ///
+    /// # Arguments
+    /// - `task_id`: The id of the `TaskRequest` to cancel.
+    ///
+    /// # Examples
+    /// ```
+    /// let request = UserPreloadCache {}.into_request();
+    /// let task_id = request.task_id.clone();
+    /// tokio::spawn(TaskManager::process_task(request));
+    /// TaskManager::cancel(&task_id);
+    /// ```
+    pub fn cancel(task_id: &str) -> bool {
+        match CANCELLATIONS.lock().unwrap().remove(task_id) {
+            Some(cancel_sender) => cancel_sender.send(()).is_ok(),
+            None => false,
+        }
     }
 
     /// Process task.
@@ -84,13 +297,13 @@ impl TaskManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// Self::process_permission_task(request)
+    /// TaskManager::process_task(request).await
     /// ```
-    pub fn process_task(request: TaskRequest) -> TaskResult<TaskStatus> {
-        let task_response = Self::send(request);
+    pub async fn process_task(request: TaskRequest) -> TaskResult<TaskStatus> {
+        let task_response = Self::send(request).await;
         match task_response.task_status {
             TaskStatus::Completed => Ok(TaskStatus::Completed),
-            TaskStatus::Failed => Err(TaskError::FailedToCompleteTask),
+            TaskStatus::Failed => Err(TaskResponse::intepret_response_error(&task_response)),
         }
     }
 
@@ -102,162 +315,325 @@ impl TaskManager {
     /// # Examples
     /// ```
     /// // Assuming `permission` is a reference to a valid Permission
-    /// Self::process_task_with_result(request) -> TaskResult<T>
+    /// TaskManager::process_task_with_result::<T>(request).await -> TaskResult<T>
     /// ```
-    pub fn process_task_with_result<T: for<'a> Deserialize<'a> + Serialize>(request: TaskRequest) -> TaskResult<T> {
-        let task_response = Self::send(request);
+    pub async fn process_task_with_result<T: for<'a> Deserialize<'a> + Serialize>(request: TaskRequest) -> TaskResult<T> {
+        let task_response = Self::send(request).await;
         match task_response.task_status {
             TaskStatus::Completed => {
-                let response = TaskResponse::intepret_response_result::<T>(&task_response);
-                response
+                TaskResponse::intepret_response_result::<T>(&task_response)
+            },
+            TaskStatus::Failed => Err(TaskResponse::intepret_response_error(&task_response)),
+        }
+    }
+
+    /// Process task and return its result even when it failed, for tasks
+    /// that report what went wrong inside of their result. Fails with the
+    /// error of the task when it has no result.
+    ///
+    /// # Arguments
+    /// - `request`: A reference to the `TaskRequest` to process.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskManager::process_task_with_report::<BatchReport>(request).await -> TaskResult<BatchReport>
+    /// ```
+    pub async fn process_task_with_report<T: for<'a> Deserialize<'a> + Serialize>(request: TaskRequest) -> TaskResult<T> {
+        let task_response = Self::send(request).await;
+        match task_response.task_status {
+            TaskStatus::Failed if task_response.task_result.is_empty() => {
+                Err(TaskResponse::intepret_response_error(&task_response))
             },
-            TaskStatus::Failed => Err(TaskError::FailedToCompleteTask),
+            _ => TaskResponse::intepret_response_result::<T>(&task_response),
         }
     }
 
     /// Initializes and starts the task listener.
     ///
+    /// Every request is handled on its own tokio task. At most `max_concurrency`
+    /// run at once; while they are all busy the listener stops taking requests
+    /// out of `INBOUND`, which makes senders wait once it is full. A request
+    /// whose type is at its `type_concurrency` limit waits for it without
+    /// holding a worker, so it never holds back requests of other types.
+    ///
     /// # Arguments
-    /// - `pg_clone`: A cloned instance of `PostgresDatabase` used for handling database operations within tasks.
+    /// - `inbound_receiver`: The receiving end of `INBOUND`.
     ///
     /// # Examples
     /// ```
-    /// // Assume `pg_clone` is a cloned instance of PostgresDatabase
-    /// self.initialize_listener(pg_clone);
+    /// self.initialize_listener(inbound_receiver);
     /// ```
-    fn initialize_listener(pg_clone: PostgresDatabase, redis_clone: RedisDatabase) {
-        tokio::spawn(async move {
-            let inbound_receiver = &INBOUND.1;
-            println!("[ARK] Task initialized, now listening to incoming requests.");
-            while let Ok(task_request) = inbound_receiver.recv() {
-                Self::process_incoming_request(&pg_clone, &redis_clone, task_request).await;
+    fn initialize_listener(self, mut inbound_receiver: mpsc::Receiver<TaskRequest>) {
+        let workers = Arc::new(Semaphore::new(self.config.max_concurrency));
+        let type_limits: HashMap<TaskType, Arc<Semaphore>> = self
+            .config
+            .type_concurrency
+            .iter()
+            .map(|(task_type, limit)| (task_type.clone(), Arc::new(Semaphore::new(*limit))))
+            .collect();
+        let listener = tokio::spawn(async move {
+            println!(
+                "[ARK] Task initialized with {} worker(s), now listening to incoming requests.",
+                self.config.max_concurrency
+            );
+            while let Some(task_request) = inbound_receiver.recv().await {
+                let worker = workers.clone().acquire_owned().await.unwrap();
+                let type_limit = type_limits.get(&task_request.task_type).cloned();
+                let context = self.context.clone();
+                let workers = workers.clone();
+                tokio::spawn(async move {
+                    let (_worker, _type_permit) = match type_limit {
+                        None => (worker, None),
+                        Some(type_limit) => match type_limit.clone().try_acquire_owned() {
+                            Ok(type_permit) => (worker, Some(type_permit)),
+                            // the type is at its limit, give the worker back while
+                            // waiting so requests of other types keep running.
+                            Err(_) => {
+                                drop(worker);
+                                let type_permit = type_limit.acquire_owned().await.unwrap();
+                                let worker = workers.acquire_owned().await.unwrap();
+                                (worker, Some(type_permit))
+                            }
+                        },
+                    };
+                    Self::process_incoming_request(&context, task_request).await;
+                });
             }
         });
+        let _ = LISTENER.set(listener);
+    }
+
+    /// Whether the task listener loop is still running.
+    ///
+    /// # Examples
+    /// ```
+    /// if !TaskManager::is_listening() { /* not ready */ }
+    /// ```
+    pub fn is_listening() -> bool {
+        LISTENER
+            .get()
+            .is_some_and(|listener| !listener.is_finished())
+    }
+
+    /// How many requests are waiting inside of `INBOUND`.
+    ///
+    /// # Examples
+    /// ```
+    /// let queue_depth = TaskManager::queue_depth();
+    /// ```
+    pub fn queue_depth() -> usize {
+        INBOUND
+            .get()
+            .map_or(0, |inbound| inbound.max_capacity() - inbound.capacity())
     }
 
     /// Processes an incoming task request.
     ///
     /// # Arguments
-    /// - `pg_clone`: A reference to a cloned `PostgresDatabase` used for database operations.
+    /// - `context`: The `TaskContext` holding the databases tasks run against.
     /// - `task_request`: The `TaskRequest` object representing the received task.
     ///
     /// # Examples
     /// ```
-    /// // Assume `pg_clone` is a reference to a PostgresDatabase and `task_request` is a valid TaskRequest
-    /// self.process_incoming_request(&pg_clone, task_request).await;
+    /// // Assume `context` is a TaskContext and `task_request` is a valid TaskRequest
+    /// Self::process_incoming_request(&context, task_request).await;
     /// ```
-    async fn process_incoming_request(
-        pg_clone: &PostgresDatabase,
-        redis_clone: &RedisDatabase,
-        task_request: TaskRequest,
-    ) {
+    async fn process_incoming_request(context: &TaskContext, task_request: TaskRequest) {
         println!(
             "[TASK] Successfully received a task from {}. Task type: {:?}.",
             task_request.task_id, task_request.task_type
         );
-        Self::handle_task_request(pg_clone, redis_clone, task_request).await;
+        Self::handle_task_request(context, task_request).await;
     }
 
-    /// Handles a given task request based on its type.
+    /// Whether a task writes to the database, those are refused while in maintenance.
+    /// Tasks marked `read_only` and sessions, which live in Redis so admins
+    /// can still sign in, are never refused.
     ///
     /// # Arguments
-    /// - `pg`: A reference to the `PostgresDatabase` used for database operations.
+    /// - `task_request`: The `TaskRequest` to check.
+    ///
+    /// # Examples
+    /// ```
+    /// Self::is_mutating(&task_request);
+    /// ```
+    fn is_mutating(task_request: &TaskRequest) -> bool {
+        !(matches!(task_request.task_type, TaskType::Session)
+            || TaskRegistry::is_read_only(&task_request.task_action))
+    }
+
+    /// Handles a given task request through the task registered under its action,
+    /// wrapped in the `TaskInterceptor` chain. A mutating task is refused
+    /// with `MaintenanceMode` inside of the chain, so interceptors see it.
+    ///
+    /// # Arguments
+    /// - `context`: The `TaskContext` holding the databases tasks run against.
     /// - `task_request`: The `TaskRequest` object containing details about the task to be handled.
     ///
     /// # Examples
     /// ```
-    /// // Assume `pg` is a reference to a PostgresDatabase and `task_request` is a valid TaskRequest
-    /// self.handle_task_request(&pg, task_request).await;
+    /// // Assume `context` is a TaskContext and `task_request` is a valid TaskRequest
+    /// Self::handle_task_request(&context, task_request).await;
     /// ```
-    async fn handle_task_request(pg: &PostgresDatabase, redis: &RedisDatabase, task_request: TaskRequest) {
-        match task_request.task_type {
-            TaskType::Permission => {
-                let task_response = PermissionTaskHandler::handle(pg, task_request).await;
-                Self::send_task_response(task_response);
+    async fn handle_task_request(context: &TaskContext, mut task_request: TaskRequest) {
+        let task_reply = task_request.task_reply.take();
+        let task_cancel = task_request.task_cancel.take();
+        let task_id = task_request.task_id.clone();
+        let timeout = task_request.timeout();
+        let intercepted = match interceptor::is_empty() {
+            true => None,
+            false => Some(task_request.clone()),
+        };
+        let run_task = async {
+            if let Err(error) = interceptor::run_before(&task_request).await {
+                return Self::failed_response(&task_id, error);
             }
-            TaskType::Role => {
-                let task_response = RoleTaskHandler::handle(pg, task_request).await;
-                Self::send_task_response(task_response);
-            },
-            TaskType::User => {
-                let task_response = UserTaskHandler::handle(pg, task_request).await;
-                Self::send_task_response(task_response)
-            },
-            TaskType::Session => {
-                let task_response = SessionTaskHandler::handle(redis, task_request).await;
-                Self::send_task_response(task_response)
-                // do session...
-                // when session is executed should return the session token and expiration....
-            },
+            if Maintenance::is_enabled() && Self::is_mutating(&task_request) {
+                return Self::failed_response(&task_id, TaskError::MaintenanceMode);
+            }
+            TaskRegistry::dispatch(context, task_request).await
+        };
+        let task_response = tokio::select! {
+            biased;
+            _ = Self::wait_for_cancel(task_cancel) => {
+                Self::failed_response(&task_id, TaskError::Cancelled)
+            }
+            task_response = tokio::time::timeout(timeout, run_task) => {
+                task_response.unwrap_or_else(|_| Self::failed_response(&task_id, TaskError::Timeout))
+            }
+        };
+        if let Some(task_request) = intercepted {
+            interceptor::run_after(&task_request, &task_response).await;
         }
+        CANCELLATIONS.lock().unwrap().remove(&task_id);
+        Self::send_task_response(task_reply, task_response);
     }
 
-    /// Sends a task response to the outbound channel.
+    /// Resolves once the task is cancelled, never when it cannot be.
     ///
     /// # Arguments
-    /// - `task_response`: The `TaskResponse` object that encapsulates the result or outcome of a task.
+    /// - `task_cancel`: The cancel channel taken out of the `TaskRequest`.
     ///
     /// # Examples
     /// ```
-    /// // Assuming `task_response` is a valid TaskResponse object
-    /// send_task_response(task_response);
+    /// Self::wait_for_cancel(task_request.task_cancel.take()).await;
+    /// ```
+    async fn wait_for_cancel(task_cancel: Option<Receiver<()>>) {
+        if let Some(task_cancel) = task_cancel {
+            if task_cancel.await.is_ok() {
+                return;
+            }
+        }
+        future::pending().await
+    }
+
+    /// A failed `TaskResponse` for a task that never produced one.
+    ///
+    /// # Arguments
+    /// - `task_id`: The id of the task.
+    /// - `error`: Why the task failed.
+    ///
+    /// # Examples
+    /// ```
+    /// Self::failed_response(&task_id, TaskError::Timeout);
     /// ```
-    fn send_task_response(task_response: TaskResponse) {
-        OUTBOUND.0.send(task_response).unwrap();
+    fn failed_response(task_id: &str, error: TaskError) -> TaskResponse {
+        TaskResponse {
+            task_id: String::from(task_id),
+            task_result: String::default(),
+            task_status: TaskStatus::Failed,
+            task_error: vec![error.to_string()],
+        }
     }
 
-    /// Sends a task request to the inbound channel.
+    /// Sends a task response back to the caller through the request's reply channel.
     ///
     /// # Arguments
-    /// - task_request: A reference to the TaskRequest object that needs to be sent.
+    /// - `task_reply`: The reply channel taken out of the `TaskRequest`.
+    /// - `task_response`: The `TaskResponse` object that encapsulates the result or outcome of a task.
     ///
     /// # Examples
-    /// /// // Assuming `task_request` is a valid TaskRequest reference /// self.send_task_request(&task_request); ///
-    fn send_task_request(task_request: &TaskRequest) {
-        INBOUND.0.send(task_request.clone()).unwrap();
+    /// ```
+    /// // Assuming `task_response` is a valid TaskResponse object
+    /// send_task_response(task_request.task_reply.take(), task_response);
+    /// ```
+    fn send_task_response(task_reply: Option<Sender<TaskResponse>>, task_response: TaskResponse) {
+        // the caller may have gone away, there is no one left to tell.
+        if let Some(task_reply) = task_reply {
+            let _ = task_reply.send(task_response);
+        }
     }
 
-    /// Waits for the completion of a specific task.
+    /// Sends a task request to the inbound channel, waits while it is full
+    /// but not past the deadline of the request, it then fails with `Timeout`
+    /// without ever running.
     ///
     /// # Arguments
-    /// - `task_request`: A reference to the `TaskRequest` for which the completion is awaited.
+    /// - `task_request`: The TaskRequest object that needs to be sent.
+    /// - `timeout`: The deadline of the `TaskRequest`.
     ///
     /// # Examples
     /// ```
-    /// // Assuming `task_request` is a reference to a valid TaskRequest
-    /// let task_response = wait_for_task_completion(&task_request);
+    /// // Assuming `task_request` is a valid TaskRequest
+    /// Self::send_task_request(task_request, timeout).await?;
     /// ```
-    fn wait_for_task_completion(task_request: &TaskRequest) -> TaskResponse {
-        for task in OUTBOUND.1.iter(){
-            if task.task_id.eq(&task_request.task_id) {
-                Self::log_task_outcome(&task);
-                return task;
+    async fn send_task_request(task_request: TaskRequest, timeout: Duration) -> TaskResult<()> {
+        // when there is no listener the request, and its reply channel, are
+        // dropped here which fails the caller instead of leaving it waiting.
+        let Some(inbound) = INBOUND.get() else {
+            return Ok(());
+        };
+        let task_id = task_request.task_id.clone();
+        match tokio::time::timeout(timeout, inbound.send(task_request)).await {
+            Err(_) => {
+                println!(
+                    "[TASK] Task: {} timed out waiting for room inside of the inbound queue.",
+                    task_id
+                );
+                Err(TaskError::Timeout)
             }
+            Ok(_) => Ok(()),
         }
-        unreachable!()
     }
 
-    /// Waits for the completion of a specific async task.
+    /// Waits for the response of a specific task on its reply channel, at
+    /// most until the deadline of the task. A task past its deadline is cancelled.
     ///
     /// # Arguments
-    /// - `task_request`: A reference to the `TaskRequest` for which the completion is awaited.
+    /// - `task_id`: The id of the `TaskRequest` for which the completion is awaited.
+    /// - `timeout`: The deadline of the `TaskRequest`.
+    /// - `reply_receiver`: The receiving end of the request's reply channel.
+    /// - `task_cancel`: The cancel channel of a request sent through a `TaskTransport`.
     ///
     /// # Examples
     /// ```
-    /// // Assuming `task_request` is a reference to a valid TaskRequest
-    /// let task_response = wait_for_task_completion(&task_request);
+    /// // Assuming `reply_receiver` belongs to the sent TaskRequest
+    /// let task_response = wait_for_task_completion(&task_id, timeout, reply_receiver, None).await;
     /// ```
-    fn wait_for_async_task_completion(task_request: &TaskRequest) -> JoinHandle<TaskResponse> {
-        let task_request_clone = task_request.clone(); // Clone the TaskRequest
-        task::spawn(async move {
-            for task in OUTBOUND.1.iter() {
-                if task.task_id.eq(&task_request_clone.task_id) {
-                    Self::log_task_outcome(&task);
-                    return task;
-                }
+    async fn wait_for_task_completion(
+        task_id: &str,
+        timeout: Duration,
+        reply_receiver: Receiver<TaskResponse>,
+        task_cancel: Option<Receiver<()>>,
+    ) -> TaskResponse {
+        let task_response = tokio::select! {
+            biased;
+            _ = Self::wait_for_cancel(task_cancel) => {
+                Self::failed_response(task_id, TaskError::Cancelled)
             }
-            unreachable!()
-        })
+            task_reply = tokio::time::timeout(timeout, reply_receiver) => match task_reply {
+                Ok(Ok(task_response)) => task_response,
+                // the reply channel is dropped without an answer when the handler panics.
+                Ok(Err(_)) => Self::failed_response(task_id, TaskError::TaskInternalError),
+                Err(_) => {
+                    Self::cancel(task_id);
+                    Self::failed_response(task_id, TaskError::Timeout)
+                }
+            },
+        };
+        CANCELLATIONS.lock().unwrap().remove(task_id);
+        Self::log_task_outcome(&task_response);
+        task_response
     }
 
     /// Logs the outcome of a task based on its response status.
@@ -278,7 +654,7 @@ impl TaskManager {
             ),
             TaskStatus::Failed => println!(
                 "[TASK] Task: {} did not complete successfully. Error: {}",
-                task_response.task_id, task_response.task_error[0]
+                task_response.task_id, task_response.task_error.join(", ")
             ),
         }
     }
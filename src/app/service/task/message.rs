@@ -1,7 +1,22 @@
+use std::{collections::HashMap, fmt::Display, time::Duration};
+
 use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot::{Receiver, Sender};
+
+use crate::app::service::cache::error::CacheError;
+
+use super::{
+    error::{TaskError, TaskResult},
+    retry::Transient,
+};
 
-use super::error::{TaskError, TaskResult};
+/// How long a task may take, in milliseconds, unless the request says otherwise.
+pub const DEFAULT_TASK_TIMEOUT_MS: u64 = 30_000;
+
+fn default_task_timeout() -> u64 {
+    DEFAULT_TASK_TIMEOUT_MS
+}
 
 /// Represents the status of a task.
 ///
@@ -19,13 +34,32 @@ pub enum TaskStatus {
 /// Represents the type of task.
 ///
 /// This enum is used to identify what type of task is being sent to the
-/// INBOUND channel. It ensures that the right handler is used.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// INBOUND channel. Tasks are dispatched by their action, the type is used
+/// for concurrency limits and logging. `Custom` is for tasks registered
+/// outside of the iam platform.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TaskType {
     Permission,
     Role,
     User,
-    Session
+    Session,
+    /// An ordered list of IAM operations run inside of one transaction.
+    Batch,
+    Custom(String),
+}
+
+impl TaskType {
+    /// The name of the type, ex: `User`, or the name of a custom type.
+    pub fn name(&self) -> &str {
+        match self {
+            TaskType::Permission => "Permission",
+            TaskType::Role => "Role",
+            TaskType::User => "User",
+            TaskType::Session => "Session",
+            TaskType::Batch => "Batch",
+            TaskType::Custom(name) => name,
+        }
+    }
 }
 
 /// A request structure for a task.
@@ -33,7 +67,7 @@ pub enum TaskType {
 /// This struct is used to encapsulate the details of a task request. It includes
 /// the unique identifier of the task and the payload which contains the details
 /// or data required to perform the task.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct TaskRequest {
     /// A unique identifier for the task.
     pub task_id: String,
@@ -46,6 +80,53 @@ pub struct TaskRequest {
 
     /// The type of action to perform on the handler.
     pub task_action: String,
+
+    /// How long the task may take, in milliseconds, before it fails with
+    /// `TaskError::Timeout`. The time spent waiting in `INBOUND` counts.
+    #[serde(default = "default_task_timeout")]
+    pub task_timeout: u64,
+
+    /// The stream the `TaskResponse` is written to when the task runs in another
+    /// process, set by the `RedisStreamTransport`.
+    #[serde(default)]
+    pub task_reply_to: Option<String>,
+
+    /// Context carried along with the task for the `TaskInterceptor`s, ex: the actor
+    /// that sent it.
+    #[serde(default)]
+    pub task_metadata: HashMap<String, String>,
+
+    /// Requests sent again under the same key are answered with the first
+    /// result instead of running, see `Idempotency`.
+    #[serde(default)]
+    pub task_idempotency_key: Option<String>,
+
+    /// Where the `TaskResponse` is sent back to, only the caller that sent the
+    /// request holds the receiving end.
+    #[serde(skip)]
+    pub task_reply: Option<Sender<TaskResponse>>,
+
+    /// Fires when the task is cancelled through `TaskManager::cancel`.
+    #[serde(skip)]
+    pub task_cancel: Option<Receiver<()>>,
+}
+
+/// The reply and cancel channels belong to a single request, so they are never cloned.
+impl Clone for TaskRequest {
+    fn clone(&self) -> Self {
+        Self {
+            task_id: self.task_id.clone(),
+            task_payload: self.task_payload.clone(),
+            task_type: self.task_type.clone(),
+            task_action: self.task_action.clone(),
+            task_timeout: self.task_timeout,
+            task_reply_to: self.task_reply_to.clone(),
+            task_metadata: self.task_metadata.clone(),
+            task_idempotency_key: self.task_idempotency_key.clone(),
+            task_reply: None,
+            task_cancel: None,
+        }
+    }
 }
 
 impl TaskRequest {
@@ -60,9 +141,69 @@ impl TaskRequest {
             task_payload: serde_json::to_string(&task_payload).unwrap(),
             task_type,
             task_action: String::from(task_action),
+            task_timeout: DEFAULT_TASK_TIMEOUT_MS,
+            task_reply_to: None,
+            task_metadata: HashMap::new(),
+            task_idempotency_key: None,
+            task_reply: None,
+            task_cancel: None,
         }
     }
 
+    /// Sets how long the task may take before it fails with `TaskError::Timeout`.
+    ///
+    /// # Arguments
+    /// - `timeout`: the deadline of the task, counted from when it is sent.
+    ///
+    /// # Examples
+    /// ```
+    /// let request = UserPreloadCache {}.into_request().with_timeout(Duration::from_secs(120));
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.task_timeout = timeout.as_millis() as u64;
+        self
+    }
+
+    /// The deadline of the task.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.task_timeout)
+    }
+
+    /// Attaches a value to the task for the `TaskInterceptor`s.
+    ///
+    /// # Arguments
+    /// - `key`: the name of the value.
+    /// - `value`: the value.
+    ///
+    /// # Examples
+    /// ```
+    /// let request = UserPreloadCache {}.into_request().with_metadata("actor", &user_id);
+    /// ```
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.task_metadata.insert(String::from(key), String::from(value));
+        self
+    }
+
+    /// Sends the task under an idempotency key, sending it again under the
+    /// same key answers with the first result.
+    ///
+    /// # Arguments
+    /// - `key`: a key unique to the operation, ex: a uuid picked by the client.
+    ///
+    /// # Examples
+    /// ```
+    /// let request = RoleCreateTask { role }.into_request().with_idempotency_key("5f0c...");
+    /// ```
+    pub fn with_idempotency_key(mut self, key: &str) -> Self {
+        self.task_idempotency_key = Some(String::from(key));
+        self
+    }
+
+    /// A value attached to the task through `TaskRequest::with_metadata`.
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.task_metadata.get(key).map(String::as_str)
+    }
+
     pub fn intepret_request_payload<T: for<'a> Deserialize<'a>>(
         task_request: &TaskRequest,
     ) -> TaskResult<T> {
@@ -78,7 +219,7 @@ impl TaskRequest {
 /// This struct is used to represent the response of a task. It is generic over `T`,
 /// allowing for flexibility in the type of result that the task returns. It includes
 /// the unique task identifier, the result of the task, and the status of the task.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TaskResponse {
     /// The unique identifier of the task.
     pub task_id: String,
@@ -121,6 +262,65 @@ impl TaskResponse {
         }
     }
 
+    /// Fails a task because of a database error, with `TaskError::TransientFailure`
+    /// when trying again may succeed and `TaskError::TaskInternalError` otherwise.
+    ///
+    /// # Arguments
+    /// - `request`: the request of the task.
+    /// - `error`: the error the database answered with.
+    ///
+    /// # Examples
+    /// ```
+    /// let pool = match db.pool.get().await {
+    ///     Ok(pool) => pool,
+    ///     Err(error) => return TaskResponse::throw_database_failure(request, &error),
+    /// };
+    /// ```
+    pub fn throw_database_failure<E: Transient + Display>(request: TaskRequest, error: &E) -> Self {
+        println!(
+            "[TASK] Task: {} failed on the database. Error: {}",
+            request.task_id, error
+        );
+        Self::throw_failed_response(request, vec![Self::failure_of(error).to_string()])
+    }
+
+    /// Fails a task because the cache could not be updated, with
+    /// `TaskError::TransientFailure` when the cache did not answer.
+    ///
+    /// # Arguments
+    /// - `request`: the request of the task.
+    /// - `error`: the error the cache answered with.
+    ///
+    /// # Examples
+    /// ```
+    /// if let Err(error) = UserCacheManager::add_user_to_cache(user).await {
+    ///     return TaskResponse::throw_cache_failure(request, &error);
+    /// }
+    /// ```
+    pub fn throw_cache_failure(request: TaskRequest, error: &CacheError) -> Self {
+        println!(
+            "[TASK] Task: {} failed on the cache. Error: {}",
+            request.task_id, error
+        );
+        Self::throw_failed_response(request, vec![Self::failure_of(error).to_string()])
+    }
+
+    fn failure_of<E: Transient>(error: &E) -> TaskError {
+        match error.is_transient() {
+            true => TaskError::TransientFailure,
+            false => TaskError::TaskInternalError,
+        }
+    }
+
+    /// Whether the task failed with `TaskError::TransientFailure`.
+    pub fn is_transient_failure(&self) -> bool {
+        self.task_status == TaskStatus::Failed
+            && self
+                .task_error
+                .first()
+                .is_some_and(|error| error.eq(&TaskError::TransientFailure.to_string()))
+    }
+
     pub fn intepret_response_result<T: for<'a> Deserialize<'a>>(
         task_response: &TaskResponse,
     ) -> TaskResult<T> {
@@ -129,4 +329,14 @@ impl TaskResponse {
             Err(_) => Err(TaskError::FailedToInterpretPayload),
         }
     }
+
+    /// Recovers the `TaskError` a failed task was thrown with, falls back to
+    /// `TaskError::FailedToCompleteTask` when the first error is not one.
+    pub fn intepret_response_error(task_response: &TaskResponse) -> TaskError {
+        task_response
+            .task_error
+            .first()
+            .and_then(|error| error.parse::<TaskError>().ok())
+            .unwrap_or(TaskError::FailedToCompleteTask)
+    }
 }
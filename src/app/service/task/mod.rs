@@ -20,37 +20,57 @@
 //                               |                      |
 //                               v                      v
 //                       +-------+------+       +-------+------+
-//                       | Outbound     |       | Outbound     |
+//                       | Reply        |       | Reply        |
 //                       | Channel      |       | Channel      |
 //                       | Sends Result |       | Sends Result |
 //                       | to User      |       | to User      |
 //                       +--------------+       +--------------+
 // 
 
+use std::{collections::HashMap, sync::Mutex};
+
 use axum::async_trait;
-use crossbeam_channel::{Receiver, Sender, unbounded};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::{
+    sync::{mpsc::Sender, oneshot},
+    task::JoinHandle,
+};
 
-use self::message::{TaskRequest, TaskResponse};
+use self::{
+    message::{TaskRequest, TaskResponse},
+    transport::TaskTransport,
+};
 
+pub mod dead_letter;
 pub mod error;
+pub mod idempotency;
+pub mod interceptor;
 pub mod manager;
 pub mod message;
+pub mod recorder;
+pub mod redact;
+pub mod registry;
+pub mod retry;
+pub mod schedule;
+pub mod stream;
+pub mod transport;
+
+/// Receives tasks and processing them, created by `TaskManager::listen`.
+/// It is bounded so senders wait once the workers fall behind.
+static INBOUND: OnceCell<Sender<TaskRequest>> = OnceCell::new();
+
+/// The listener loop spawned by `TaskManager::listen`.
+static LISTENER: OnceCell<JoinHandle<()>> = OnceCell::new();
 
-/// Receives tasks and processing them.
-static INBOUND: Lazy<(Sender<TaskRequest>, Receiver<TaskRequest>)> = Lazy::new(|| unbounded());
+/// Where requests go instead of `INBOUND`, set by `TaskManager::use_transport`.
+static TRANSPORT: OnceCell<Box<dyn TaskTransport>> = OnceCell::new();
 
-/// Receives results from tasks and sends them back to the origin point.
-static OUTBOUND: Lazy<(Sender<TaskResponse>, Receiver<TaskResponse>)> = Lazy::new(|| unbounded());
+/// The cancel switch of every outstanding task, keyed by `task_id`.
+static CANCELLATIONS: Lazy<Mutex<HashMap<String, oneshot::Sender<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Used to create specific tasks.
 #[async_trait]
 pub trait Task<D, R, P> {
     async fn run(db: &D, request: R, param: P) -> TaskResponse;
 }
-
-/// Handles the task
-#[async_trait]
-pub trait TaskHandler<T> {
-    async fn handle(pg: &T, task_request: TaskRequest) -> TaskResponse;
-}
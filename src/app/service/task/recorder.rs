@@ -0,0 +1,140 @@
+use std::{env, fs::OpenOptions, io};
+
+use axum::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::AsyncWriteExt,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+};
+
+use super::{
+    interceptor::TaskInterceptor,
+    manager::TaskManager,
+    message::{TaskRequest, TaskResponse, TaskType},
+    redact::{redact_fields_from_env, redact_text},
+};
+
+/// Where task traffic is recorded.
+///
+/// # Fields
+///
+/// * `path` - The JSONL file every request and its response are appended to,
+///   nothing is recorded when it is empty.
+/// * `redact_fields` - Fields of a payload or result holding secrets, recorded
+///   as `[REDACTED]` at any depth, or as `null` when they are not a string.
+pub struct RecorderConfig {
+    pub path: Option<String>,
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for RecorderConfig {
+    /// Reads `TASK_RECORD`, ex: `recordings/ark.jsonl`, and
+    /// `TASK_RECORD_REDACT_FIELDS`, ex: `token,stamp`, which defaults to the
+    /// tokens and stamps of the IAM models.
+    fn default() -> Self {
+        Self {
+            path: env::var("TASK_RECORD").ok().filter(|path| !path.is_empty()),
+            redact_fields: redact_fields_from_env("TASK_RECORD_REDACT_FIELDS"),
+        }
+    }
+}
+
+/// A line of a recording, a request along with the response it got.
+#[derive(Serialize, Deserialize)]
+pub struct RecordedTask {
+    pub recorded_at: i64,
+    pub task_request: TaskRequest,
+    pub task_response: TaskResponse,
+}
+
+/// Appends every task this process runs to a JSONL file, in the order they
+/// complete, so the traffic can be fed to `ark-replay` later on. `Session`
+/// tasks are left out, they carry the tokens of signed-in users, and the
+/// secret fields of the other tasks are redacted.
+pub struct TaskRecorder {
+    sender: UnboundedSender<String>,
+    redact_fields: Vec<String>,
+}
+
+impl TaskRecorder {
+    /// Starts recording when the config has a path.
+    ///
+    /// # Arguments
+    /// - `config`: where the traffic is recorded.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskRecorder::enable(RecorderConfig::default())?;
+    /// ```
+    pub fn enable(config: RecorderConfig) -> io::Result<()> {
+        let Some(path) = config.path else {
+            return Ok(());
+        };
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let (sender, receiver) = unbounded_channel();
+        tokio::spawn(Self::write(File::from_std(file), receiver));
+        TaskManager::intercept(TaskRecorder {
+            sender,
+            redact_fields: config.redact_fields,
+        });
+        println!("[TASK] Recording tasks to {}.", path);
+        Ok(())
+    }
+
+    /// Appends the lines it receives one at a time, so lines of tasks
+    /// completing together never interleave and no task waits on the disk.
+    async fn write(mut file: File, mut receiver: UnboundedReceiver<String>) {
+        while let Some(line) = receiver.recv().await {
+            if let Err(error) = file.write_all(line.as_bytes()).await {
+                println!("[TASK] Failed to record a task. Error: {}", error);
+            }
+        }
+    }
+
+    /// Reads every line of a recording, lines that are not a `RecordedTask`
+    /// are skipped.
+    ///
+    /// # Arguments
+    /// - `path`: the JSONL file to read.
+    ///
+    /// # Examples
+    /// ```
+    /// let recording = TaskRecorder::read("recordings/ark.jsonl")?;
+    /// ```
+    pub fn read(path: &str) -> io::Result<Vec<RecordedTask>> {
+        let recording = std::fs::read_to_string(path)?;
+        Ok(recording
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str::<RecordedTask>(line).ok())
+            .collect())
+    }
+}
+
+#[async_trait]
+impl TaskInterceptor for TaskRecorder {
+    async fn after(&self, task_request: &TaskRequest, task_response: &TaskResponse) {
+        if task_request.task_type == TaskType::Session {
+            return;
+        }
+        let mut recorded = RecordedTask {
+            recorded_at: Utc::now().timestamp_millis(),
+            task_request: task_request.clone(),
+            task_response: task_response.clone(),
+        };
+        recorded.task_request.task_payload =
+            redact_text(&task_request.task_payload, &self.redact_fields);
+        recorded.task_response.task_result =
+            redact_text(&task_response.task_result, &self.redact_fields);
+        let mut line = serde_json::to_string(&recorded).unwrap();
+        line.push('\n');
+        if self.sender.send(line).is_err() {
+            println!(
+                "[TASK] Failed to record task {}. Error: the writer stopped",
+                task_request.task_id
+            );
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::env;
+
+use serde_json::Value;
+
+/// What a redacted value is kept as.
+pub static REDACTED: &str = "[REDACTED]";
+
+/// The fields redacted when none are configured, the tokens and stamps of
+/// the IAM models.
+static DEFAULT_REDACT_FIELDS: &str = "token,stamp,csrf_token,access_token,security_token";
+
+/// Reads the fields to redact from a comma separated environment variable,
+/// ex: `token,stamp`, falling back to the tokens and stamps of the IAM models.
+///
+/// # Arguments
+/// - `var`: the environment variable to read.
+///
+/// # Examples
+/// ```
+/// let redact_fields = redact_fields_from_env("TASK_RECORD_REDACT_FIELDS");
+/// ```
+pub fn redact_fields_from_env(var: &str) -> Vec<String> {
+    env::var(var)
+        .unwrap_or(String::from(DEFAULT_REDACT_FIELDS))
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The JSON text with every value of a redacted field swapped for
+/// `[REDACTED]`, text that is not JSON is kept as it is.
+///
+/// # Arguments
+/// - `text`: the payload or result of a task.
+/// - `redact_fields`: the fields holding secrets.
+///
+/// # Examples
+/// ```
+/// let payload = redact_text(&task_request.task_payload, &redact_fields);
+/// ```
+pub fn redact_text(text: &str, redact_fields: &[String]) -> String {
+    match serde_json::from_str::<Value>(text) {
+        Ok(mut value) => {
+            redact(&mut value, redact_fields);
+            value.to_string()
+        }
+        Err(_) => text.to_string(),
+    }
+}
+
+/// Swaps the value of every redacted field for `[REDACTED]` at any depth, or
+/// for `null` when it is not a string, so optional tokens still deserialize.
+///
+/// # Arguments
+/// - `value`: the JSON to redact in place.
+/// - `redact_fields`: the fields holding secrets.
+///
+/// # Examples
+/// ```
+/// redact(&mut value, &redact_fields);
+/// ```
+pub fn redact(value: &mut Value, redact_fields: &[String]) {
+    match value {
+        Value::Object(fields) => {
+            for (field, value) in fields.iter_mut() {
+                match redact_fields.iter().any(|redacted| redacted.eq(field)) {
+                    true if value.is_string() => *value = Value::String(String::from(REDACTED)),
+                    true => *value = Value::Null,
+                    false => redact(value, redact_fields),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| redact(value, redact_fields)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn redact_swaps_secret_fields_at_any_depth() {
+        let redact_fields = vec![String::from("token"), String::from("stamp")];
+        let mut value = json!({
+            "user_id": "1",
+            "security": {"token": {"token": "abc", "expiry": 1}, "stamp": "def"},
+            "sessions": [{"token": "ghi"}],
+            "stamp": null,
+        });
+        redact(&mut value, &redact_fields);
+        assert_eq!(
+            value,
+            json!({
+                "user_id": "1",
+                "security": {"token": null, "stamp": REDACTED},
+                "sessions": [{"token": REDACTED}],
+                "stamp": null,
+            })
+        );
+    }
+
+    #[test]
+    fn redact_text_keeps_text_that_is_not_json() {
+        let redact_fields = vec![String::from("token")];
+        assert_eq!(redact_text("not json", &redact_fields), "not json");
+        assert_eq!(
+            redact_text(r#"{"token":"abc"}"#, &redact_fields),
+            r#"{"token":"[REDACTED]"}"#
+        );
+    }
+}
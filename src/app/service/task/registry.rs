@@ -0,0 +1,316 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::app::database::{postgres::PostgresDatabase, redis::RedisDatabase};
+
+use super::{
+    error::TaskError,
+    message::{TaskRequest, TaskResponse, TaskType},
+    retry::RetryPolicy,
+    Task,
+};
+
+type TaskFuture = Pin<Box<dyn Future<Output = TaskResponse> + Send>>;
+type ErasedTask = Arc<dyn Fn(TaskContext, TaskRequest) -> TaskFuture + Send + Sync>;
+
+/// A task inside of the `TaskRegistry`.
+#[derive(Clone)]
+struct RegisteredTask {
+    run: ErasedTask,
+    idempotent: bool,
+    read_only: bool,
+    /// Known for tasks registered through `TaskDefinition::register`.
+    task_type: Option<TaskType>,
+}
+
+/// Every registered task, keyed by its `task_action`.
+static REGISTRY: Lazy<RwLock<HashMap<String, RegisteredTask>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The retry policies set through `TaskRegistry::set_retry_policy`, keyed by `task_action`.
+static RETRY_POLICIES: Lazy<RwLock<HashMap<String, RetryPolicy>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The databases a task can run against.
+#[derive(Clone)]
+pub struct TaskContext {
+    pub pg: PostgresDatabase,
+    pub redis: RedisDatabase,
+}
+
+/// Picks the database a task runs against out of the `TaskContext`.
+pub trait FromTaskContext: Send + Sync + 'static {
+    fn from_context(context: &TaskContext) -> &Self;
+}
+
+impl FromTaskContext for PostgresDatabase {
+    fn from_context(context: &TaskContext) -> &Self {
+        &context.pg
+    }
+}
+
+impl FromTaskContext for RedisDatabase {
+    fn from_context(context: &TaskContext) -> &Self {
+        &context.redis
+    }
+}
+
+/// A task payload that knows its own action, type and database, usually
+/// implemented through `#[derive(Task)]`.
+///
+/// # Examples
+/// ```
+/// #[derive(Serialize, Deserialize, Task)]
+/// #[task(task_type = Session, action = "session_read", db = RedisDatabase)]
+/// pub struct SessionReadTask {
+///     pub session_id: String,
+/// }
+/// ```
+pub trait TaskDefinition:
+    Task<Self::Database, TaskRequest, Self>
+    + Serialize
+    + for<'a> Deserialize<'a>
+    + Send
+    + Sized
+    + 'static
+{
+    type Database: FromTaskContext;
+    const ACTION: &'static str;
+    /// Whether running the task twice has the same effect as running it once.
+    const IDEMPOTENT: bool = false;
+    /// Whether the task leaves the database untouched, such tasks keep
+    /// running while in maintenance.
+    const READ_ONLY: bool = false;
+
+    fn task_type() -> TaskType;
+
+    /// Registers the task with the `TaskRegistry` under `ACTION`.
+    fn register() {
+        TaskRegistry::insert::<Self::Database, Self>(
+            Self::ACTION,
+            Self::IDEMPOTENT,
+            Self::READ_ONLY,
+            Some(Self::task_type()),
+        );
+    }
+
+    /// Composes a `TaskRequest` carrying the task as its payload.
+    fn into_request(self) -> TaskRequest {
+        TaskRequest::compose_request(self, Self::task_type(), Self::ACTION)
+    }
+}
+
+/// Maps a `task_action` to the payload it carries and the `Task` that runs it.
+///
+/// Tasks register themselves before `TaskManager::listen`, which then
+/// dispatches each `TaskRequest` through a map lookup. Other crates add
+/// their own tasks the same way, along with `TaskType::Custom`.
+pub struct TaskRegistry;
+
+impl TaskRegistry {
+    /// Registers a task, replacing whatever was registered under the action.
+    /// The task is not retried unless it is given a `RetryPolicy`, and is
+    /// refused while in maintenance.
+    ///
+    /// # Arguments
+    /// - `action`: the `task_action` the task answers to.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskRegistry::register::<PostgresDatabase, UserCreateTask>("user_create");
+    /// let request = TaskRequest::compose_request(UserCreateTask { user }, TaskType::User, "user_create");
+    /// ```
+    pub fn register<D, P>(action: &str)
+    where
+        D: FromTaskContext,
+        P: Task<D, TaskRequest, P> + for<'a> Deserialize<'a> + Send + 'static,
+    {
+        Self::insert::<D, P>(action, false, false, None);
+    }
+
+    /// Registers a task that is safe to run more than once, it is retried
+    /// with the default `RetryPolicy` when it fails with `TaskError::TransientFailure`.
+    ///
+    /// # Arguments
+    /// - `action`: the `task_action` the task answers to.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskRegistry::register_idempotent::<PostgresDatabase, UserReadTask>("user_read");
+    /// ```
+    pub fn register_idempotent<D, P>(action: &str)
+    where
+        D: FromTaskContext,
+        P: Task<D, TaskRequest, P> + for<'a> Deserialize<'a> + Send + 'static,
+    {
+        Self::insert::<D, P>(action, true, false, None);
+    }
+
+    fn insert<D, P>(action: &str, idempotent: bool, read_only: bool, task_type: Option<TaskType>)
+    where
+        D: FromTaskContext,
+        P: Task<D, TaskRequest, P> + for<'a> Deserialize<'a> + Send + 'static,
+    {
+        let run: ErasedTask = Arc::new(|context: TaskContext, task_request: TaskRequest| {
+            Box::pin(async move {
+                let payload = match TaskRequest::intepret_request_payload::<P>(&task_request) {
+                    Ok(payload) => payload,
+                    Err(_) => {
+                        return TaskResponse::throw_failed_response(
+                            task_request,
+                            vec![TaskError::FailedToInterpretPayload.to_string()],
+                        )
+                    }
+                };
+                P::run(D::from_context(&context), task_request, payload).await
+            })
+        });
+        REGISTRY
+            .write()
+            .unwrap()
+            .insert(
+                String::from(action),
+                RegisteredTask {
+                    run,
+                    idempotent,
+                    read_only,
+                    task_type,
+                },
+            );
+    }
+
+    /// Sets how the task registered under the action is retried, this also
+    /// opts a task that was not registered as idempotent into retries.
+    ///
+    /// # Arguments
+    /// - `action`: the `task_action` of the task.
+    /// - `policy`: the `RetryPolicy` to use, `RetryPolicy::never()` turns retries off.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskRegistry::set_retry_policy("user_preload_cache", RetryPolicy::new(5, Duration::from_millis(200), Duration::from_secs(5)));
+    /// ```
+    pub fn set_retry_policy(action: &str, policy: RetryPolicy) {
+        RETRY_POLICIES
+            .write()
+            .unwrap()
+            .insert(String::from(action), policy);
+    }
+
+    /// The `RetryPolicy` of the task registered under the action.
+    fn retry_policy(action: &str, idempotent: bool) -> RetryPolicy {
+        match RETRY_POLICIES.read().unwrap().get(action) {
+            Some(policy) => *policy,
+            None if idempotent => RetryPolicy::default(),
+            None => RetryPolicy::never(),
+        }
+    }
+
+    /// Whether a task is registered under the action.
+    pub fn contains(action: &str) -> bool {
+        REGISTRY.read().unwrap().contains_key(action)
+    }
+
+    /// Whether the task registered under the action is safe to run more than
+    /// once, see `TaskDefinition::IDEMPOTENT`. Unknown actions are not.
+    ///
+    /// # Arguments
+    /// - `action`: the `task_action` of the task.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskRegistry::is_idempotent("user_read");
+    /// ```
+    pub fn is_idempotent(action: &str) -> bool {
+        REGISTRY
+            .read()
+            .unwrap()
+            .get(action)
+            .is_some_and(|task| task.idempotent)
+    }
+
+    /// Whether the task registered under the action leaves the database
+    /// untouched, see `TaskDefinition::READ_ONLY`. Unknown actions are not.
+    ///
+    /// # Arguments
+    /// - `action`: the `task_action` of the task.
+    ///
+    /// # Examples
+    /// ```
+    /// TaskRegistry::is_read_only("user_read");
+    /// ```
+    pub fn is_read_only(action: &str) -> bool {
+        REGISTRY
+            .read()
+            .unwrap()
+            .get(action)
+            .is_some_and(|task| task.read_only)
+    }
+
+    /// Composes a request without a payload for the task registered under the
+    /// action, for tasks such as `UserPreloadCache` that take no fields. Only
+    /// tasks registered through `TaskDefinition::register` know their `TaskType`.
+    ///
+    /// # Arguments
+    /// - `action`: the `task_action` of the task.
+    ///
+    /// # Examples
+    /// ```
+    /// let request = TaskRegistry::compose_request("user_preload_cache").unwrap();
+    /// ```
+    pub fn compose_request(action: &str) -> Option<TaskRequest> {
+        let task_type = REGISTRY.read().unwrap().get(action)?.task_type.clone()?;
+        Some(TaskRequest::compose_request((), task_type, action))
+    }
+
+    /// Runs the task registered under the request's `task_action`, retrying it
+    /// while it fails with `TaskError::TransientFailure` and its `RetryPolicy` allows.
+    ///
+    /// # Arguments
+    /// - `context`: the databases the task may use.
+    /// - `task_request`: the request to run.
+    ///
+    /// # Examples
+    /// ```
+    /// let task_response = TaskRegistry::dispatch(&context, task_request).await;
+    /// ```
+    pub async fn dispatch(context: &TaskContext, task_request: TaskRequest) -> TaskResponse {
+        let task = REGISTRY
+            .read()
+            .unwrap()
+            .get(&task_request.task_action)
+            .cloned();
+        let task = match task {
+            Some(task) => task,
+            None => {
+                return TaskResponse::throw_failed_response(
+                    task_request,
+                    vec![TaskError::FailedToFindAction.to_string()],
+                )
+            }
+        };
+        let policy = Self::retry_policy(&task_request.task_action, task.idempotent);
+        let mut attempt = 1;
+        loop {
+            let task_response = (task.run)(context.clone(), task_request.clone()).await;
+            if attempt >= policy.max_attempts || !task_response.is_transient_failure() {
+                return task_response;
+            }
+            println!(
+                "[TASK] Task: {} failed transiently, retrying (attempt {} of {}).",
+                task_request.task_id,
+                attempt + 1,
+                policy.max_attempts
+            );
+            tokio::time::sleep(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::{env, error::Error, io, str::FromStr, time::Duration};
+
+use bb8::RunError;
+use bb8_postgres::tokio_postgres::{self, error::SqlState};
+use bb8_redis::redis::{ErrorKind, RedisError};
+use rand::Rng;
+
+use crate::app::service::cache::error::CacheError;
+
+/// An error that may go away when the same operation is tried again, ex: a
+/// dropped connection or a serialization failure. Logical errors such as a
+/// unique violation are never transient.
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+}
+
+impl Transient for tokio_postgres::Error {
+    fn is_transient(&self) -> bool {
+        if self.is_closed() {
+            return true;
+        }
+        match self.code() {
+            Some(code) => {
+                *code == SqlState::T_R_SERIALIZATION_FAILURE
+                    || *code == SqlState::T_R_DEADLOCK_DETECTED
+                    || *code == SqlState::ADMIN_SHUTDOWN
+                    || *code == SqlState::CRASH_SHUTDOWN
+                    || *code == SqlState::CANNOT_CONNECT_NOW
+                    || *code == SqlState::TOO_MANY_CONNECTIONS
+                    // connection exceptions.
+                    || code.code().starts_with("08")
+            }
+            // without a SQLSTATE the server never answered.
+            None => self.source().is_some_and(|source| source.is::<io::Error>()),
+        }
+    }
+}
+
+impl Transient for RedisError {
+    fn is_transient(&self) -> bool {
+        self.is_io_error()
+            || self.is_timeout()
+            || self.is_connection_dropped()
+            || self.is_connection_refusal()
+            || matches!(
+                self.kind(),
+                ErrorKind::TryAgain | ErrorKind::BusyLoadingError
+            )
+    }
+}
+
+impl<E: Transient> Transient for RunError<E> {
+    fn is_transient(&self) -> bool {
+        match self {
+            RunError::User(error) => error.is_transient(),
+            // no connection was free in time.
+            RunError::TimedOut => true,
+        }
+    }
+}
+
+impl Transient for CacheError {
+    fn is_transient(&self) -> bool {
+        // the cache did not answer, or its Redis failed.
+        matches!(
+            self,
+            CacheError::FailedToCompleteCache | CacheError::InternalError
+        )
+    }
+}
+
+/// How a task that failed with `TaskError::TransientFailure` is retried.
+///
+/// # Fields
+///
+/// * `max_attempts` - How many times the task runs at most, `1` never retries.
+/// * `base_delay` - The delay before the first retry, doubled on every retry after.
+/// * `max_delay` - The longest delay between two attempts.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Reads `TASK_RETRY_MAX_ATTEMPTS` (default 3), `TASK_RETRY_BASE_DELAY_MS`
+    /// (default 50) and `TASK_RETRY_MAX_DELAY_MS` (default 2000).
+    fn default() -> Self {
+        Self::new(
+            Self::from_env("TASK_RETRY_MAX_ATTEMPTS").unwrap_or(3),
+            Duration::from_millis(Self::from_env("TASK_RETRY_BASE_DELAY_MS").unwrap_or(50)),
+            Duration::from_millis(Self::from_env("TASK_RETRY_MAX_DELAY_MS").unwrap_or(2000)),
+        )
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// A policy that runs the task once.
+    pub fn never() -> Self {
+        Self::new(1, Duration::ZERO, Duration::ZERO)
+    }
+
+    /// How long to wait after the given failed attempt, an exponential
+    /// backoff with full jitter so retries of concurrent tasks spread out.
+    ///
+    /// # Arguments
+    /// - `attempt`: the attempt that failed, starting at 1.
+    ///
+    /// # Examples
+    /// ```
+    /// tokio::time::sleep(policy.delay(attempt)).await;
+    /// ```
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        rand::thread_rng().gen_range(Duration::ZERO..=backoff)
+    }
+
+    fn from_env<T: FromStr>(key: &str) -> Option<T> {
+        env::var(key).ok().and_then(|value| value.parse::<T>().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Enough samples that a jitter stuck on one value would show.
+    static SAMPLES: usize = 1000;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(5, Duration::from_millis(100), Duration::from_secs(1))
+    }
+
+    #[test]
+    fn delay_stays_within_the_backoff_of_the_attempt() {
+        let policy = policy();
+        for (attempt, backoff) in [(1, 100), (2, 200), (3, 400), (4, 800)] {
+            for _ in 0..SAMPLES {
+                assert!(policy.delay(attempt) <= Duration::from_millis(backoff));
+            }
+        }
+    }
+
+    #[test]
+    fn delay_never_exceeds_the_max_delay() {
+        let policy = policy();
+        for attempt in [5, 10, 32, 64, u32::MAX] {
+            for _ in 0..SAMPLES {
+                assert!(policy.delay(attempt) <= policy.max_delay);
+            }
+        }
+    }
+
+    #[test]
+    fn delay_of_attempt_zero_is_the_one_of_the_first_attempt() {
+        let policy = policy();
+        for _ in 0..SAMPLES {
+            assert!(policy.delay(0) <= policy.base_delay);
+        }
+    }
+
+    #[test]
+    fn delay_is_jittered_across_the_whole_backoff() {
+        let policy = policy();
+        let backoff = Duration::from_millis(800);
+        let delays: Vec<Duration> = (0..SAMPLES).map(|_| policy.delay(4)).collect();
+        assert!(delays.iter().any(|delay| *delay < backoff / 4));
+        assert!(delays.iter().any(|delay| *delay > backoff * 3 / 4));
+    }
+
+    #[test]
+    fn delay_is_zero_without_a_base_delay() {
+        let policy = RetryPolicy::new(3, Duration::ZERO, Duration::from_secs(1));
+        assert_eq!(policy.delay(1), Duration::ZERO);
+        assert_eq!(policy.delay(3), Duration::ZERO);
+        assert_eq!(RetryPolicy::never().delay(1), Duration::ZERO);
+    }
+
+    #[test]
+    fn runs_at_least_once() {
+        assert_eq!(RetryPolicy::never().max_attempts, 1);
+        assert_eq!(
+            RetryPolicy::new(0, Duration::ZERO, Duration::ZERO).max_attempts,
+            1
+        );
+    }
+
+    #[test]
+    fn only_an_unanswered_cache_is_transient() {
+        assert!(CacheError::FailedToCompleteCache.is_transient());
+        assert!(CacheError::InternalError.is_transient());
+        assert!(!CacheError::ItemNotFound.is_transient());
+        assert!(!CacheError::UniqueViolation.is_transient());
+    }
+}
@@ -0,0 +1,469 @@
+//
+// Scheduler
+//
+// +---------------------+       +---------------------+       +---------------------+
+// | Scheduled Job       |       | Redis               |       | TaskManager         |
+// | Sleeps Until the    | ----> | SET NX on the Fire  | ----> | Runs the Task Like  |
+// | Next Fire Time      |       | Time, One Node Wins |       | Any Other Request   |
+// +---------------------+       +---------------------+       +---------------------+
+//
+
+use std::{
+    collections::HashMap,
+    env, fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use bb8_redis::redis::{Cmd, RedisError};
+use chrono::{DateTime, TimeZone, Utc};
+use nanoid::nanoid;
+use once_cell::sync::Lazy;
+
+use crate::app::database::redis::RedisDatabase;
+
+use super::{manager::TaskManager, message::TaskRequest, registry::TaskRegistry};
+
+type ComposeRequest = Arc<dyn Fn() -> TaskRequest + Send + Sync>;
+
+/// Every job defined in code, keyed by its name.
+static JOBS: Lazy<RwLock<HashMap<String, ScheduledJob>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// When a job fires.
+#[derive(Clone, Debug)]
+pub enum Schedule {
+    /// Fires every interval, counted from the unix epoch so every node agrees
+    /// on the fire times.
+    Every(Duration),
+    /// Fires on a cron expression with a seconds field, ex: `0 */15 * * * *`.
+    Cron(Box<cron::Schedule>),
+}
+
+impl Schedule {
+    /// A schedule that fires every interval.
+    ///
+    /// # Examples
+    /// ```
+    /// let schedule = Schedule::every(Duration::from_secs(600));
+    /// ```
+    pub fn every(interval: Duration) -> Self {
+        Self::Every(interval.max(Duration::from_secs(1)))
+    }
+
+    /// A schedule that fires on a cron expression, panics when the expression
+    /// is invalid since it is written in code.
+    ///
+    /// # Examples
+    /// ```
+    /// let schedule = Schedule::cron("0 0 3 * * *");
+    /// ```
+    pub fn cron(expression: &str) -> Self {
+        expression.parse().expect("invalid cron expression")
+    }
+
+    /// The first fire time after the given time.
+    ///
+    /// # Arguments
+    /// - `after`: the time to look from.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Every(interval) => {
+                let interval = interval.as_millis() as i64;
+                let next = (after.timestamp_millis() / interval + 1) * interval;
+                Utc.timestamp_millis_opt(next).single()
+            }
+            Self::Cron(schedule) => schedule.after(&after).next(),
+        }
+    }
+
+    /// Parses the duration of `@every`, ex: `30s`, `10m`, `2h` or `1d`.
+    fn parse_interval(value: &str) -> Option<Duration> {
+        let value = value.trim();
+        let unit_at = value.find(|c: char| !c.is_ascii_digit())?;
+        let amount: u64 = value[..unit_at].parse().ok()?;
+        let unit = match &value[unit_at..] {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 60 * 60 * 24,
+            _ => return None,
+        };
+        amount.checked_mul(unit).map(Duration::from_secs)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Every(interval) => write!(f, "@every {}s", interval.as_secs()),
+            Self::Cron(schedule) => write!(f, "{}", schedule),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// Parses `@every <interval>` ex: `@every 10m`, or a cron expression.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        match value.strip_prefix("@every") {
+            Some(interval) => Self::parse_interval(interval)
+                .map(Self::every)
+                .ok_or(format!("invalid interval: {}", interval.trim())),
+            None => cron::Schedule::from_str(value)
+                .map(|schedule| Self::Cron(Box::new(schedule)))
+                .map_err(|error| error.to_string()),
+        }
+    }
+}
+
+/// A task submitted on a `Schedule`.
+///
+/// # Fields
+///
+/// * `name` - The name of the job, also the key of its lock.
+/// * `schedule` - When the job fires.
+/// * `per_instance` - Whether every instance runs the job, for jobs that
+///   refresh something local to the process ex: `ROLE_CACHE`.
+/// * `compose_request` - Composes the `TaskRequest` submitted on every fire.
+#[derive(Clone)]
+pub struct ScheduledJob {
+    pub name: String,
+    pub schedule: Schedule,
+    pub per_instance: bool,
+    compose_request: ComposeRequest,
+}
+
+impl ScheduledJob {
+    /// Creates a job that a single instance of the cluster runs on every fire.
+    ///
+    /// # Arguments
+    /// - `name`: the name of the job.
+    /// - `schedule`: when the job fires.
+    /// - `compose_request`: composes the request submitted on every fire.
+    ///
+    /// # Examples
+    /// ```
+    /// let job = ScheduledJob::new("user_refresh_cache", Schedule::every(Duration::from_secs(600)), || {
+    ///     UserPreloadCache {}.into_request()
+    /// });
+    /// ```
+    pub fn new<F>(name: &str, schedule: Schedule, compose_request: F) -> Self
+    where
+        F: Fn() -> TaskRequest + Send + Sync + 'static,
+    {
+        Self {
+            name: String::from(name),
+            schedule,
+            per_instance: false,
+            compose_request: Arc::new(compose_request),
+        }
+    }
+
+    /// Runs the job on every instance instead of a single one.
+    pub fn per_instance(mut self) -> Self {
+        self.per_instance = true;
+        self
+    }
+
+    fn compose_request(&self) -> TaskRequest {
+        (self.compose_request)()
+    }
+}
+
+/// Which jobs run and when.
+///
+/// # Fields
+///
+/// * `enabled` - Whether this instance runs any job.
+/// * `schedules` - Schedules that replace the ones of jobs defined in code,
+///   `off` turns a job off. A name that is not a job is taken as the
+///   `task_action` of a registered task without a payload.
+pub struct SchedulerConfig {
+    pub enabled: bool,
+    pub schedules: HashMap<String, String>,
+}
+
+impl Default for SchedulerConfig {
+    /// Reads `TASK_SCHEDULER` (default `true`) and `TASK_SCHEDULES`, ex:
+    /// `user_refresh_cache=@every 5m;session_prune=off;user_preload_cache=0 0 3 * * *`.
+    fn default() -> Self {
+        let schedules = env::var("TASK_SCHEDULES")
+            .unwrap_or_default()
+            .split(';')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(name, schedule)| (String::from(name.trim()), String::from(schedule.trim())))
+            .collect();
+        Self {
+            enabled: env::var("TASK_SCHEDULER").map_or(true, |enabled| enabled.ne("false")),
+            schedules,
+        }
+    }
+}
+
+/// Submits scheduled jobs through the `TaskManager`.
+///
+/// Every instance keeps the same schedule, and before a job fires each one
+/// tries to `SET NX` a key named after the job and its fire time, only the
+/// instance that sets it submits the task. A fire that comes while the
+/// previous run of the job has not finished is skipped.
+pub struct Scheduler {
+    redis: RedisDatabase,
+    config: SchedulerConfig,
+    instance: String,
+}
+
+impl Scheduler {
+    pub fn new(redis: RedisDatabase, config: SchedulerConfig) -> Self {
+        Self {
+            redis,
+            config,
+            instance: format!("scheduler-{}", nanoid!(7)),
+        }
+    }
+
+    /// Defines a job, replacing whatever was defined under its name. Jobs are
+    /// defined before `Scheduler::start`.
+    ///
+    /// # Examples
+    /// ```
+    /// Scheduler::register(ScheduledJob::new("session_prune", Schedule::cron("0 0 * * * *"), || {
+    ///     SessionPruneTask {}.into_request()
+    /// }));
+    /// ```
+    pub fn register(job: ScheduledJob) {
+        JOBS.write().unwrap().insert(job.name.clone(), job);
+    }
+
+    /// Spawns a loop for every job, with the schedules of `TASK_SCHEDULES`
+    /// applied.
+    ///
+    /// # Examples
+    /// ```
+    /// Scheduler::new(redis, SchedulerConfig::default()).start();
+    /// ```
+    pub fn start(self) {
+        if !self.config.enabled {
+            println!("[SCHEDULER] disabled on this instance.");
+            return;
+        }
+        let scheduler = Arc::new(self);
+        for job in scheduler.jobs() {
+            println!("[SCHEDULER] job: {} scheduled ({}).", job.name, job.schedule);
+            tokio::spawn(Arc::clone(&scheduler).run_job(job));
+        }
+    }
+
+    /// The jobs defined in code, along with the ones defined in config.
+    fn jobs(&self) -> Vec<ScheduledJob> {
+        let mut jobs = JOBS.read().unwrap().clone();
+        for (name, schedule) in self.config.schedules.iter() {
+            if schedule.eq("off") {
+                jobs.remove(name);
+                continue;
+            }
+            let schedule = match schedule.parse::<Schedule>() {
+                Ok(schedule) => schedule,
+                Err(error) => {
+                    println!("[SCHEDULER] job: {} has an invalid schedule, {}.", name, error);
+                    continue;
+                }
+            };
+            match jobs.get_mut(name) {
+                Some(job) => job.schedule = schedule,
+                None => {
+                    let action = name.clone();
+                    if TaskRegistry::compose_request(&action).is_none() {
+                        println!("[SCHEDULER] job: {} is not a job or a registered task.", name);
+                        continue;
+                    }
+                    let job = ScheduledJob::new(name, schedule, move || {
+                        TaskRegistry::compose_request(&action).unwrap()
+                    });
+                    jobs.insert(name.clone(), job);
+                }
+            }
+        }
+        jobs.into_values().collect()
+    }
+
+    async fn run_job(self: Arc<Self>, job: ScheduledJob) {
+        let mut after = Utc::now();
+        while let Some(fire_at) = job.schedule.next_after(after) {
+            let wait = (fire_at - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+            tokio::time::sleep(wait).await;
+            // the lock outlives the fire by the gap to the next one, so it is
+            // still held when an instance with a late clock tries it.
+            let lock_for = job
+                .schedule
+                .next_after(fire_at)
+                .map_or(Duration::from_secs(60), |next| {
+                    (next - fire_at).to_std().unwrap_or(Duration::from_secs(60))
+                });
+            if job.per_instance || self.acquire(&job, fire_at, lock_for).await {
+                let task_request = job.compose_request();
+                let task_id = task_request.task_id.clone();
+                match TaskManager::process_task(task_request).await {
+                    Ok(_) => println!("[SCHEDULER] job: {} completed as {}.", job.name, task_id),
+                    Err(error) => println!(
+                        "[SCHEDULER] job: {} failed as {}, {}.",
+                        job.name, task_id, error
+                    ),
+                }
+            }
+            // fires missed while the job ran are skipped.
+            after = Utc::now().max(fire_at);
+        }
+    }
+
+    /// Whether this instance won the fire, an instance that cannot reach
+    /// Redis skips it.
+    async fn acquire(&self, job: &ScheduledJob, fire_at: DateTime<Utc>, lock_for: Duration) -> bool {
+        let mut pool = match self.redis.pool.get().await {
+            Ok(pool) => pool,
+            Err(error) => {
+                println!("[SCHEDULER] job: {} skipped, {}.", job.name, error);
+                return false;
+            }
+        };
+        let lock_key = format!("ark:schedule:{}:{}", job.name, fire_at.timestamp_millis());
+        let acquired: Result<Option<String>, RedisError> = Cmd::new()
+            .arg("SET")
+            .arg(&lock_key)
+            .arg(&self.instance)
+            .arg("NX")
+            .arg("PX")
+            .arg(lock_for.as_millis().max(1000) as u64)
+            .query_async(&mut *pool)
+            .await;
+        match acquired {
+            Ok(reply) => reply.is_some(),
+            Err(error) => {
+                println!("[SCHEDULER] job: {} skipped, {}.", job.name, error);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc)
+    }
+
+    fn interval(schedule: &str) -> Duration {
+        match schedule.parse::<Schedule>().unwrap() {
+            Schedule::Every(interval) => interval,
+            Schedule::Cron(_) => panic!("{} parsed as cron", schedule),
+        }
+    }
+
+    #[test]
+    fn parses_every_interval_unit() {
+        assert_eq!(interval("@every 30s"), Duration::from_secs(30));
+        assert_eq!(interval("@every 10m"), Duration::from_secs(600));
+        assert_eq!(interval("@every 2h"), Duration::from_secs(7200));
+        assert_eq!(interval("@every 1d"), Duration::from_secs(86_400));
+        assert_eq!(interval("  @every   5m  "), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn raises_intervals_shorter_than_a_second() {
+        assert_eq!(interval("@every 0s"), Duration::from_secs(1));
+        assert_eq!(
+            Schedule::every(Duration::from_millis(10)).to_string(),
+            "@every 1s"
+        );
+    }
+
+    #[test]
+    fn refuses_invalid_intervals() {
+        for schedule in [
+            "@every",
+            "@every 10",
+            "@every m",
+            "@every 10w",
+            "@every -5m",
+            "@every 1.5h",
+            "@every 99999999999999999999d",
+            "@every 999999999999999999d",
+        ] {
+            assert!(schedule.parse::<Schedule>().is_err(), "{} parsed", schedule);
+        }
+    }
+
+    #[test]
+    fn parses_cron_expressions_with_a_seconds_field() {
+        assert!(matches!(
+            "0 */15 * * * *".parse::<Schedule>(),
+            Ok(Schedule::Cron(_))
+        ));
+        assert!(matches!(Schedule::cron("0 0 3 * * *"), Schedule::Cron(_)));
+    }
+
+    #[test]
+    fn refuses_invalid_cron_expressions() {
+        for schedule in ["", "not a schedule", "0 61 * * * *", "0 0 25 * * *"] {
+            assert!(schedule.parse::<Schedule>().is_err(), "{} parsed", schedule);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid cron expression")]
+    fn panics_on_an_invalid_cron_written_in_code() {
+        Schedule::cron("every day");
+    }
+
+    #[test]
+    fn every_fires_on_multiples_of_the_interval_from_the_epoch() {
+        let schedule = Schedule::every(Duration::from_secs(600));
+        assert_eq!(
+            schedule.next_after(at("2024-01-01T10:03:00Z")),
+            Some(at("2024-01-01T10:10:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(at("2024-01-01T10:59:59.999Z")),
+            Some(at("2024-01-01T11:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn every_fires_after_a_time_that_is_a_fire_time() {
+        let schedule = Schedule::every(Duration::from_secs(600));
+        assert_eq!(
+            schedule.next_after(at("2024-01-01T10:10:00Z")),
+            Some(at("2024-01-01T10:20:00Z"))
+        );
+    }
+
+    #[test]
+    fn cron_fires_on_the_next_matching_time() {
+        let schedule = Schedule::cron("0 */15 * * * *");
+        assert_eq!(
+            schedule.next_after(at("2024-01-01T10:03:00Z")),
+            Some(at("2024-01-01T10:15:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(at("2024-01-01T10:15:00Z")),
+            Some(at("2024-01-01T10:30:00Z"))
+        );
+        let daily = Schedule::cron("0 0 3 * * *");
+        assert_eq!(
+            daily.next_after(at("2024-02-28T04:00:00Z")),
+            Some(at("2024-02-29T03:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn displays_every_as_it_is_parsed() {
+        let schedule: Schedule = "@every 10m".parse().unwrap();
+        assert_eq!(schedule.to_string(), "@every 600s");
+        assert_eq!(interval(&schedule.to_string()), Duration::from_secs(600));
+    }
+}
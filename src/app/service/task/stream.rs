@@ -0,0 +1,577 @@
+//
+// Redis Streams Transport
+//
+// +---------------------+       +---------------------+       +---------------------+
+// | API Node            |       | Task Stream         |       | Worker              |
+// | XADD TaskRequest    | ----> | Consumer Group      | ----> | XREADGROUP, Runs    |
+// +----------+----------+       +---------------------+       | the Task, XACK      |
+//            ^                                                +----------+----------+
+//            |                  +---------------------+                  |
+//            +----------------- | Reply Stream        | <----------------+
+//              XREAD Response   | of the API Node     |   XADD TaskResponse
+//                               +---------------------+
+//
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::async_trait;
+use bb8::RunError;
+use bb8_redis::redis::{
+    streams::{
+        StreamClaimReply, StreamId, StreamPendingCountReply, StreamRangeReply, StreamReadOptions,
+        StreamReadReply,
+    },
+    AsyncCommands, ErrorKind, RedisError, RedisResult,
+};
+use nanoid::nanoid;
+use tokio::sync::{oneshot::Sender, Semaphore};
+
+use crate::app::database::redis::RedisDatabase;
+
+use super::{
+    dead_letter::DeadLetters,
+    error::TaskError,
+    manager::TaskManager,
+    message::{TaskRequest, TaskResponse, TaskType},
+    transport::TaskTransport,
+};
+
+/// The field of a stream entry holding a `TaskRequest`.
+static REQUEST_FIELD: &str = "task";
+
+/// The field of a stream entry holding a `TaskResponse`.
+static RESPONSE_FIELD: &str = "response";
+
+/// How long a reply stream outlives its last reply, or the API node reading
+/// it, in seconds.
+static REPLY_TTL_SECONDS: i64 = 3600;
+
+/// The consumer group an API node reads its reply stream through.
+static REPLY_GROUP: &str = "ark-api";
+
+/// How many times a request is handed to a worker before it is given up on.
+static MAX_DELIVERIES: usize = 5;
+
+/// Where and how tasks travel through Redis Streams.
+///
+/// # Fields
+///
+/// * `stream` - The stream requests are added to.
+/// * `group` - The consumer group every worker reads the stream through.
+/// * `consumer` - The name of this worker inside of the group.
+/// * `node` - The name of this API node, its replies are read from `{stream}:replies:{node}`.
+/// * `remote_types` - The `TaskType`s sent to workers, every other type runs in process.
+/// * `in_flight` - How many requests a worker runs at the same time.
+/// * `claim_idle` - How long a request stays unacknowledged before another worker takes it over.
+/// * `block` - How long a read waits for new entries.
+pub struct RedisStreamConfig {
+    pub stream: String,
+    pub group: String,
+    pub consumer: String,
+    pub node: String,
+    pub remote_types: Vec<TaskType>,
+    pub in_flight: usize,
+    pub claim_idle: Duration,
+    pub block: Duration,
+}
+
+impl Default for RedisStreamConfig {
+    /// Reads `TASK_STREAM` (default `ark:tasks`), `TASK_STREAM_GROUP` (default
+    /// `ark-workers`), `TASK_STREAM_CONSUMER` (default a random name),
+    /// `TASK_STREAM_NODE` (default `HOSTNAME`, otherwise a random name),
+    /// `TASK_REMOTE_TYPES` ex: `User,Report`, `TASK_STREAM_IN_FLIGHT` (default 32)
+    /// and `TASK_STREAM_CLAIM_IDLE_MS` (default 60000).
+    fn default() -> Self {
+        let remote_types = env::var("TASK_REMOTE_TYPES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(Self::task_type_from_name)
+            .collect();
+        Self {
+            stream: env::var("TASK_STREAM").unwrap_or(String::from("ark:tasks")),
+            group: env::var("TASK_STREAM_GROUP").unwrap_or(String::from("ark-workers")),
+            consumer: env::var("TASK_STREAM_CONSUMER").unwrap_or(format!("worker-{}", nanoid!(7))),
+            node: env::var("TASK_STREAM_NODE")
+                .or_else(|_| env::var("HOSTNAME"))
+                .ok()
+                .filter(|node| !node.is_empty())
+                .unwrap_or(format!("api-{}", nanoid!(7))),
+            remote_types,
+            in_flight: Self::from_env("TASK_STREAM_IN_FLIGHT").unwrap_or(32).max(1),
+            claim_idle: Duration::from_millis(
+                Self::from_env("TASK_STREAM_CLAIM_IDLE_MS").unwrap_or(60_000),
+            ),
+            block: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RedisStreamConfig {
+    /// Sends tasks of a `TaskType` to workers.
+    ///
+    /// # Arguments
+    /// - `task_type`: the type to send.
+    ///
+    /// # Examples
+    /// ```
+    /// RedisStreamConfig::default().remote_type(TaskType::Custom(String::from("Report")));
+    /// ```
+    pub fn remote_type(mut self, task_type: TaskType) -> Self {
+        if !self.remote_types.contains(&task_type) {
+            self.remote_types.push(task_type);
+        }
+        self
+    }
+
+    fn task_type_from_name(name: &str) -> TaskType {
+        match name {
+            "Permission" => TaskType::Permission,
+            "Role" => TaskType::Role,
+            "User" => TaskType::User,
+            "Session" => TaskType::Session,
+            "Batch" => TaskType::Batch,
+            custom => TaskType::Custom(String::from(custom)),
+        }
+    }
+
+    fn from_env<T: std::str::FromStr>(key: &str) -> Option<T> {
+        env::var(key).ok().and_then(|value| value.parse::<T>().ok())
+    }
+}
+
+/// Sends the requests of `remote_types` to workers through `stream`, and
+/// routes their replies, written to the reply stream of this node, back to
+/// the callers.
+///
+/// The reply stream is named after `node`, so a node that restarts under the
+/// same name picks up the replies of the tasks still running on workers, and
+/// acknowledges the ones nobody waits for anymore. The stream of a node that
+/// stopped for good expires `REPLY_TTL_SECONDS` after its last reply.
+pub struct RedisStreamTransport {
+    redis: RedisDatabase,
+    config: RedisStreamConfig,
+    reply_stream: String,
+    pending: Arc<Mutex<HashMap<String, Sender<TaskResponse>>>>,
+}
+
+impl RedisStreamTransport {
+    pub fn new(redis: RedisDatabase, config: RedisStreamConfig) -> Self {
+        Self {
+            reply_stream: format!("{}:replies:{}", config.stream, config.node),
+            redis,
+            config,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Starts reading the replies of the workers.
+    ///
+    /// # Examples
+    /// ```
+    /// let transport = RedisStreamTransport::new(redis, RedisStreamConfig::default());
+    /// transport.listen();
+    /// TaskManager::use_transport(transport);
+    /// ```
+    pub fn listen(&self) {
+        let redis = self.redis.clone();
+        let reply_stream = self.reply_stream.clone();
+        let node = self.config.node.clone();
+        let pending = self.pending.clone();
+        let options = StreamReadOptions::default()
+            .group(REPLY_GROUP, &node)
+            .block(self.config.block.as_millis() as usize);
+        tokio::spawn(async move {
+            create_group(&redis, &reply_stream, REPLY_GROUP).await;
+            println!(
+                "[ARK] Task transport initialized, now listening to replies on {}.",
+                reply_stream
+            );
+            // the replies read but not acknowledged before a restart come first.
+            let mut last_id = "0";
+            loop {
+                match read_stream(&redis, &reply_stream, last_id, &options).await {
+                    Ok(entries) => {
+                        if entries.is_empty() {
+                            last_id = ">";
+                        }
+                        let ids: Vec<String> =
+                            entries.iter().map(|entry| entry.id.clone()).collect();
+                        for entry in &entries {
+                            Self::deliver_reply(&pending, entry);
+                        }
+                        if !ids.is_empty() {
+                            let _ = acknowledge(&redis, &reply_stream, REPLY_GROUP, &ids).await;
+                        }
+                        // the stream of a node that stopped expires on its own.
+                        let _ = expire(&redis, &reply_stream, REPLY_TTL_SECONDS).await;
+                    }
+                    // the stream expired while nothing was replied, or was never made.
+                    Err(error) if error.code() == Some("NOGROUP") => {
+                        create_group(&redis, &reply_stream, REPLY_GROUP).await;
+                    }
+                    Err(error) => {
+                        println!(
+                            "[TASK] Failed to read replies from {}. Error: {}",
+                            reply_stream, error
+                        );
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Hands a reply to the caller waiting for it, if there still is one.
+    fn deliver_reply(pending: &Mutex<HashMap<String, Sender<TaskResponse>>>, entry: &StreamId) {
+        let task_response = match entry
+            .get::<String>(RESPONSE_FIELD)
+            .and_then(|response| serde_json::from_str::<TaskResponse>(&response).ok())
+        {
+            Some(task_response) => task_response,
+            None => return,
+        };
+        match pending.lock().unwrap().remove(&task_response.task_id) {
+            Some(task_reply) => {
+                let _ = task_reply.send(task_response);
+            }
+            None => println!(
+                "[TASK] Dropping the reply to task {}, its caller stopped waiting.",
+                task_response.task_id
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl TaskTransport for RedisStreamTransport {
+    fn handles(&self, task_request: &TaskRequest) -> bool {
+        self.config.remote_types.contains(&task_request.task_type)
+    }
+
+    async fn send(&self, mut task_request: TaskRequest) {
+        let task_id = task_request.task_id.clone();
+        task_request.task_reply_to = Some(self.reply_stream.clone());
+        if let Some(task_reply) = task_request.task_reply.take() {
+            let mut pending = self.pending.lock().unwrap();
+            // forget the callers that stopped waiting.
+            pending.retain(|_, task_reply| !task_reply.is_closed());
+            pending.insert(task_id.clone(), task_reply);
+        }
+        let payload = serde_json::to_string(&task_request).unwrap();
+        if let Err(error) =
+            add_to_stream(&self.redis, &self.config.stream, REQUEST_FIELD, &payload).await
+        {
+            println!(
+                "[TASK] Failed to send task {} to {}. Error: {}",
+                task_id, self.config.stream, error
+            );
+            if let Some(task_reply) = self.pending.lock().unwrap().remove(&task_id) {
+                let _ = task_reply.send(TaskResponse::throw_failed_response(
+                    task_request,
+                    vec![TaskError::TransientFailure.to_string()],
+                ));
+            }
+        }
+    }
+}
+
+/// Takes requests out of `stream` as part of `group`, runs them on the
+/// `TaskManager` of this process and writes the responses to the reply
+/// stream of each request.
+///
+/// Requests left unacknowledged by a worker that stopped are taken over
+/// after `claim_idle`, so a task runs at least once, and may run twice.
+pub struct RedisStreamWorker {
+    redis: RedisDatabase,
+    config: RedisStreamConfig,
+}
+
+impl RedisStreamWorker {
+    pub fn new(redis: RedisDatabase, config: RedisStreamConfig) -> Self {
+        Self { redis, config }
+    }
+
+    /// Runs requests until the process stops.
+    ///
+    /// # Examples
+    /// ```
+    /// RedisStreamWorker::new(redis, RedisStreamConfig::default()).run().await;
+    /// ```
+    pub async fn run(self) {
+        create_group(&self.redis, &self.config.stream, &self.config.group).await;
+        println!(
+            "[ARK] Task worker {} initialized, now reading {} as part of {}.",
+            self.config.consumer, self.config.stream, self.config.group
+        );
+        let in_flight = Arc::new(Semaphore::new(self.config.in_flight));
+        let worker = Arc::new(self);
+        let mut last_claim: Option<Instant> = None;
+        loop {
+            let mut entries = Vec::new();
+            if last_claim.is_none_or(|claimed| claimed.elapsed() >= worker.config.claim_idle) {
+                entries.extend(worker.claim_stale().await);
+                last_claim = Some(Instant::now());
+            }
+            match worker.read_new(in_flight.available_permits().max(1)).await {
+                Ok(new_entries) => entries.extend(new_entries),
+                Err(error) => {
+                    println!(
+                        "[TASK] Failed to read tasks from {}. Error: {}",
+                        worker.config.stream, error
+                    );
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+            for entry in entries {
+                let permit = in_flight.clone().acquire_owned().await.unwrap();
+                let worker = worker.clone();
+                tokio::spawn(async move {
+                    let _permit = permit;
+                    worker.process_entry(entry).await;
+                });
+            }
+        }
+    }
+
+    /// Reads requests no worker of the group has seen yet.
+    async fn read_new(&self, count: usize) -> RedisResult<Vec<StreamId>> {
+        let options = StreamReadOptions::default()
+            .group(&self.config.group, &self.config.consumer)
+            .block(self.config.block.as_millis() as usize)
+            .count(count);
+        read_stream(&self.redis, &self.config.stream, ">", &options).await
+    }
+
+    /// Takes over the requests other workers left unacknowledged for longer
+    /// than `claim_idle`, giving up on those delivered `MAX_DELIVERIES` times.
+    async fn claim_stale(&self) -> Vec<StreamId> {
+        let claim_idle = self.config.claim_idle.as_millis() as usize;
+        let result: RedisResult<Vec<StreamId>> = async {
+            let mut connection = self.redis.pool.get().await.map_err(from_run_error)?;
+            let pending: StreamPendingCountReply = connection
+                .xpending_count(&self.config.stream, &self.config.group, "-", "+", 100)
+                .await?;
+            let (abandoned, stale): (Vec<_>, Vec<_>) = pending
+                .ids
+                .into_iter()
+                .filter(|pending| pending.last_delivered_ms >= claim_idle)
+                .partition(|pending| pending.times_delivered >= MAX_DELIVERIES);
+            for pending in abandoned {
+                println!(
+                    "[TASK] Giving up on entry {} of {} after {} deliveries.",
+                    pending.id, self.config.stream, pending.times_delivered
+                );
+                self.keep_abandoned(&pending.id).await;
+                self.acknowledge(&pending.id).await;
+            }
+            if stale.is_empty() {
+                return Ok(Vec::new());
+            }
+            let ids: Vec<String> = stale.into_iter().map(|pending| pending.id).collect();
+            let claimed: StreamClaimReply = connection
+                .xclaim(
+                    &self.config.stream,
+                    &self.config.group,
+                    &self.config.consumer,
+                    claim_idle,
+                    &ids,
+                )
+                .await?;
+            Ok(claimed.ids)
+        }
+        .await;
+        result.unwrap_or_else(|error| {
+            println!(
+                "[TASK] Failed to claim stale tasks of {}. Error: {}",
+                self.config.stream, error
+            );
+            Vec::new()
+        })
+    }
+
+    /// Keeps the request of an entry that is given up on as a dead letter.
+    async fn keep_abandoned(&self, id: &str) {
+        let entry: RedisResult<StreamRangeReply> = async {
+            let mut connection = self.redis.pool.get().await.map_err(from_run_error)?;
+            connection.xrange(&self.config.stream, id, id).await
+        }
+        .await;
+        let task_request = entry.ok().and_then(|entry| {
+            entry
+                .ids
+                .first()?
+                .get::<String>(REQUEST_FIELD)
+                .and_then(|request| serde_json::from_str::<TaskRequest>(&request).ok())
+        });
+        if let Some(task_request) = task_request {
+            DeadLetters::record(&task_request, &[TaskError::TaskAbandoned.to_string()]).await;
+        }
+    }
+
+    /// Runs the request of an entry, replies to its caller then acknowledges it.
+    async fn process_entry(&self, entry: StreamId) {
+        let task_request = match entry
+            .get::<String>(REQUEST_FIELD)
+            .and_then(|request| serde_json::from_str::<TaskRequest>(&request).ok())
+        {
+            Some(task_request) => task_request,
+            None => {
+                println!(
+                    "[TASK] Dropping entry {} of {}, it does not hold a task.",
+                    entry.id, self.config.stream
+                );
+                self.acknowledge(&entry.id).await;
+                return;
+            }
+        };
+        let reply_to = task_request.task_reply_to.clone();
+        let task_response = TaskManager::execute_locally(task_request).await;
+        if let Some(reply_to) = reply_to {
+            if let Err(error) = self.reply(&reply_to, &task_response).await {
+                println!(
+                    "[TASK] Failed to reply to task {} on {}. Error: {}",
+                    task_response.task_id, reply_to, error
+                );
+            }
+        }
+        self.acknowledge(&entry.id).await;
+    }
+
+    async fn reply(&self, reply_to: &str, task_response: &TaskResponse) -> RedisResult<()> {
+        let payload = serde_json::to_string(task_response).unwrap();
+        add_to_stream(&self.redis, reply_to, RESPONSE_FIELD, &payload).await?;
+        expire(&self.redis, reply_to, REPLY_TTL_SECONDS).await
+    }
+
+    /// Acknowledges then removes an entry, it is never handed out again.
+    async fn acknowledge(&self, id: &str) {
+        let result = acknowledge(&self.redis, &self.config.stream, &self.config.group, &[id]).await;
+        if let Err(error) = result {
+            println!(
+                "[TASK] Failed to acknowledge entry {} of {}. Error: {}",
+                id, self.config.stream, error
+            );
+        }
+    }
+}
+
+/// Creates a consumer group, and its stream, unless they already exist.
+async fn create_group(redis: &RedisDatabase, stream: &str, group: &str) {
+    let result: RedisResult<()> = match redis.pool.get().await {
+        Ok(mut connection) => connection.xgroup_create_mkstream(stream, group, "0").await,
+        Err(error) => Err(from_run_error(error)),
+    };
+    if let Err(error) = result {
+        if error.code() != Some("BUSYGROUP") {
+            println!(
+                "[TASK] Failed to create the group {} of {}. Error: {}",
+                group, stream, error
+            );
+        }
+    }
+}
+
+/// Acknowledges then removes entries of a group.
+async fn acknowledge<I: AsRef<str>>(
+    redis: &RedisDatabase,
+    stream: &str,
+    group: &str,
+    ids: &[I],
+) -> RedisResult<()> {
+    let ids: Vec<&str> = ids.iter().map(|id| id.as_ref()).collect();
+    let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+    let _: usize = connection.xack(stream, group, &ids).await?;
+    delete_from_stream(redis, stream, &ids).await
+}
+
+async fn expire(redis: &RedisDatabase, key: &str, seconds: i64) -> RedisResult<()> {
+    let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+    connection.expire(key, seconds).await
+}
+
+async fn add_to_stream(
+    redis: &RedisDatabase,
+    stream: &str,
+    field: &str,
+    value: &str,
+) -> RedisResult<()> {
+    let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+    connection.xadd(stream, "*", &[(field, value)]).await
+}
+
+async fn delete_from_stream<I: AsRef<str>>(
+    redis: &RedisDatabase,
+    stream: &str,
+    ids: &[I],
+) -> RedisResult<()> {
+    let ids: Vec<&str> = ids.iter().map(|id| id.as_ref()).collect();
+    let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+    connection.xdel(stream, &ids).await
+}
+
+pub(crate) async fn read_stream(
+    redis: &RedisDatabase,
+    stream: &str,
+    id: &str,
+    options: &StreamReadOptions,
+) -> RedisResult<Vec<StreamId>> {
+    let mut connection = redis.pool.get().await.map_err(from_run_error)?;
+    let reply: StreamReadReply = connection.xread_options(&[stream], &[id], options).await?;
+    Ok(reply.keys.into_iter().flat_map(|key| key.ids).collect())
+}
+
+pub(crate) fn from_run_error(error: RunError<RedisError>) -> RedisError {
+    match error {
+        RunError::User(error) => error,
+        RunError::TimedOut => {
+            RedisError::from((ErrorKind::IoError, "timed out waiting for a connection"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_type_by_name() {
+        let names = ["Permission", "Role", "User", "Session", "Batch", "Report"];
+        let task_types: Vec<TaskType> = names
+            .iter()
+            .map(|name| RedisStreamConfig::task_type_from_name(name))
+            .collect();
+        assert_eq!(
+            task_types,
+            vec![
+                TaskType::Permission,
+                TaskType::Role,
+                TaskType::User,
+                TaskType::Session,
+                TaskType::Batch,
+                TaskType::Custom(String::from("Report")),
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_a_type_once() {
+        let config = RedisStreamConfig::default()
+            .remote_type(TaskType::User)
+            .remote_type(TaskType::User);
+        assert_eq!(
+            config
+                .remote_types
+                .iter()
+                .filter(|task_type| **task_type == TaskType::User)
+                .count(),
+            1
+        );
+    }
+}
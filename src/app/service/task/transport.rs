@@ -0,0 +1,17 @@
+use axum::async_trait;
+
+use super::message::TaskRequest;
+
+/// Carries a `TaskRequest` to a worker outside of this process and its
+/// `TaskResponse` back, set with `TaskManager::use_transport`.
+///
+/// Requests the transport does not handle keep going through `INBOUND`.
+#[async_trait]
+pub trait TaskTransport: Send + Sync {
+    /// Whether the request is sent through the transport.
+    fn handles(&self, task_request: &TaskRequest) -> bool;
+
+    /// Hands the request over to a worker. The `task_reply` of the request is
+    /// answered once the worker replies, or dropped when it cannot be sent.
+    async fn send(&self, task_request: TaskRequest);
+}
@@ -0,0 +1,42 @@
+use super::{
+    ark::ArkServer,
+    database::{postgres::PostgresDatabase, redis::RedisDatabase},
+    platform::{iam::sync::IamCacheSync, maintenance::Maintenance},
+    service::task::stream::{RedisStreamConfig, RedisStreamWorker},
+};
+
+/// A process that only runs tasks, taken out of Redis Streams, so API nodes
+/// and workers scale separately.
+///
+/// # Example
+///
+/// ```
+/// ArkWorker::new(RedisStreamConfig::default()).run(pg, redis).await;
+/// ```
+pub struct ArkWorker {
+    config: RedisStreamConfig,
+}
+
+impl ArkWorker {
+    pub fn new(config: RedisStreamConfig) -> Self {
+        Self { config }
+    }
+
+    /// Registers the tasks, preloads the caches they read from, follows the
+    /// maintenance state and IAM cache changes of the API nodes, starts the
+    /// scheduler then runs requests until the process stops.
+    ///
+    /// # Arguments
+    ///
+    /// * `pg` - An instance of `PostgresDatabase` the tasks run against.
+    /// * `redis` - An instance of `RedisDatabase` the tasks and streams use.
+    pub async fn run(self, pg: PostgresDatabase, redis: RedisDatabase) {
+        println!("[ARK] mode: Worker");
+        Maintenance::share(redis.clone()).await;
+        IamCacheSync::share(redis.clone()).await;
+        ArkServer::register_listeners(pg, redis.clone()).await;
+        ArkServer::preload_necessities().await;
+        ArkServer::start_scheduler(redis.clone());
+        RedisStreamWorker::new(redis, self.config).run().await;
+    }
+}
@@ -0,0 +1,30 @@
+use std::{env, process};
+
+use ark::app::{
+    database::{
+        postgres::{PostgresConfig, PostgresDatabase},
+        redis::{RedisConfig, RedisDatabase},
+    },
+    replay::{ArkReplay, ReplayConfig},
+};
+
+#[tokio::main]
+async fn main() {
+    let Some(path) = env::args().nth(1) else {
+        println!("usage: ark-replay <recording.jsonl>");
+        process::exit(2);
+    };
+    let pg = PostgresDatabase::new(PostgresConfig::default()).await;
+    let redis = RedisDatabase::new(RedisConfig::default()).await;
+    match ArkReplay::new(ReplayConfig::default())
+        .run(pg, redis, &path)
+        .await
+    {
+        Ok(report) if report.differences.is_empty() => {}
+        Ok(_) => process::exit(1),
+        Err(error) => {
+            println!("[REPLAY] failed to read {}. Error: {}", path, error);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use ark::app::{
+    database::{
+        postgres::{PostgresConfig, PostgresDatabase},
+        redis::{RedisConfig, RedisDatabase},
+    },
+    service::task::stream::RedisStreamConfig,
+    worker::ArkWorker,
+};
+
+#[tokio::main]
+async fn main() {
+    let pg = PostgresDatabase::new(PostgresConfig::default()).await;
+    let redis = RedisDatabase::new(RedisConfig::default()).await;
+    ArkWorker::new(RedisStreamConfig::default())
+        .run(pg, redis)
+        .await;
+}
@@ -0,0 +1,4 @@
+// lets `#[derive(Task)]` name this crate `::ark` from inside of it too.
+extern crate self as ark;
+
+pub mod app;
@@ -1,4 +1,4 @@
-use app::{
+use ark::app::{
     ark::ArkServer,
     database::{
         postgres::{PostgresConfig, PostgresDatabase},
@@ -6,8 +6,6 @@ use app::{
     },
 };
 
-pub mod app;
-
 #[tokio::main]
 async fn main() {
     let ark = ArkServer::default().await;
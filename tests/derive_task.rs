@@ -0,0 +1,72 @@
+use ark::app::{
+    database::{postgres::PostgresDatabase, redis::RedisDatabase},
+    service::task::{
+        message::{TaskRequest, TaskResponse, TaskStatus, TaskType},
+        registry::TaskDefinition,
+        Task,
+    },
+};
+use ark_macros::Task;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+
+// `#[derive(Task)]` used from outside of ark, the way other crates use it.
+
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = "Report", action = "report_read", db = RedisDatabase, idempotent, read_only)]
+struct ReportReadTask {
+    report_id: String,
+}
+
+#[async_trait]
+impl Task<RedisDatabase, TaskRequest, ReportReadTask> for ReportReadTask {
+    async fn run(_: &RedisDatabase, request: TaskRequest, _: ReportReadTask) -> TaskResponse {
+        TaskResponse::compose_response(request, TaskStatus::Completed, (), Vec::new())
+    }
+}
+
+mod reexported {
+    pub use ark as platform;
+}
+
+#[derive(Serialize, Deserialize, Task)]
+#[task(task_type = User, action = "report_create", crate = "reexported::platform")]
+struct ReportCreateTask {
+    report_id: String,
+}
+
+#[async_trait]
+impl Task<PostgresDatabase, TaskRequest, ReportCreateTask> for ReportCreateTask {
+    async fn run(_: &PostgresDatabase, request: TaskRequest, _: ReportCreateTask) -> TaskResponse {
+        TaskResponse::compose_response(request, TaskStatus::Completed, (), Vec::new())
+    }
+}
+
+#[test]
+fn derives_the_definition_of_a_task() {
+    assert_eq!(ReportReadTask::ACTION, "report_read");
+    assert_eq!(
+        ReportReadTask::task_type(),
+        TaskType::Custom(String::from("Report"))
+    );
+    assert_eq!(
+        (ReportReadTask::IDEMPOTENT, ReportReadTask::READ_ONLY),
+        (true, true)
+    );
+
+    let task_request = ReportReadTask {
+        report_id: String::from("report"),
+    }
+    .into_request();
+    assert_eq!(task_request.task_action, "report_read");
+}
+
+#[test]
+fn derives_through_the_crate_override() {
+    assert_eq!(ReportCreateTask::ACTION, "report_create");
+    assert_eq!(ReportCreateTask::task_type(), TaskType::User);
+    assert_eq!(
+        (ReportCreateTask::IDEMPOTENT, ReportCreateTask::READ_ONLY),
+        (false, false)
+    );
+}